@@ -10,7 +10,7 @@
  */
 
 import { Ed25519KeyIdentity } from '@dfinity/identity';
-import { ChainGuardClient } from '@chainguarsdk/sdk';
+import { ChainGuardClient, describeError } from '@chainguarsdk/sdk';
 import { ConfigManager } from '../utils/config';
 import { DCAStrategy } from '../strategies/dca';
 import { RebalanceStrategy } from '../strategies/rebalance';
@@ -64,7 +64,7 @@ async function main() {
         console.log(`Transaction: ${smallTransfer.Executed.tx_hash[0]}\n`);
       }
     } else if ('Denied' in smallTransfer) {
-      console.log(`🚫 Transfer denied: ${smallTransfer.Denied.reason}\n`);
+      console.log(`🚫 Transfer denied: ${describeError(smallTransfer.Denied.error)}\n`);
     }
   } catch (error) {
     console.error('Error:', error);
@@ -101,7 +101,7 @@ async function main() {
       // const signed = await client.signRequest(pending.id);
       // console.log(`✅ Signature collected (${signed.collected_signatures.length}/${signed.required_signatures})\n`);
     } else if ('Denied' in largeTransfer) {
-      console.log(`🚫 Transfer denied: ${largeTransfer.Denied.reason}\n`);
+      console.log(`🚫 Transfer denied: ${describeError(largeTransfer.Denied.error)}\n`);
     }
   } catch (error) {
     console.error('Error:', error);
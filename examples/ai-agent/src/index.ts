@@ -185,6 +185,17 @@ export class ChainGuardAgent {
       const roles = await this.client.getRoles(this.client.getPrincipal());
       console.log(`Your Roles: ${roles.map(r => Object.keys(r)[0]).join(', ') || 'None'}`);
 
+      const session = await this.client.getSession(this.client.getPrincipal());
+      if ('Ok' in session && session.Ok.length > 0) {
+        const s = session.Ok[0];
+        const state = s.revoked ? 'Revoked' : Date.now() * 1_000_000 >= Number(s.expires_at) ? 'Expired' : 'Active';
+        console.log(`Agent Session: ${state}`);
+        console.log(`  Budget: ${s.total_budget - s.spent} of ${s.total_budget} ${s.budget_token[0] ?? '(re-mint required)'} remaining (max ${s.max_per_transaction}/tx)`);
+        console.log(`  Expires: ${new Date(Number(s.expires_at / 1_000_000n)).toISOString()}`);
+      } else {
+        console.log('Agent Session: None');
+      }
+
       const policies = await this.client.listPolicies();
       console.log(`Active Policies: ${policies.length}`);
 
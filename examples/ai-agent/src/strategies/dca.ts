@@ -5,7 +5,7 @@
  * regardless of price. This reduces the impact of volatility.
  */
 
-import { ChainGuardClient } from '@chainguarsdk/sdk';
+import { ChainGuardClient, describeError } from '@chainguarsdk/sdk';
 import { ConfigManager } from '../utils/config';
 import { ActionResult } from '@chainguarsdk/sdk';
 
@@ -94,15 +94,26 @@ export class DCAStrategy {
   async getHistory(limit: number = 10): Promise<any[]> {
     const logs = await this.client.getAuditLogs();
 
+    // Results are logged as separate ActionExecuted entries referencing the request
+    const results = new Map(
+      logs.flatMap(log => ('ActionExecuted' in log.event
+        ? [[log.event.ActionExecuted.audit_id, log.event.ActionExecuted.result] as const]
+        : []))
+    );
+
     return logs
-      .filter(log => log.action_type === 'swap')
+      .flatMap(log => ('ActionRequested' in log.event ? [{ log, action: log.event.ActionRequested }] : []))
+      .filter(({ action }) => action.action_type === 'swap')
       .slice(-limit)
-      .map(log => ({
-        timestamp: log.timestamp,
-        params: JSON.parse(log.action_params),
-        result: log.execution_result,
-        success: log.execution_result?.[0]?.success ?? false,
-      }));
+      .map(({ log, action }) => {
+        const result = action.execution_result[0] ?? results.get(log.id);
+        return {
+          timestamp: log.timestamp,
+          params: JSON.parse(action.action_params),
+          result: result ? [result] : [],
+          success: result?.success ?? false,
+        };
+      });
   }
 
   /**
@@ -178,7 +189,7 @@ export class DCAStrategy {
       console.log(`Expires at: ${new Date(Number(pending.expires_at) / 1000000).toISOString()}`);
     } else if ('Denied' in result) {
       console.log('🚫 DCA Execution Denied');
-      console.log(`Reason: ${result.Denied.reason}`);
+      console.log(`Reason: ${describeError(result.Denied.error)}`);
     }
 
     console.log('========================================\n');
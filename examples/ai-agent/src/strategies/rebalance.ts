@@ -5,7 +5,7 @@
  * Triggers when any asset deviates from target by more than threshold.
  */
 
-import { ChainGuardClient } from '@chainguarsdk/sdk';
+import { ChainGuardClient, describeError } from '@chainguarsdk/sdk';
 import { ConfigManager } from '../utils/config';
 import { ActionResult } from '@chainguarsdk/sdk';
 
@@ -259,7 +259,7 @@ export class RebalanceStrategy {
       const pending = result.PendingSignatures;
       console.log(`⏳ Swap pending approval (Request ID: ${pending.id})`);
     } else if ('Denied' in result) {
-      console.log(`🚫 Swap denied: ${result.Denied.reason}`);
+      console.log(`🚫 Swap denied: ${describeError(result.Denied.error)}`);
     }
   }
 }
@@ -1,78 +1,168 @@
 'use client';
 
 import { useEffect, useState } from 'react';
+import { Principal } from '@dfinity/principal';
+import type { AuditQueryOptions } from '@chainguarsdk/sdk';
 import { useChainGuard } from '@/lib/hooks/useChainGuard';
-import type { AuditEntry } from '@/lib/types/chainguard';
+import type {
+  ActionEvent,
+  AuditEntry,
+  AuditPage as AuditPageResult,
+  ExecutionResult,
+  PolicyDecision,
+} from '@/lib/types/chainguard';
+
+// Action requests carry a policy decision; every other event is a governance change
+function actionOf(log: AuditEntry): ActionEvent | null {
+  return 'ActionRequested' in log.event ? log.event.ActionRequested : null;
+}
+
+function eventKind(log: AuditEntry): string {
+  const action = actionOf(log);
+  return action ? action.action_type : Object.keys(log.event)[0];
+}
+
+const PAGE_SIZE = 25n;
 
 export default function AuditPage() {
-  const { getAuditLogs, loading, error } = useChainGuard();
-  const [auditLogs, setAuditLogs] = useState<AuditEntry[]>([]);
-  const [filteredLogs, setFilteredLogs] = useState<AuditEntry[]>([]);
+  const { getAuditPage, loading, error } = useChainGuard();
+  const [page, setPage] = useState<AuditPageResult | null>(null);
+  const [cursors, setCursors] = useState<(bigint | undefined)[]>([undefined]);
+  const [counts, setCounts] = useState({ total: 0n, allowed: 0n, denied: 0n, threshold: 0n });
   const [selectedEntry, setSelectedEntry] = useState<AuditEntry | null>(null);
   const [refreshing, setRefreshing] = useState(false);
 
-  // Filters
-  const [filters, setFilters] = useState({
+  // Filters (all but the search text are applied by the canister)
+  const emptyFilters = {
     actionType: 'all',
     decision: 'all',
-    dateFrom: '',
-    dateTo: '',
+    result: 'all',
+    requester: '',
+    chain: '',
+    token: '',
     searchText: '',
-  });
+  };
+  const [filters, setFilters] = useState(emptyFilters);
+  const { searchText, ...queryFilters } = filters;
+  const queryKey = JSON.stringify(queryFilters);
+
+  const auditLogs = page?.entries ?? [];
+
+  // Execution results are appended as ActionExecuted entries pointing at their request
+  const executions = new Map<bigint, ExecutionResult>();
+  for (const log of [...auditLogs, ...(page?.results ?? [])]) {
+    if ('ActionExecuted' in log.event) {
+      executions.set(log.event.ActionExecuted.audit_id, log.event.ActionExecuted.result);
+    }
+  }
+
+  function executionOf(log: AuditEntry): ExecutionResult | undefined {
+    const action = actionOf(log);
+    return action ? action.execution_result[0] ?? executions.get(log.id) : undefined;
+  }
 
   useEffect(() => {
-    loadAuditLogs();
+    loadCounts();
   }, [loading]);
 
+  // A filter change starts again from the newest page
   useEffect(() => {
-    applyFilters();
-  }, [auditLogs, filters]);
+    setCursors([undefined]);
+    loadPage(undefined);
+  }, [loading, queryKey]);
+
+  function queryOptions(cursor?: bigint): AuditQueryOptions {
+    let requester: Principal | undefined;
+    try {
+      requester = filters.requester.trim() ? Principal.fromText(filters.requester.trim()) : undefined;
+    } catch {
+      requester = undefined;
+    }
+    return {
+      cursor,
+      limit: PAGE_SIZE,
+      requester,
+      actionType: filters.actionType !== 'all' ? filters.actionType : undefined,
+      decision: filters.decision !== 'all' ? ({ [filters.decision]: null } as PolicyDecision) : undefined,
+      chain: filters.chain.trim() || undefined,
+      token: filters.token.trim() || undefined,
+      success: filters.result !== 'all' ? filters.result === 'success' : undefined,
+    };
+  }
 
-  async function loadAuditLogs() {
+  async function loadPage(cursor?: bigint) {
     if (!loading) {
       setRefreshing(true);
-      const logs = await getAuditLogs();
-      setAuditLogs(logs);
+      setPage(await getAuditPage(queryOptions(cursor)));
       setRefreshing(false);
     }
   }
 
-  function applyFilters() {
-    let filtered = [...auditLogs];
-
-    // Filter by action type
-    if (filters.actionType !== 'all') {
-      filtered = filtered.filter(log => log.action_type === filters.actionType);
-    }
-
-    // Filter by decision
-    if (filters.decision !== 'all') {
-      filtered = filtered.filter(log => {
-        if (filters.decision === 'Allowed' && 'Allowed' in log.policy_result.decision) return true;
-        if (filters.decision === 'Denied' && 'Denied' in log.policy_result.decision) return true;
-        if (filters.decision === 'RequiresThreshold' && 'RequiresThreshold' in log.policy_result.decision) return true;
-        return false;
+  // Totals only (limit 0), independent of the filters
+  async function loadCounts() {
+    if (!loading) {
+      const [all, allowed, denied, threshold] = await Promise.all([
+        getAuditPage({ limit: 0n }),
+        getAuditPage({ limit: 0n, decision: { Allowed: null } }),
+        getAuditPage({ limit: 0n, decision: { Denied: null } }),
+        getAuditPage({ limit: 0n, decision: { RequiresThreshold: null } }),
+      ]);
+      setCounts({
+        total: all?.total ?? 0n,
+        allowed: allowed?.total ?? 0n,
+        denied: denied?.total ?? 0n,
+        threshold: threshold?.total ?? 0n,
       });
     }
+  }
+
+  function refresh() {
+    loadCounts();
+    loadPage(cursors[cursors.length - 1]);
+  }
 
-    // Filter by search text
-    if (filters.searchText) {
-      const search = filters.searchText.toLowerCase();
-      filtered = filtered.filter(log =>
-        log.action_type.toLowerCase().includes(search) ||
-        log.action_params.toLowerCase().includes(search) ||
-        log.policy_result.reason.toLowerCase().includes(search)
-      );
+  function olderPage() {
+    const next = page?.next_cursor[0];
+    if (next !== undefined) {
+      setCursors([...cursors, next]);
+      loadPage(next);
     }
+  }
 
-    setFilteredLogs(filtered);
+  function newerPage() {
+    if (cursors.length > 1) {
+      const previous = cursors.slice(0, -1);
+      setCursors(previous);
+      loadPage(previous[previous.length - 1]);
+    }
   }
 
+  // Free-text search within the loaded page
+  const search = searchText.toLowerCase();
+  const filteredLogs = !search ? auditLogs : auditLogs.filter(log => {
+    const action = actionOf(log);
+    return eventKind(log).toLowerCase().includes(search) ||
+      log.caller.toText().toLowerCase().includes(search) ||
+      (action !== null && (
+        action.action_params.toLowerCase().includes(search) ||
+        action.policy_result.reason.toLowerCase().includes(search)
+      ));
+  });
+
   function formatTimestamp(timestamp: bigint): string {
     const date = new Date(Number(timestamp) / 1000000);
     return date.toLocaleString();
   }
 
+  // Before/after values of a governance event, principals as text
+  function formatEvent(log: AuditEntry): string {
+    return JSON.stringify(log.event, (_, v) => {
+      if (typeof v === 'bigint') return v.toString();
+      if (v && typeof v.toText === 'function') return v.toText();
+      return v;
+    }, 2);
+  }
+
   function formatPrincipal(p: any): string {
     const str = p?.toText ? p.toText() : String(p);
     return str.slice(0, 10) + '...' + str.slice(-8);
@@ -93,23 +183,26 @@ export default function AuditPage() {
   }
 
   function getActionTypeColor(actionType: string): string {
-    if (actionType === 'Transfer') return 'bg-[#3B00B9]/10 text-[#3B00B9]';
-    if (actionType === 'Swap') return 'bg-[#ED1E79]/10 text-[#ED1E79]';
-    if (actionType === 'ApproveToken') return 'bg-[#6A5ACD]/10 text-[#6A5ACD]';
+    if (actionType === 'transfer') return 'bg-[#3B00B9]/10 text-[#3B00B9]';
+    if (actionType === 'swap') return 'bg-[#ED1E79]/10 text-[#ED1E79]';
+    if (actionType === 'approve') return 'bg-[#6A5ACD]/10 text-[#6A5ACD]';
     return 'bg-gray-100 text-gray-700';
   }
 
   function exportToCSV() {
-    const headers = ['ID', 'Timestamp', 'Action Type', 'Decision', 'Requester', 'Reason', 'TX Hash'];
-    const rows = filteredLogs.map(log => [
-      log.id.toString(),
-      formatTimestamp(log.timestamp),
-      log.action_type,
-      getDecisionText(log.policy_result.decision),
-      log.requester.toText(),
-      log.policy_result.reason,
-      log.execution_result && log.execution_result[0]?.tx_hash ? log.execution_result[0].tx_hash[0] || '' : ''
-    ]);
+    const headers = ['ID', 'Timestamp', 'Event', 'Decision', 'Caller', 'Reason', 'TX Hash'];
+    const rows = filteredLogs.map(log => {
+      const action = actionOf(log);
+      return [
+        log.id.toString(),
+        formatTimestamp(log.timestamp),
+        eventKind(log),
+        action ? getDecisionText(action.policy_result.decision) : '',
+        log.caller.toText(),
+        action ? action.policy_result.reason : formatEvent(log),
+        executionOf(log)?.tx_hash[0] || ''
+      ];
+    });
 
     const csv = [headers, ...rows].map(row => row.map(cell => `"${cell}"`).join(',')).join('\n');
     const blob = new Blob([csv], { type: 'text/csv' });
@@ -145,9 +238,12 @@ export default function AuditPage() {
     );
   }
 
-  const allowedCount = auditLogs.filter(log => 'Allowed' in log.policy_result.decision).length;
-  const deniedCount = auditLogs.filter(log => 'Denied' in log.policy_result.decision).length;
-  const thresholdCount = auditLogs.filter(log => 'RequiresThreshold' in log.policy_result.decision).length;
+  const selectedAction = selectedEntry ? actionOf(selectedEntry) : null;
+  const selectedExecution = selectedEntry ? executionOf(selectedEntry) : undefined;
+
+  const allowedCount = counts.allowed.toString();
+  const deniedCount = counts.denied.toString();
+  const thresholdCount = counts.threshold.toString();
 
   return (
     <div className="min-h-screen bg-white">
@@ -175,7 +271,7 @@ export default function AuditPage() {
                 Export CSV
               </button>
               <button
-                onClick={loadAuditLogs}
+                onClick={refresh}
                 disabled={refreshing}
                 className="flex items-center gap-2 px-4 py-3 bg-[#3B00B9] text-white rounded-lg hover:bg-[#1B025A] transition disabled:opacity-50 font-semibold"
               >
@@ -197,7 +293,7 @@ export default function AuditPage() {
           <h2 className="text-3xl font-bold text-[#1B025A] mb-6">Activity Overview</h2>
           <div className="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-4 gap-6">
             <div className="border border-gray-200 rounded-xl p-6 bg-white hover:border-[#3B00B9] hover:shadow-sm transition-all">
-              <div className="text-4xl font-bold text-[#1B025A] mb-2">{auditLogs.length}</div>
+              <div className="text-4xl font-bold text-[#1B025A] mb-2">{counts.total.toString()}</div>
               <div className="text-sm font-semibold text-gray-900 mb-1">Total Logs</div>
               <div className="text-xs text-gray-500">All audit entries</div>
             </div>
@@ -232,9 +328,9 @@ export default function AuditPage() {
                   className="w-full px-4 py-3 border border-gray-200 rounded-lg focus:ring-2 focus:ring-[#3B00B9] focus:border-transparent bg-white text-gray-900"
                 >
                   <option value="all">All Types</option>
-                  <option value="Transfer">Transfer</option>
-                  <option value="Swap">Swap</option>
-                  <option value="ApproveToken">Approve Token</option>
+                  <option value="transfer">Transfer</option>
+                  <option value="swap">Swap</option>
+                  <option value="approve">Approve Token</option>
                 </select>
               </div>
 
@@ -252,20 +348,66 @@ export default function AuditPage() {
                 </select>
               </div>
 
+              <div>
+                <label className="block text-sm font-semibold text-gray-900 mb-2">Result</label>
+                <select
+                  value={filters.result}
+                  onChange={(e) => setFilters({ ...filters, result: e.target.value })}
+                  className="w-full px-4 py-3 border border-gray-200 rounded-lg focus:ring-2 focus:ring-[#3B00B9] focus:border-transparent bg-white text-gray-900"
+                >
+                  <option value="all">All Results</option>
+                  <option value="success">Success</option>
+                  <option value="failed">Failed</option>
+                </select>
+              </div>
+
+              <div>
+                <label className="block text-sm font-semibold text-gray-900 mb-2">Requester</label>
+                <input
+                  type="text"
+                  value={filters.requester}
+                  onChange={(e) => setFilters({ ...filters, requester: e.target.value })}
+                  placeholder="Principal ID"
+                  className="w-full px-4 py-3 border border-gray-200 rounded-lg focus:ring-2 focus:ring-[#3B00B9] focus:border-transparent bg-white text-gray-900"
+                />
+              </div>
+
+              <div>
+                <label className="block text-sm font-semibold text-gray-900 mb-2">Chain</label>
+                <input
+                  type="text"
+                  value={filters.chain}
+                  onChange={(e) => setFilters({ ...filters, chain: e.target.value })}
+                  placeholder="e.g. sepolia"
+                  className="w-full px-4 py-3 border border-gray-200 rounded-lg focus:ring-2 focus:ring-[#3B00B9] focus:border-transparent bg-white text-gray-900"
+                />
+              </div>
+
+              <div>
+                <label className="block text-sm font-semibold text-gray-900 mb-2">Token</label>
+                <input
+                  type="text"
+                  value={filters.token}
+                  onChange={(e) => setFilters({ ...filters, token: e.target.value })}
+                  placeholder="e.g. USDC"
+                  className="w-full px-4 py-3 border border-gray-200 rounded-lg focus:ring-2 focus:ring-[#3B00B9] focus:border-transparent bg-white text-gray-900"
+                />
+              </div>
+
               <div>
                 <label className="block text-sm font-semibold text-gray-900 mb-2">Search</label>
                 <input
                   type="text"
                   value={filters.searchText}
                   onChange={(e) => setFilters({ ...filters, searchText: e.target.value })}
-                  placeholder="Search logs..."
+                  placeholder="Search this page..."
                   className="w-full px-4 py-3 border border-gray-200 rounded-lg focus:ring-2 focus:ring-[#3B00B9] focus:border-transparent bg-white text-gray-900"
                 />
               </div>
 
               <div className="flex items-end">
                 <button
-                  onClick={() => setFilters({ actionType: 'all', decision: 'all', dateFrom: '', dateTo: '', searchText: '' })}
+                  onClick={() => setFilters(emptyFilters)}
                   className="w-full px-4 py-3 bg-gray-100 text-gray-700 font-semibold rounded-lg hover:bg-gray-200 transition border border-gray-200"
                 >
                   Clear Filters
@@ -278,7 +420,7 @@ export default function AuditPage() {
         {/* Logs Table */}
         <div>
           <h2 className="text-3xl font-bold text-[#1B025A] mb-6">
-            Audit Entries {filteredLogs.length !== auditLogs.length && `(${filteredLogs.length} of ${auditLogs.length})`}
+            Audit Entries {page && `(${page.total.toString()} matching)`}
           </h2>
 
           {filteredLogs.length === 0 ? (
@@ -298,14 +440,17 @@ export default function AuditPage() {
                       <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">ID</th>
                       <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Timestamp</th>
                       <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Type</th>
-                      <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Requester</th>
+                      <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Caller</th>
                       <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Decision</th>
                       <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Result</th>
                       <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Details</th>
                     </tr>
                   </thead>
                   <tbody>
-                    {filteredLogs.map((log) => (
+                    {filteredLogs.map((log) => {
+                      const action = actionOf(log);
+                      const execution = executionOf(log);
+                      return (
                       <tr key={log.id.toString()} className="border-b border-gray-100 hover:bg-gray-50 transition-colors">
                         <td className="py-4 px-6">
                           <span className="font-mono text-sm font-semibold text-gray-900">#{log.id.toString()}</span>
@@ -314,22 +459,26 @@ export default function AuditPage() {
                           <span className="text-sm text-gray-600">{formatTimestamp(log.timestamp)}</span>
                         </td>
                         <td className="py-4 px-6">
-                          <span className={`inline-block px-3 py-1 rounded-full text-xs font-semibold ${getActionTypeColor(log.action_type)}`}>
-                            {log.action_type}
+                          <span className={`inline-block px-3 py-1 rounded-full text-xs font-semibold ${getActionTypeColor(eventKind(log))}`}>
+                            {eventKind(log)}
                           </span>
                         </td>
                         <td className="py-4 px-6">
-                          <span className="font-mono text-xs text-gray-600">{formatPrincipal(log.requester)}</span>
+                          <span className="font-mono text-xs text-gray-600">{formatPrincipal(log.caller)}</span>
                         </td>
                         <td className="py-4 px-6">
-                          <span className={`inline-block px-3 py-1 rounded-full text-xs font-semibold ${getDecisionColor(log.policy_result.decision)}`}>
-                            {getDecisionText(log.policy_result.decision)}
-                          </span>
+                          {action ? (
+                            <span className={`inline-block px-3 py-1 rounded-full text-xs font-semibold ${getDecisionColor(action.policy_result.decision)}`}>
+                              {getDecisionText(action.policy_result.decision)}
+                            </span>
+                          ) : (
+                            <span className="text-gray-400 text-xs">N/A</span>
+                          )}
                         </td>
                         <td className="py-4 px-6">
-                          {log.execution_result && log.execution_result[0] ? (
+                          {execution ? (
                             <div>
-                              {log.execution_result[0].success ? (
+                              {execution.success ? (
                                 <span className="inline-block px-3 py-1 rounded-full text-xs font-semibold bg-[#18C39F]/10 text-[#18C39F]">
                                   Success
                                 </span>
@@ -352,12 +501,32 @@ export default function AuditPage() {
                           </button>
                         </td>
                       </tr>
-                    ))}
+                      );
+                    })}
                   </tbody>
                 </table>
               </div>
             </div>
           )}
+
+          {/* Pagination */}
+          <div className="flex items-center justify-between mt-6">
+            <button
+              onClick={newerPage}
+              disabled={cursors.length <= 1 || refreshing}
+              className="px-4 py-2 text-sm font-semibold text-[#3B00B9] border border-gray-200 rounded-lg hover:bg-[#3B00B9]/5 transition disabled:opacity-50"
+            >
+              Newer
+            </button>
+            <span className="text-sm text-gray-600">Page {cursors.length}</span>
+            <button
+              onClick={olderPage}
+              disabled={!page || page.next_cursor.length === 0 || refreshing}
+              className="px-4 py-2 text-sm font-semibold text-[#3B00B9] border border-gray-200 rounded-lg hover:bg-[#3B00B9]/5 transition disabled:opacity-50"
+            >
+              Older
+            </button>
+          </div>
         </div>
       </div>
 
@@ -394,100 +563,113 @@ export default function AuditPage() {
                     <div className="text-sm font-medium text-gray-900">{formatTimestamp(selectedEntry.timestamp)}</div>
                   </div>
                   <div className="border border-gray-200 rounded-lg p-4 bg-white">
-                    <div className="text-xs font-semibold text-gray-500 mb-2">Action Type</div>
-                    <span className={`inline-block px-3 py-1 rounded-full text-xs font-semibold ${getActionTypeColor(selectedEntry.action_type)}`}>
-                      {selectedEntry.action_type}
+                    <div className="text-xs font-semibold text-gray-500 mb-2">Event</div>
+                    <span className={`inline-block px-3 py-1 rounded-full text-xs font-semibold ${getActionTypeColor(eventKind(selectedEntry))}`}>
+                      {eventKind(selectedEntry)}
                     </span>
                   </div>
                   <div className="border border-gray-200 rounded-lg p-4 bg-white col-span-2">
-                    <div className="text-xs font-semibold text-gray-500 mb-2">Requester</div>
-                    <div className="text-xs font-mono text-gray-900 break-all">{selectedEntry.requester.toText()}</div>
+                    <div className="text-xs font-semibold text-gray-500 mb-2">Caller</div>
+                    <div className="text-xs font-mono text-gray-900 break-all">{selectedEntry.caller.toText()}</div>
                   </div>
                 </div>
               </div>
 
-              {/* Action Parameters */}
-              <div>
-                <h3 className="text-lg font-bold text-gray-900 mb-3">Action Parameters</h3>
-                <div className="bg-gray-50 border border-gray-200 rounded-lg p-4">
-                  <pre className="font-mono text-xs text-gray-900 whitespace-pre-wrap break-all overflow-x-auto">
-                    {selectedEntry.action_params}
-                  </pre>
-                </div>
-              </div>
-
-              {/* Policy Result */}
-              <div>
-                <h3 className="text-lg font-bold text-gray-900 mb-3">Policy Evaluation</h3>
-                <div className="space-y-3">
-                  <div className="flex items-center gap-3">
-                    <span className="text-sm font-semibold text-gray-600">Decision:</span>
-                    <span className={`inline-block px-3 py-1 rounded-full text-xs font-semibold ${getDecisionColor(selectedEntry.policy_result.decision)}`}>
-                      {getDecisionText(selectedEntry.policy_result.decision)}
-                    </span>
-                  </div>
-                  {selectedEntry.policy_result.matched_policy[0] && (
-                    <div className="border border-[#3B00B9]/20 bg-[#3B00B9]/5 rounded-lg p-4">
-                      <div className="text-xs font-semibold text-[#3B00B9] mb-2">Matched Policy</div>
-                      <div className="text-sm text-gray-900">{selectedEntry.policy_result.matched_policy[0]}</div>
-                    </div>
-                  )}
-                  <div className="border border-gray-200 rounded-lg p-4 bg-white">
-                    <div className="text-xs font-semibold text-gray-500 mb-2">Reason</div>
-                    <div className="text-sm text-gray-900">{selectedEntry.policy_result.reason}</div>
+              {selectedAction ? (
+                <>
+                {/* Action Parameters */}
+                <div>
+                  <h3 className="text-lg font-bold text-gray-900 mb-3">Action Parameters</h3>
+                  <div className="bg-gray-50 border border-gray-200 rounded-lg p-4">
+                    <pre className="font-mono text-xs text-gray-900 whitespace-pre-wrap break-all overflow-x-auto">
+                      {selectedAction.action_params}
+                    </pre>
                   </div>
                 </div>
-              </div>
 
-              {/* Execution Result */}
-              {selectedEntry.execution_result && selectedEntry.execution_result[0] && (
+                {/* Policy Result */}
                 <div>
-                  <h3 className="text-lg font-bold text-gray-900 mb-3">Execution Result</h3>
+                  <h3 className="text-lg font-bold text-gray-900 mb-3">Policy Evaluation</h3>
                   <div className="space-y-3">
                     <div className="flex items-center gap-3">
-                      <span className="text-sm font-semibold text-gray-600">Status:</span>
-                      {selectedEntry.execution_result[0].success ? (
-                        <span className="inline-block px-3 py-1 rounded-full text-xs font-semibold bg-[#18C39F]/10 text-[#18C39F]">
-                          Success
-                        </span>
-                      ) : (
-                        <span className="inline-block px-3 py-1 rounded-full text-xs font-semibold bg-[#EF4444]/10 text-[#EF4444]">
-                          Failed
-                        </span>
-                      )}
+                      <span className="text-sm font-semibold text-gray-600">Decision:</span>
+                      <span className={`inline-block px-3 py-1 rounded-full text-xs font-semibold ${getDecisionColor(selectedAction.policy_result.decision)}`}>
+                        {getDecisionText(selectedAction.policy_result.decision)}
+                      </span>
                     </div>
+                    {selectedAction.policy_result.matched_policy[0] && (
+                      <div className="border border-[#3B00B9]/20 bg-[#3B00B9]/5 rounded-lg p-4">
+                        <div className="text-xs font-semibold text-[#3B00B9] mb-2">Matched Policy</div>
+                        <div className="text-sm text-gray-900">{selectedAction.policy_result.matched_policy[0]}</div>
+                      </div>
+                    )}
                     <div className="border border-gray-200 rounded-lg p-4 bg-white">
-                      <div className="text-xs font-semibold text-gray-500 mb-2">Chain</div>
-                      <div className="text-sm font-medium text-gray-900">{selectedEntry.execution_result[0].chain}</div>
+                      <div className="text-xs font-semibold text-gray-500 mb-2">Reason</div>
+                      <div className="text-sm text-gray-900">{selectedAction.policy_result.reason}</div>
                     </div>
-                    {selectedEntry.execution_result[0].tx_hash[0] && (
-                      <div className="border border-[#18C39F]/20 bg-[#18C39F]/5 rounded-lg p-4">
-                        <div className="text-xs font-semibold text-[#18C39F] mb-2">Transaction Hash</div>
-                        <a
-                          href={`https://sepolia.etherscan.io/tx/${selectedEntry.execution_result[0].tx_hash[0]}`}
-                          target="_blank"
-                          rel="noopener noreferrer"
-                          className="text-sm font-mono text-[#3B00B9] hover:text-[#1B025A] break-all hover:underline"
-                        >
-                          {selectedEntry.execution_result[0].tx_hash[0]}
-                        </a>
+                  </div>
+                </div>
+
+                {/* Execution Result */}
+                {selectedExecution && (
+                  <div>
+                    <h3 className="text-lg font-bold text-gray-900 mb-3">Execution Result</h3>
+                    <div className="space-y-3">
+                      <div className="flex items-center gap-3">
+                        <span className="text-sm font-semibold text-gray-600">Status:</span>
+                        {selectedExecution.success ? (
+                          <span className="inline-block px-3 py-1 rounded-full text-xs font-semibold bg-[#18C39F]/10 text-[#18C39F]">
+                            Success
+                          </span>
+                        ) : (
+                          <span className="inline-block px-3 py-1 rounded-full text-xs font-semibold bg-[#EF4444]/10 text-[#EF4444]">
+                            Failed
+                          </span>
+                        )}
                       </div>
-                    )}
-                    {selectedEntry.execution_result[0].error[0] && (
-                      <div className="border border-[#EF4444]/20 bg-[#EF4444]/5 rounded-lg p-4">
-                        <div className="text-xs font-semibold text-[#EF4444] mb-2">Error</div>
-                        <div className="text-sm text-gray-900">{selectedEntry.execution_result[0].error[0]}</div>
+                      <div className="border border-gray-200 rounded-lg p-4 bg-white">
+                        <div className="text-xs font-semibold text-gray-500 mb-2">Chain</div>
+                        <div className="text-sm font-medium text-gray-900">{selectedExecution.chain}</div>
                       </div>
-                    )}
+                      {selectedExecution.tx_hash[0] && (
+                        <div className="border border-[#18C39F]/20 bg-[#18C39F]/5 rounded-lg p-4">
+                          <div className="text-xs font-semibold text-[#18C39F] mb-2">Transaction Hash</div>
+                          <a
+                            href={`https://sepolia.etherscan.io/tx/${selectedExecution.tx_hash[0]}`}
+                            target="_blank"
+                            rel="noopener noreferrer"
+                            className="text-sm font-mono text-[#3B00B9] hover:text-[#1B025A] break-all hover:underline"
+                          >
+                            {selectedExecution.tx_hash[0]}
+                          </a>
+                        </div>
+                      )}
+                      {selectedExecution.error[0] && (
+                        <div className="border border-[#EF4444]/20 bg-[#EF4444]/5 rounded-lg p-4">
+                          <div className="text-xs font-semibold text-[#EF4444] mb-2">Error</div>
+                          <div className="text-sm text-gray-900">{selectedExecution.error[0]}</div>
+                        </div>
+                      )}
+                    </div>
                   </div>
-                </div>
-              )}
+                )}
 
-              {/* Threshold Request ID */}
-              {selectedEntry.threshold_request_id[0] && (
-                <div className="border border-[#F59E0B]/20 bg-[#F59E0B]/5 rounded-lg p-4">
-                  <div className="text-xs font-semibold text-[#F59E0B] mb-2">Threshold Request ID</div>
-                  <div className="text-sm font-mono text-gray-900">{selectedEntry.threshold_request_id[0].toString()}</div>
+                {/* Threshold Request ID */}
+                {selectedAction.threshold_request_id[0] && (
+                  <div className="border border-[#F59E0B]/20 bg-[#F59E0B]/5 rounded-lg p-4">
+                    <div className="text-xs font-semibold text-[#F59E0B] mb-2">Threshold Request ID</div>
+                    <div className="text-sm font-mono text-gray-900">{selectedAction.threshold_request_id[0].toString()}</div>
+                  </div>
+                )}
+                </>
+              ) : (
+                <div>
+                  <h3 className="text-lg font-bold text-gray-900 mb-3">Event Details</h3>
+                  <div className="bg-gray-50 border border-gray-200 rounded-lg p-4">
+                    <pre className="font-mono text-xs text-gray-900 whitespace-pre-wrap break-all overflow-x-auto">
+                      {formatEvent(selectedEntry)}
+                    </pre>
+                  </div>
                 </div>
               )}
 
@@ -12,7 +12,7 @@ export default function Dashboard() {
     getConfig,
     isPaused,
     getPendingRequests,
-    getAuditLogs,
+    getAuditPage,
     listPolicies
   } = useChainGuard();
 
@@ -26,18 +26,18 @@ export default function Dashboard() {
 
   useEffect(() => {
     async function loadData() {
-      const [config, paused, pending, logs, policies] = await Promise.all([
+      const [config, paused, pending, auditCount, policies] = await Promise.all([
         getConfig(),
         isPaused(),
         getPendingRequests(),
-        getAuditLogs(),
+        getAuditPage({ limit: 0n }),
         listPolicies(),
       ]);
 
       setSystemStatus(config);
       setStats({
         pendingRequests: pending.length,
-        totalActions: logs.length,
+        totalActions: auditCount ? Number(auditCount.total) : 0,
         activePolicies: policies.length,
         paused,
       });
@@ -46,7 +46,7 @@ export default function Dashboard() {
     if (!loading) {
       loadData();
     }
-  }, [loading, getConfig, isPaused, getPendingRequests, getAuditLogs, listPolicies]);
+  }, [loading, getConfig, isPaused, getPendingRequests, getAuditPage, listPolicies]);
 
   if (loading) {
     return (
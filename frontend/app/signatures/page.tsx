@@ -63,6 +63,7 @@ export default function SignaturesPage() {
     if ('Executed' in status) return 'bg-[#18C39F]/10 text-[#18C39F]';
     if ('Expired' in status) return 'bg-gray-100 text-gray-600';
     if ('Rejected' in status) return 'bg-[#EF4444]/10 text-[#EF4444]';
+    if ('Cancelled' in status) return 'bg-gray-100 text-gray-600';
     return 'bg-gray-100 text-gray-700';
   }
 
@@ -72,6 +73,7 @@ export default function SignaturesPage() {
     if ('Executed' in status) return 'Executed';
     if ('Expired' in status) return 'Expired';
     if ('Rejected' in status) return 'Rejected';
+    if ('Cancelled' in status) return 'Cancelled';
     return 'Unknown';
   }
 
@@ -187,7 +189,7 @@ export default function SignaturesPage() {
                       <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Action</th>
                       <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Requester</th>
                       <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Created</th>
-                      <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Signatures</th>
+                      <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Weight</th>
                       <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Status</th>
                       <th className="text-left py-4 px-6 text-sm font-bold text-gray-900">Actions</th>
                     </tr>
@@ -215,13 +217,13 @@ export default function SignaturesPage() {
                           <td className="py-4 px-6">
                             <div className="flex items-center gap-2">
                               <div className="text-sm font-bold text-gray-900">
-                                {request.collected_signatures.length}/{request.required_signatures}
+                                {request.current_weight.toString()}/{request.required_weight.toString()}
                               </div>
                               <div className="flex-1 bg-gray-200 rounded-full h-2 min-w-[60px]">
                                 <div
                                   className="bg-[#3B00B9] h-2 rounded-full transition-all"
                                   style={{
-                                    width: `${(request.collected_signatures.length / request.required_signatures) * 100}%`
+                                    width: `${Math.min(100, (Number(request.current_weight) / Number(request.required_weight)) * 100)}%`
                                   }}
                                 ></div>
                               </div>
@@ -330,7 +332,7 @@ export default function SignaturesPage() {
               {/* Signatures */}
               <div>
                 <h3 className="text-lg font-bold text-gray-900 mb-3">
-                  Collected Signatures ({selectedRequest.collected_signatures.length}/{selectedRequest.required_signatures})
+                  Collected Signatures (weight {selectedRequest.current_weight.toString()}/{selectedRequest.required_weight.toString()})
                 </h3>
                 <div className="space-y-2">
                   {selectedRequest.collected_signatures.map((sig, idx) => (
@@ -340,20 +342,46 @@ export default function SignaturesPage() {
                         <div className="text-xs font-mono text-gray-600 break-all">{sig.signer.toText()}</div>
                       </div>
                       <div className="text-xs text-gray-600 font-medium ml-4 text-right">
+                        <div className="font-semibold text-gray-900">Weight {sig.weight.toString()}</div>
                         {formatTimestamp(sig.signed_at)}
                       </div>
                     </div>
                   ))}
-                  {selectedRequest.collected_signatures.length < selectedRequest.required_signatures && (
+                  {selectedRequest.current_weight < selectedRequest.required_weight && (
                     <div className="border border-[#F59E0B]/20 bg-[#F59E0B]/5 rounded-lg p-4 text-center">
                       <div className="text-sm text-[#F59E0B] font-semibold">
-                        Waiting for {selectedRequest.required_signatures - selectedRequest.collected_signatures.length} more signature(s)
+                        Waiting for {(selectedRequest.required_weight - selectedRequest.current_weight).toString()} more signing weight
                       </div>
                     </div>
                   )}
                 </div>
               </div>
 
+              {/* Rejections */}
+              {selectedRequest.rejections.length > 0 && (
+                <div>
+                  <h3 className="text-lg font-bold text-gray-900 mb-3">
+                    Rejections ({selectedRequest.rejections.length}/{selectedRequest.required_rejections.toString()})
+                  </h3>
+                  <div className="space-y-2">
+                    {selectedRequest.rejections.map((rejection, idx) => (
+                      <div key={idx} className="flex items-center justify-between border border-[#EF4444]/20 bg-[#EF4444]/5 rounded-lg p-4">
+                        <div className="flex-1">
+                          <div className="text-xs font-semibold text-[#EF4444] mb-1">
+                            {rejection.veto ? 'Veto' : `Rejection ${idx + 1}`}
+                          </div>
+                          <div className="text-sm text-gray-900 mb-1">{rejection.reason}</div>
+                          <div className="text-xs font-mono text-gray-600 break-all">{rejection.signer.toText()}</div>
+                        </div>
+                        <div className="text-xs text-gray-600 font-medium ml-4 text-right">
+                          {formatTimestamp(rejection.rejected_at)}
+                        </div>
+                      </div>
+                    ))}
+                  </div>
+                </div>
+              )}
+
               {/* Action Buttons */}
               <div className="flex gap-3 pt-4">
                 <button
@@ -76,7 +76,8 @@ export default function StrategiesPage() {
         } else if ('PendingSignatures' in result) {
           alert('DCA requires threshold signatures. Request ID: ' + result.PendingSignatures.id);
         } else if ('Denied' in result) {
-          alert('DCA denied: ' + result.Denied.reason);
+          const error = result.Denied.error;
+          alert('DCA denied: ' + ('PolicyDenied' in error ? error.PolicyDenied.reason : Object.keys(error)[0]));
         }
       }
     } else {
@@ -6,6 +6,8 @@ import type {
   ActionResult,
   PendingRequest,
   AuditEntry,
+  AuditPage,
+  AuditQueryOptions,
   Policy,
   Action,
   ChainGuardConfig
@@ -92,6 +94,22 @@ export function useChainGuard() {
     }
   }, [client]);
 
+  // Get one filtered page of audit entries (newest first) with the total count
+  const getAuditPage = useCallback(async (options: AuditQueryOptions = {}): Promise<AuditPage | null> => {
+    if (!client) return null;
+    try {
+      const result = await client.getAuditPage(options);
+      if ('Err' in result) {
+        console.error('Get audit page failed:', result.Err);
+        return null;
+      }
+      return result.Ok;
+    } catch (err) {
+      console.error('Get audit page failed:', err);
+      return null;
+    }
+  }, [client]);
+
   // List all policies
   const listPolicies = useCallback(async (): Promise<Policy[]> => {
     if (!client) return [];
@@ -189,6 +207,7 @@ export function useChainGuard() {
     signRequest,
     // Audit & monitoring
     getAuditLogs,
+    getAuditPage,
     listPolicies,
     getConfig,
     isPaused,
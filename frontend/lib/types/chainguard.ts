@@ -5,7 +5,7 @@
 
 import { Principal } from '@dfinity/principal';
 
-export type Role = { Owner: null } | { Operator: null } | { Viewer: null };
+export type Role = { Owner: null } | { Operator: null } | { Viewer: null } | { Custom: string };
 
 export type Permission =
   | { Execute: null }
@@ -14,6 +14,44 @@ export type Permission =
   | { Sign: null }
   | { Emergency: null };
 
+export type ChainGuardError =
+  | { Unauthorized: null }
+  | { InsufficientPermissions: { required: Permission } }
+  | { RoleNotFound: { name: string } }
+  | { RoleAlreadyExists: { name: string } }
+  | { RoleInUse: { name: string } }
+  | { LastOwner: null }
+  | { OwnershipProposalNotFound: null }
+  | { NotInitialized: null }
+  | { AlreadyInitialized: null }
+  | { InvalidConfiguration: { reason: string } }
+  | { PolicyNotFound: { id: bigint } }
+  | { PolicyVersionNotFound: { version: bigint } }
+  | { PolicyEvaluationFailed: { reason: string } }
+  | { PolicyDenied: { policy_id: [] | [bigint]; reason: string } }
+  | { RequestNotFound: { id: bigint } }
+  | { RequestExpired: null }
+  | { RequestAlreadySigned: null }
+  | { RequestAlreadyRejected: null }
+  | { RequestNotApproved: null }
+  | { SignerNotEligible: { required_roles: Role[] } }
+  | { RequesterCannotSign: null }
+  | { DailyApprovalLimitReached: { limit: bigint } }
+  | { RequestTimelocked: { executable_at: bigint } }
+  | { InvalidRequestStatus: { expected: string; actual: string } }
+  | { SessionNotFound: null }
+  | { SessionExpired: null }
+  | { SessionRevoked: null }
+  | { SessionActionNotAllowed: { action_type: string } }
+  | { SessionChainNotAllowed: { chain: string } }
+  | { SessionTokenNotAllowed: { token: string } }
+  | { SessionTransactionLimitExceeded: { limit: bigint; requested: bigint } }
+  | { SessionBudgetExceeded: { remaining: bigint; requested: bigint } }
+  | { ExecutionFailed: { reason: string } }
+  | { ChainNotSupported: { chain: string } }
+  | { SystemPaused: null }
+  | { InternalError: { msg: string } };
+
 export interface SwapAction {
   chain: string;
   token_in: string;
@@ -52,11 +90,20 @@ export interface ExecutionResult {
 export type ActionResult =
   | { Executed: ExecutionResult }
   | { PendingSignatures: PendingRequest }
-  | { Denied: { reason: string } };
+  | { Denied: { error: ChainGuardError } };
 
 export interface Signature {
   signer: Principal;
   signed_at: bigint;
+  weight: bigint;  // Signer's vote weight when signing
+}
+
+// Vote weight of a principal or of every holder of a role (unweighted signers count 1)
+export type WeightTarget = { Principal: Principal } | { Role: Role };
+
+export interface SignerWeight {
+  target: WeightTarget;
+  weight: bigint;
 }
 
 export type RequestStatus =
@@ -64,7 +111,8 @@ export type RequestStatus =
   | { Approved: null }
   | { Executed: null }
   | { Expired: null }
-  | { Rejected: null };
+  | { Rejected: null }
+  | { Cancelled: null }; // Cancelled by an Emergency holder during its time-lock
 
 export interface PendingRequest {
   id: bigint;
@@ -75,6 +123,32 @@ export interface PendingRequest {
   required_signatures: number;
   collected_signatures: Signature[];
   status: RequestStatus;
+  required_weight: bigint;  // Approved once current_weight reaches this
+  current_weight: bigint;
+  rejections: Rejection[];
+  required_rejections: bigint;  // Rejected once this many signers reject (a veto rejects at once)
+  timelock: [] | [bigint];       // Nanoseconds between approval and execution
+  executable_at: [] | [bigint];  // Set on approval of a time-locked request
+  failed_at: [] | [bigint];      // Last failed time-locked execution; retry with executeRequest()
+}
+
+export interface Rejection {
+  signer: Principal;
+  reason: string;
+  rejected_at: bigint;
+  veto: boolean;
+}
+
+// Rejections needed to reject a request, and principals whose rejection alone does
+export interface RejectionConfig {
+  threshold: bigint;
+  veto_principals: Principal[];
+}
+
+// Separation of duties between requesters and signers
+export interface SigningRules {
+  exclude_requester: boolean; // Requesters cannot sign their own requests (default true)
+  max_daily_approvals_per_requester: [] | [bigint]; // Per signer and requester, rolling 24h
 }
 
 export type PolicyDecision =
@@ -85,39 +159,45 @@ export type PolicyDecision =
 export interface PolicyResult {
   decision: PolicyDecision;
   matched_policy: [] | [string];
+  matched_policy_id: [] | [bigint];
   reason: string;
 }
 
-export interface AuditEntry {
-  id: bigint;
-  timestamp: bigint;
-  action_type: string;
-  action_params: string;
-  requester: Principal;
-  policy_result: PolicyResult;
-  threshold_request_id: [] | [bigint];
-  execution_result: [] | [ExecutionResult];
-}
-
 export type Condition =
   | { MaxAmount: bigint }
   | { MinAmount: bigint }
   | { DailyLimit: bigint }
   | { AllowedTokens: string[] }
   | { AllowedChains: string[] }
-  | { TimeWindow: { start: bigint; end: bigint } }
-  | { Cooldown: bigint };
+  | { TimeWindow: { start: bigint; end: bigint; days: [] | [number] } }
+  | { Cooldown: bigint }
+  | { AllowedRecipients: string[] }
+  | { DeniedRecipients: string[] }
+  | { AllowedSpenders: string[] }
+  | { All: Condition[] }
+  | { Any: Condition[] }
+  | { Not: Condition };
 
 export type PolicyAction =
   | { Allow: null }
   | { Deny: null }
-  | { RequireThreshold: { required: number; from_roles: Role[] } };
+  | {
+      RequireThreshold: {
+        required: number;
+        from_roles: Role[];
+        expiry_seconds: [] | [bigint]; // Signing window; [] = 24h default
+        timelock_seconds: [] | [bigint]; // Delay between approval and execution; [] = none
+        required_weight: [] | [bigint]; // Signer weight needed for approval; [] = one per required signature
+      };
+    };
 
 export interface Policy {
+  id: [] | [bigint]; // Assigned by the canister; pass [] when adding
   name: string;
   conditions: Condition[];
   action: PolicyAction;
   priority: number;
+  utc_offset_minutes: [] | [number];
 }
 
 export interface ChainGuardConfig {
@@ -127,7 +207,121 @@ export interface ChainGuardConfig {
   policies: Policy[];
 }
 
-export type Result<T = null> = { Ok: T } | { Err: string };
+export interface AgentSession {
+  principal: Principal;
+  created_by: Principal;
+  created_at: bigint;
+  expires_at: bigint;
+  total_budget: bigint;
+  spent: bigint;
+  max_per_transaction: bigint;
+  allowed_action_types: string[];
+  allowed_chains: string[];
+  revoked: boolean;
+  budget_token: [] | [string];  // Token the budget is counted in; [] on sessions that must be re-minted
+}
+
+export interface RoleAssignment {
+  principal: Principal;
+  role: Role;
+  assigned_at: bigint;
+  assigned_by: Principal;
+  expires_at: [] | [bigint];
+}
+
+export interface OwnershipProposal {
+  proposed_owner: Principal;
+  proposed_by: Principal;
+  proposed_at: bigint;
+}
+
+export interface ActionEvent {
+  action_type: string;
+  action_params: string;
+  policy_result: PolicyResult;
+  threshold_request_id: [] | [bigint];
+  execution_result: [] | [ExecutionResult];
+  policy_version: [] | [bigint];
+}
+
+export type AuditEvent =
+  | { ActionRequested: ActionEvent }
+  | { ActionExecuted: { audit_id: bigint; result: ExecutionResult } }
+  | {
+      RequestSigned: {
+        request_id: bigint;
+        signatures: bigint;
+        required: number;
+        status: RequestStatus;
+        current_weight: [] | [bigint];
+        required_weight: [] | [bigint];
+        executable_at: [] | [bigint];
+      };
+    }
+  | {
+      RequestRejected: {
+        request_id: bigint;
+        reason: string;
+        rejections: [] | [bigint];
+        required_rejections: [] | [bigint];
+        veto: [] | [boolean];
+        status: [] | [RequestStatus];
+      };
+    }
+  | { RequestExpired: { request_id: bigint } }
+  | { RequestCancelled: { request_id: bigint; reason: string } }
+  | { SignerWeightChanged: { target: WeightTarget; before: [] | [bigint]; after: [] | [bigint] } }
+  | { RejectionConfigChanged: { before: RejectionConfig; after: RejectionConfig } }
+  | { SigningRulesChanged: { before: SigningRules; after: SigningRules } }
+  | { RoleAssigned: { before: [] | [RoleAssignment]; after: RoleAssignment } }
+  | { RoleRevoked: { before: RoleAssignment } }
+  | { RoleExpired: { before: RoleAssignment } }
+  | { RoleDefinitionChanged: { name: string; before: [] | [Permission[]]; after: [] | [Permission[]] } }
+  | { OwnershipProposed: { proposal: OwnershipProposal } }
+  | { OwnershipProposalCancelled: { proposal: OwnershipProposal } }
+  | { OwnershipTransferred: { proposal: OwnershipProposal } }
+  | { PolicyAdded: { version: bigint; after: Policy } }
+  | { PolicyUpdated: { version: bigint; before: Policy; after: Policy } }
+  | { PolicyRemoved: { version: bigint; before: Policy } }
+  | { PoliciesRolledBack: { version: bigint; restored_version: bigint; before: Policy[]; after: Policy[] } }
+  | { Initialized: { config: ChainGuardConfig } }
+  | { Paused: null }
+  | { Resumed: null }
+  | { SessionCreated: { before: [] | [AgentSession]; after: AgentSession } }
+  | { SessionRevoked: { before: AgentSession } };
+
+export interface AuditEntry {
+  id: bigint;
+  timestamp: bigint;
+  caller: Principal;
+  event: AuditEvent;
+  prev_hash: Uint8Array | number[];
+  hash: Uint8Array | number[];
+}
+
+// Filters and cursor for a page of audit entries (newest first)
+export interface AuditQuery {
+  cursor: [] | [bigint];
+  limit: [] | [bigint];
+  start: [] | [bigint];
+  end: [] | [bigint];
+  requester: [] | [Principal];
+  action_type: [] | [string];
+  decision: [] | [PolicyDecision];
+  chain: [] | [string];
+  token: [] | [string];
+  success: [] | [boolean];
+  threshold_request_id: [] | [bigint];
+}
+
+export interface AuditPage {
+  entries: AuditEntry[];
+  results: AuditEntry[];  // ActionExecuted entries for the action requests in entries
+  next_cursor: [] | [bigint];
+  total: bigint;
+}
+
+export type Result<T = null> = { Ok: T } | { Err: ChainGuardError };
 
 /**
  * ChainGuard Canister Interface
@@ -155,10 +349,19 @@ export interface ChainGuardService {
   get_pending_requests: () => Promise<PendingRequest[]>;
   sign_request: (id: bigint) => Promise<Result<PendingRequest>>;
   reject_request: (id: bigint, reason: string) => Promise<Result>;
+  execute_request: (id: bigint) => Promise<Result<ExecutionResult>>;
+  cancel_request: (id: bigint, reason: string) => Promise<Result>;
+  set_signer_weight: (target: WeightTarget, weight: [] | [bigint]) => Promise<Result>;
+  list_signer_weights: () => Promise<SignerWeight[]>;
+  set_rejection_config: (config: RejectionConfig) => Promise<Result>;
+  get_rejection_config: () => Promise<RejectionConfig>;
+  set_signing_rules: (rules: SigningRules) => Promise<Result>;
+  get_signing_rules: () => Promise<SigningRules>;
 
   // Audit
   get_audit_logs: (start: [] | [bigint], end: [] | [bigint]) => Promise<AuditEntry[]>;
   get_audit_entry: (id: bigint) => Promise<[] | [AuditEntry]>;
+  get_audit_page: (query: AuditQuery) => Promise<Result<AuditPage>>;
 
   // Emergency
   pause: () => Promise<Result>;
@@ -9,6 +9,7 @@ export const idlFactory = ({ IDL }: { IDL: typeof import('@dfinity/candid').IDL
     Owner: IDL.Null,
     Operator: IDL.Null,
     Viewer: IDL.Null,
+    Custom: IDL.Text,
   });
 
   const Permission = IDL.Variant({
@@ -19,6 +20,45 @@ export const idlFactory = ({ IDL }: { IDL: typeof import('@dfinity/candid').IDL
     Emergency: IDL.Null,
   });
 
+  const ChainGuardError = IDL.Variant({
+    Unauthorized: IDL.Null,
+    InsufficientPermissions: IDL.Record({ required: Permission }),
+    RoleNotFound: IDL.Record({ name: IDL.Text }),
+    RoleAlreadyExists: IDL.Record({ name: IDL.Text }),
+    RoleInUse: IDL.Record({ name: IDL.Text }),
+    LastOwner: IDL.Null,
+    OwnershipProposalNotFound: IDL.Null,
+    NotInitialized: IDL.Null,
+    AlreadyInitialized: IDL.Null,
+    InvalidConfiguration: IDL.Record({ reason: IDL.Text }),
+    PolicyNotFound: IDL.Record({ id: IDL.Nat64 }),
+    PolicyVersionNotFound: IDL.Record({ version: IDL.Nat64 }),
+    PolicyEvaluationFailed: IDL.Record({ reason: IDL.Text }),
+    PolicyDenied: IDL.Record({ policy_id: IDL.Opt(IDL.Nat64), reason: IDL.Text }),
+    RequestNotFound: IDL.Record({ id: IDL.Nat64 }),
+    RequestExpired: IDL.Null,
+    RequestAlreadySigned: IDL.Null,
+    RequestAlreadyRejected: IDL.Null,
+    RequestNotApproved: IDL.Null,
+    SignerNotEligible: IDL.Record({ required_roles: IDL.Vec(Role) }),
+    RequesterCannotSign: IDL.Null,
+    DailyApprovalLimitReached: IDL.Record({ limit: IDL.Nat64 }),
+    RequestTimelocked: IDL.Record({ executable_at: IDL.Nat64 }),
+    InvalidRequestStatus: IDL.Record({ expected: IDL.Text, actual: IDL.Text }),
+    SessionNotFound: IDL.Null,
+    SessionExpired: IDL.Null,
+    SessionRevoked: IDL.Null,
+    SessionActionNotAllowed: IDL.Record({ action_type: IDL.Text }),
+    SessionChainNotAllowed: IDL.Record({ chain: IDL.Text }),
+    SessionTokenNotAllowed: IDL.Record({ token: IDL.Text }),
+    SessionTransactionLimitExceeded: IDL.Record({ limit: IDL.Nat64, requested: IDL.Nat64 }),
+    SessionBudgetExceeded: IDL.Record({ remaining: IDL.Nat64, requested: IDL.Nat64 }),
+    ExecutionFailed: IDL.Record({ reason: IDL.Text }),
+    ChainNotSupported: IDL.Record({ chain: IDL.Text }),
+    SystemPaused: IDL.Null,
+    InternalError: IDL.Record({ msg: IDL.Text }),
+  });
+
   const Action = IDL.Variant({
     Swap: IDL.Record({
       chain: IDL.Text,
@@ -52,6 +92,14 @@ export const idlFactory = ({ IDL }: { IDL: typeof import('@dfinity/candid').IDL
   const Signature = IDL.Record({
     signer: IDL.Principal,
     signed_at: IDL.Nat64,
+    weight: IDL.Nat64,
+  });
+
+  const Rejection = IDL.Record({
+    signer: IDL.Principal,
+    reason: IDL.Text,
+    rejected_at: IDL.Nat64,
+    veto: IDL.Bool,
   });
 
   const RequestStatus = IDL.Variant({
@@ -60,6 +108,7 @@ export const idlFactory = ({ IDL }: { IDL: typeof import('@dfinity/candid').IDL
     Executed: IDL.Null,
     Expired: IDL.Null,
     Rejected: IDL.Null,
+    Cancelled: IDL.Null,
   });
 
   const PendingRequest = IDL.Record({
@@ -71,12 +120,39 @@ export const idlFactory = ({ IDL }: { IDL: typeof import('@dfinity/candid').IDL
     required_signatures: IDL.Nat8,
     collected_signatures: IDL.Vec(Signature),
     status: RequestStatus,
+    required_weight: IDL.Nat64,
+    current_weight: IDL.Nat64,
+    rejections: IDL.Vec(Rejection),
+    required_rejections: IDL.Nat64,
+    timelock: IDL.Opt(IDL.Nat64),
+    executable_at: IDL.Opt(IDL.Nat64),
+    failed_at: IDL.Opt(IDL.Nat64),
+  });
+
+  const RejectionConfig = IDL.Record({
+    threshold: IDL.Nat64,
+    veto_principals: IDL.Vec(IDL.Principal),
+  });
+
+  const SigningRules = IDL.Record({
+    exclude_requester: IDL.Bool,
+    max_daily_approvals_per_requester: IDL.Opt(IDL.Nat64),
+  });
+
+  const WeightTarget = IDL.Variant({
+    Principal: IDL.Principal,
+    Role: Role,
+  });
+
+  const SignerWeight = IDL.Record({
+    target: WeightTarget,
+    weight: IDL.Nat64,
   });
 
   const ActionResult = IDL.Variant({
     Executed: ExecutionResult,
     PendingSignatures: PendingRequest,
-    Denied: IDL.Record({ reason: IDL.Text }),
+    Denied: IDL.Record({ error: ChainGuardError }),
   });
 
   const PolicyDecision = IDL.Variant({
@@ -88,29 +164,28 @@ export const idlFactory = ({ IDL }: { IDL: typeof import('@dfinity/candid').IDL
   const PolicyResult = IDL.Record({
     decision: PolicyDecision,
     matched_policy: IDL.Opt(IDL.Text),
+    matched_policy_id: IDL.Opt(IDL.Nat64),
     reason: IDL.Text,
   });
 
-  const AuditEntry = IDL.Record({
-    id: IDL.Nat64,
-    timestamp: IDL.Nat64,
-    action_type: IDL.Text,
-    action_params: IDL.Text,
-    requester: IDL.Principal,
-    policy_result: PolicyResult,
-    threshold_request_id: IDL.Opt(IDL.Nat64),
-    execution_result: IDL.Opt(ExecutionResult),
-  });
-
-  const Condition = IDL.Variant({
-    MaxAmount: IDL.Nat64,
-    MinAmount: IDL.Nat64,
-    DailyLimit: IDL.Nat64,
-    AllowedTokens: IDL.Vec(IDL.Text),
-    AllowedChains: IDL.Vec(IDL.Text),
-    TimeWindow: IDL.Record({ start: IDL.Nat64, end: IDL.Nat64 }),
-    Cooldown: IDL.Nat64,
-  });
+  const Condition = IDL.Rec();
+  Condition.fill(
+    IDL.Variant({
+      MaxAmount: IDL.Nat64,
+      MinAmount: IDL.Nat64,
+      DailyLimit: IDL.Nat64,
+      AllowedTokens: IDL.Vec(IDL.Text),
+      AllowedChains: IDL.Vec(IDL.Text),
+      TimeWindow: IDL.Record({ start: IDL.Nat64, end: IDL.Nat64, days: IDL.Opt(IDL.Nat8) }),
+      Cooldown: IDL.Nat64,
+      AllowedRecipients: IDL.Vec(IDL.Text),
+      DeniedRecipients: IDL.Vec(IDL.Text),
+      AllowedSpenders: IDL.Vec(IDL.Text),
+      All: IDL.Vec(Condition),
+      Any: IDL.Vec(Condition),
+      Not: Condition,
+    })
+  );
 
   const PolicyAction = IDL.Variant({
     Allow: IDL.Null,
@@ -118,14 +193,19 @@ export const idlFactory = ({ IDL }: { IDL: typeof import('@dfinity/candid').IDL
     RequireThreshold: IDL.Record({
       required: IDL.Nat8,
       from_roles: IDL.Vec(Role),
+      expiry_seconds: IDL.Opt(IDL.Nat64),
+      timelock_seconds: IDL.Opt(IDL.Nat64),
+      required_weight: IDL.Opt(IDL.Nat64),
     }),
   });
 
   const Policy = IDL.Record({
+    id: IDL.Opt(IDL.Nat64),
     name: IDL.Text,
     conditions: IDL.Vec(Condition),
     action: PolicyAction,
     priority: IDL.Nat32,
+    utc_offset_minutes: IDL.Opt(IDL.Int32),
   });
 
   const ChainGuardConfig = IDL.Record({
@@ -138,19 +218,147 @@ export const idlFactory = ({ IDL }: { IDL: typeof import('@dfinity/candid').IDL
     policies: IDL.Vec(Policy),
   });
 
+  const AgentSession = IDL.Record({
+    principal: IDL.Principal,
+    created_by: IDL.Principal,
+    created_at: IDL.Nat64,
+    expires_at: IDL.Nat64,
+    total_budget: IDL.Nat64,
+    spent: IDL.Nat64,
+    max_per_transaction: IDL.Nat64,
+    allowed_action_types: IDL.Vec(IDL.Text),
+    allowed_chains: IDL.Vec(IDL.Text),
+    revoked: IDL.Bool,
+    budget_token: IDL.Opt(IDL.Text),
+  });
+
+  const RoleAssignment = IDL.Record({
+    principal: IDL.Principal,
+    role: Role,
+    assigned_at: IDL.Nat64,
+    assigned_by: IDL.Principal,
+    expires_at: IDL.Opt(IDL.Nat64),
+  });
+
+  const OwnershipProposal = IDL.Record({
+    proposed_owner: IDL.Principal,
+    proposed_by: IDL.Principal,
+    proposed_at: IDL.Nat64,
+  });
+
+  const ActionEvent = IDL.Record({
+    action_type: IDL.Text,
+    action_params: IDL.Text,
+    policy_result: PolicyResult,
+    threshold_request_id: IDL.Opt(IDL.Nat64),
+    execution_result: IDL.Opt(ExecutionResult),
+    policy_version: IDL.Opt(IDL.Nat64),
+  });
+
+  const AuditEvent = IDL.Variant({
+    ActionRequested: ActionEvent,
+    ActionExecuted: IDL.Record({ audit_id: IDL.Nat64, result: ExecutionResult }),
+    RequestSigned: IDL.Record({
+      request_id: IDL.Nat64,
+      signatures: IDL.Nat64,
+      required: IDL.Nat8,
+      status: RequestStatus,
+      current_weight: IDL.Opt(IDL.Nat64),
+      required_weight: IDL.Opt(IDL.Nat64),
+      executable_at: IDL.Opt(IDL.Nat64),
+    }),
+    RequestRejected: IDL.Record({
+      request_id: IDL.Nat64,
+      reason: IDL.Text,
+      rejections: IDL.Opt(IDL.Nat64),
+      required_rejections: IDL.Opt(IDL.Nat64),
+      veto: IDL.Opt(IDL.Bool),
+      status: IDL.Opt(RequestStatus),
+    }),
+    RequestExpired: IDL.Record({ request_id: IDL.Nat64 }),
+    RequestCancelled: IDL.Record({ request_id: IDL.Nat64, reason: IDL.Text }),
+    SignerWeightChanged: IDL.Record({
+      target: WeightTarget,
+      before: IDL.Opt(IDL.Nat64),
+      after: IDL.Opt(IDL.Nat64),
+    }),
+    RejectionConfigChanged: IDL.Record({ before: RejectionConfig, after: RejectionConfig }),
+    SigningRulesChanged: IDL.Record({ before: SigningRules, after: SigningRules }),
+    RoleAssigned: IDL.Record({ before: IDL.Opt(RoleAssignment), after: RoleAssignment }),
+    RoleRevoked: IDL.Record({ before: RoleAssignment }),
+    RoleExpired: IDL.Record({ before: RoleAssignment }),
+    RoleDefinitionChanged: IDL.Record({
+      name: IDL.Text,
+      before: IDL.Opt(IDL.Vec(Permission)),
+      after: IDL.Opt(IDL.Vec(Permission)),
+    }),
+    OwnershipProposed: IDL.Record({ proposal: OwnershipProposal }),
+    OwnershipProposalCancelled: IDL.Record({ proposal: OwnershipProposal }),
+    OwnershipTransferred: IDL.Record({ proposal: OwnershipProposal }),
+    PolicyAdded: IDL.Record({ version: IDL.Nat64, after: Policy }),
+    PolicyUpdated: IDL.Record({ version: IDL.Nat64, before: Policy, after: Policy }),
+    PolicyRemoved: IDL.Record({ version: IDL.Nat64, before: Policy }),
+    PoliciesRolledBack: IDL.Record({
+      version: IDL.Nat64,
+      restored_version: IDL.Nat64,
+      before: IDL.Vec(Policy),
+      after: IDL.Vec(Policy),
+    }),
+    Initialized: IDL.Record({ config: ChainGuardConfig }),
+    Paused: IDL.Null,
+    Resumed: IDL.Null,
+    SessionCreated: IDL.Record({ before: IDL.Opt(AgentSession), after: AgentSession }),
+    SessionRevoked: IDL.Record({ before: AgentSession }),
+  });
+
+  const AuditEntry = IDL.Record({
+    id: IDL.Nat64,
+    timestamp: IDL.Nat64,
+    caller: IDL.Principal,
+    event: AuditEvent,
+    prev_hash: IDL.Vec(IDL.Nat8),
+    hash: IDL.Vec(IDL.Nat8),
+  });
+
+  const AuditQuery = IDL.Record({
+    cursor: IDL.Opt(IDL.Nat64),
+    limit: IDL.Opt(IDL.Nat64),
+    start: IDL.Opt(IDL.Nat64),
+    end: IDL.Opt(IDL.Nat64),
+    requester: IDL.Opt(IDL.Principal),
+    action_type: IDL.Opt(IDL.Text),
+    decision: IDL.Opt(PolicyDecision),
+    chain: IDL.Opt(IDL.Text),
+    token: IDL.Opt(IDL.Text),
+    success: IDL.Opt(IDL.Bool),
+    threshold_request_id: IDL.Opt(IDL.Nat64),
+  });
+
+  const AuditPage = IDL.Record({
+    entries: IDL.Vec(AuditEntry),
+    results: IDL.Vec(AuditEntry),
+    next_cursor: IDL.Opt(IDL.Nat64),
+    total: IDL.Nat64,
+  });
+
   const Result = IDL.Variant({
     Ok: IDL.Null,
-    Err: IDL.Text,
+    Err: ChainGuardError,
   });
 
   const ResultWithId = IDL.Variant({
     Ok: IDL.Nat64,
-    Err: IDL.Text,
+    Err: ChainGuardError,
   });
 
   const ResultWithRequest = IDL.Variant({
     Ok: PendingRequest,
-    Err: IDL.Text,
+    Err: ChainGuardError,
+  });
+
+  const ResultWithExecution = IDL.Variant({
+    Ok: ExecutionResult,
+    Err: ChainGuardError,
   });
 
   return IDL.Service({
@@ -174,12 +382,26 @@ export const idlFactory = ({ IDL }: { IDL: typeof import('@dfinity/candid').IDL
 
     // Threshold Signing
     get_pending_requests: IDL.Func([], [IDL.Vec(PendingRequest)], ['query']),
+    get_timelocked_requests: IDL.Func([], [IDL.Vec(PendingRequest)], ['query']),
     sign_request: IDL.Func([IDL.Nat64], [ResultWithRequest], []),
     reject_request: IDL.Func([IDL.Nat64, IDL.Text], [Result], []),
+    execute_request: IDL.Func([IDL.Nat64], [ResultWithExecution], []),
+    cancel_request: IDL.Func([IDL.Nat64, IDL.Text], [Result], []),
+    set_signer_weight: IDL.Func([WeightTarget, IDL.Opt(IDL.Nat64)], [Result], []),
+    list_signer_weights: IDL.Func([], [IDL.Vec(SignerWeight)], ['query']),
+    set_rejection_config: IDL.Func([RejectionConfig], [Result], []),
+    get_rejection_config: IDL.Func([], [RejectionConfig], ['query']),
+    set_signing_rules: IDL.Func([SigningRules], [Result], []),
+    get_signing_rules: IDL.Func([], [SigningRules], ['query']),
 
     // Audit
     get_audit_logs: IDL.Func([IDL.Opt(IDL.Nat64), IDL.Opt(IDL.Nat64)], [IDL.Vec(AuditEntry)], ['query']),
     get_audit_entry: IDL.Func([IDL.Nat64], [IDL.Opt(AuditEntry)], ['query']),
+    get_audit_page: IDL.Func(
+      [AuditQuery],
+      [IDL.Variant({ Ok: AuditPage, Err: ChainGuardError })],
+      ['query']
+    ),
 
     // Emergency
     pause: IDL.Func([], [Result], []),
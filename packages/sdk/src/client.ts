@@ -12,12 +12,22 @@ import type {
   ChainGuardService,
   Action,
   ActionResult,
+  ExecutionResult,
   PendingRequest,
   AuditEntry,
+  AuditPage,
+  AuditQueryOptions,
+  CertifiedAuditRange,
   Policy,
   ChainGuardConfig,
   Role,
   Result,
+  AgentSession,
+  SessionConfig,
+  SignerWeight,
+  WeightTarget,
+  RejectionConfig,
+  SigningRules,
 } from './types';
 
 export interface ChainGuardClientOptions {
@@ -207,6 +217,13 @@ export class ChainGuardClient {
     return await this.actor.get_pending_requests();
   }
 
+  /**
+   * Get approved requests that are still inside their time-lock, soonest first
+   */
+  async getTimelockedRequests(): Promise<PendingRequest[]> {
+    return await this.actor.get_timelocked_requests();
+  }
+
   /**
    * Sign a pending request
    */
@@ -215,12 +232,106 @@ export class ChainGuardClient {
   }
 
   /**
-   * Reject a pending request
+   * Reject a pending request. The reason is stored on the request; the request
+   * is rejected once the rejection threshold is reached or a veto principal rejects.
    */
   async rejectRequest(requestId: bigint, reason: string): Promise<Result> {
     return await this.actor.reject_request(requestId, reason);
   }
 
+  /**
+   * Execute a time-locked request whose window has passed (requires Sign).
+   * The canister also executes due requests on a timer.
+   */
+  async executeRequest(requestId: bigint): Promise<Result<ExecutionResult>> {
+    return await this.actor.execute_request(requestId);
+  }
+
+  /**
+   * Cancel a time-locked request before it executes (requires Emergency)
+   */
+  async cancelRequest(requestId: bigint, reason: string): Promise<Result> {
+    return await this.actor.cancel_request(requestId, reason);
+  }
+
+  /**
+   * Set the vote weight of a principal or role (requires Configure).
+   * A principal's own weight wins over its roles'; signers without one weigh 1.
+   * Pass no weight to clear it.
+   */
+  async setSignerWeight(target: WeightTarget, weight?: bigint): Promise<Result> {
+    return await this.actor.set_signer_weight(target, weight === undefined ? [] : [weight]);
+  }
+
+  /**
+   * List configured signer weights
+   */
+  async listSignerWeights(): Promise<SignerWeight[]> {
+    return await this.actor.list_signer_weights();
+  }
+
+  /**
+   * Set how many signer rejections reject a request and who can veto alone
+   * (requires Configure). Applies to requests created afterwards.
+   */
+  async setRejectionConfig(config: RejectionConfig): Promise<Result> {
+    return await this.actor.set_rejection_config(config);
+  }
+
+  /**
+   * Get the rejection threshold and veto principals
+   */
+  async getRejectionConfig(): Promise<RejectionConfig> {
+    return await this.actor.get_rejection_config();
+  }
+
+  /**
+   * Set the separation-of-duties rules (requires Configure): whether requesters
+   * may sign their own requests and an optional daily approval cap per requester
+   */
+  async setSigningRules(rules: SigningRules): Promise<Result> {
+    return await this.actor.set_signing_rules(rules);
+  }
+
+  /**
+   * Get the separation-of-duties rules
+   */
+  async getSigningRules(): Promise<SigningRules> {
+    return await this.actor.get_signing_rules();
+  }
+
+  // ============ Agent Sessions ============
+
+  /**
+   * Mint a scoped session for an agent principal (requires Owner role).
+   * The agent may then call requestAction within the session's expiry,
+   * budget, action types and chains.
+   */
+  async createSession(config: SessionConfig): Promise<Result<AgentSession>> {
+    return await this.actor.create_session(config);
+  }
+
+  /**
+   * Revoke an agent session immediately (requires Emergency permission)
+   */
+  async revokeSession(principal: Principal): Promise<Result> {
+    return await this.actor.revoke_session(principal);
+  }
+
+  /**
+   * Get the session of a principal (own session, or any with ViewLogs)
+   */
+  async getSession(principal: Principal): Promise<Result<[] | [AgentSession]>> {
+    return await this.actor.get_session(principal);
+  }
+
+  /**
+   * List all agent sessions (requires ViewLogs permission)
+   */
+  async listSessions(): Promise<Result<AgentSession[]>> {
+    return await this.actor.list_sessions();
+  }
+
   // ============ Audit & Monitoring ============
 
   /**
@@ -243,6 +354,39 @@ export class ChainGuardClient {
     return result.length > 0 ? result[0] ?? null : null;
   }
 
+  /**
+   * Get one page of audit entries, newest first, with the total matching count.
+   * Pass the returned next_cursor as `cursor` to fetch the following page.
+   * @param options - Cursor, page size (default 50, max 1000) and filters
+   */
+  async getAuditPage(options: AuditQueryOptions = {}): Promise<Result<AuditPage>> {
+    const opt = <T>(value: T | undefined): [] | [T] => (value !== undefined ? [value] : []);
+    return await this.actor.get_audit_page({
+      cursor: opt(options.cursor),
+      limit: opt(options.limit),
+      start: opt(options.start),
+      end: opt(options.end),
+      requester: opt(options.requester),
+      action_type: opt(options.actionType),
+      decision: opt(options.decision),
+      chain: opt(options.chain),
+      token: opt(options.token),
+      success: opt(options.success),
+      threshold_request_id: opt(options.thresholdRequestId),
+    });
+  }
+
+  /**
+   * Get audit entries by id together with the certificate over the log tip.
+   * Each entry's prev_hash is the hash of the entry before it; the certificate
+   * signs tip_hash, the hash of entry tip_id.
+   * @param startId - First entry id to return
+   * @param limit - Maximum number of entries (capped by the canister at 1000)
+   */
+  async getCertifiedAuditRange(startId: bigint, limit: bigint): Promise<Result<CertifiedAuditRange>> {
+    return await this.actor.get_certified_audit_range(startId, limit);
+  }
+
   /**
    * Get canister configuration
    */
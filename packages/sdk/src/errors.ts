@@ -0,0 +1,55 @@
+/**
+ * Helpers for ChainGuard's typed errors
+ */
+
+import type { ChainGuardError } from './types';
+
+/**
+ * Human-readable message for a ChainGuardError.
+ * Branch on the variant itself (e.g. `'SystemPaused' in error`) for control flow.
+ */
+export function describeError(error: ChainGuardError): string {
+  if ('PolicyDenied' in error) return error.PolicyDenied.reason;
+  if ('InsufficientPermissions' in error) {
+    return `Missing permission: ${Object.keys(error.InsufficientPermissions.required)[0]}`;
+  }
+  if ('LastOwner' in error) return 'Cannot remove the last Owner';
+  if ('SystemPaused' in error) return 'ChainGuard is paused';
+  if ('RequestNotFound' in error) return `Request ${error.RequestNotFound.id} not found`;
+  if ('RequestExpired' in error) return 'Request has expired';
+  if ('SignerNotEligible' in error) {
+    const roles = error.SignerNotEligible.required_roles.map((r) => Object.keys(r)[0]);
+    return `Signer must hold one of: ${roles.join(', ')}`;
+  }
+  if ('RequesterCannotSign' in error) return 'Requesters cannot sign their own requests';
+  if ('RequestTimelocked' in error) {
+    const until = new Date(Number(error.RequestTimelocked.executable_at / 1_000_000n));
+    return `Request is time-locked until ${until.toISOString()}`;
+  }
+  if ('DailyApprovalLimitReached' in error) {
+    return `Daily approval limit of ${error.DailyApprovalLimitReached.limit} for this requester reached`;
+  }
+  if ('SessionExpired' in error) return 'Agent session has expired';
+  if ('SessionRevoked' in error) return 'Agent session has been revoked';
+  if ('SessionActionNotAllowed' in error) {
+    return `Session does not allow ${error.SessionActionNotAllowed.action_type}`;
+  }
+  if ('SessionChainNotAllowed' in error) {
+    return `Session does not allow chain ${error.SessionChainNotAllowed.chain}`;
+  }
+  if ('SessionTokenNotAllowed' in error) {
+    return `Session budget does not cover token ${error.SessionTokenNotAllowed.token}`;
+  }
+  if ('SessionTransactionLimitExceeded' in error) {
+    const { limit, requested } = error.SessionTransactionLimitExceeded;
+    return `Amount ${requested} exceeds the session per-transaction limit of ${limit}`;
+  }
+  if ('SessionBudgetExceeded' in error) {
+    const { remaining, requested } = error.SessionBudgetExceeded;
+    return `Amount ${requested} exceeds the remaining session budget of ${remaining}`;
+  }
+  if ('ExecutionFailed' in error) return error.ExecutionFailed.reason;
+  if ('InvalidConfiguration' in error) return error.InvalidConfiguration.reason;
+  if ('InternalError' in error) return error.InternalError.msg;
+  return Object.keys(error)[0];
+}
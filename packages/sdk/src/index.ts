@@ -8,6 +8,7 @@
 
 export { ChainGuardClient, ChainGuardClientOptions } from './client';
 export { idlFactory } from './idl';
+export { describeError } from './errors';
 export type {
   // Core types
   Role,
@@ -24,6 +25,11 @@ export type {
   // Threshold signing
   PendingRequest,
   Signature,
+  SignerWeight,
+  WeightTarget,
+  Rejection,
+  RejectionConfig,
+  SigningRules,
   RequestStatus,
 
   // Policies
@@ -35,12 +41,25 @@ export type {
 
   // Audit
   AuditEntry,
+  AuditEvent,
+  ActionEvent,
+  AuditQuery,
+  AuditQueryOptions,
+  AuditPage,
+  CertifiedAuditRange,
+  RoleAssignment,
+  OwnershipProposal,
+
+  // Agent sessions
+  AgentSession,
+  SessionConfig,
 
   // Configuration
   ChainGuardConfig,
 
   // Results
   Result,
+  ChainGuardError,
 
   // Service interface
   ChainGuardService,
@@ -1,137 +1,380 @@
+use crate::errors::{ChainGuardError, ChainGuardResult};
 use crate::types::*;
 use candid::Principal;
 use std::collections::HashMap;
 
+const SECONDS_PER_DAY: i64 = 86_400;
+
+// (requester, policy id, action type)
+type ExecutionKey = (Principal, u64, String);
+
+// Inputs shared by every condition of a policy during evaluation
+struct EvalContext<'a> {
+    action: &'a Action,
+    daily_spent: u64,
+    current_time: u64,
+    utc_offset_minutes: i32,
+    last_execution: Option<u64>,
+}
+
 pub struct AccessControl {
-    role_assignments: HashMap<Principal, Vec<Role>>,
+    role_assignments: HashMap<Principal, Vec<RoleAssignment>>,
+    custom_roles: HashMap<String, Vec<Permission>>,
     policies: Vec<Policy>,
+    next_policy_id: u64,
+    last_executions: HashMap<ExecutionKey, u64>,
+    cooldown_reservations: HashMap<u64, ExecutionRecord>,  // In-flight executions, keyed by audit entry id
+    ownership_proposal: Option<OwnershipProposal>,
 }
 
 impl AccessControl {
     pub fn new() -> Self {
         Self {
             role_assignments: HashMap::new(),
+            custom_roles: HashMap::new(),
             policies: Vec::new(),
+            next_policy_id: 0,
+            last_executions: HashMap::new(),
+            cooldown_reservations: HashMap::new(),
+            ownership_proposal: None,
         }
     }
 
-    // Check if principal has a specific role
-    pub fn has_role(&self, principal: &Principal, role: &Role) -> bool {
-        self.role_assignments
-            .get(principal)
-            .map(|roles| roles.contains(role))
-            .unwrap_or(false)
+    // Check if principal has a specific, unexpired role
+    pub fn has_role(&self, principal: &Principal, role: &Role, current_time: u64) -> bool {
+        self.get_roles(principal, current_time).contains(role)
     }
 
-    // Get all roles for a principal
-    pub fn get_roles(&self, principal: &Principal) -> Vec<Role> {
+    // Get all unexpired roles for a principal
+    pub fn get_roles(&self, principal: &Principal, current_time: u64) -> Vec<Role> {
         self.role_assignments
             .get(principal)
-            .cloned()
+            .map(|grants| {
+                grants
+                    .iter()
+                    .filter(|g| g.is_active(current_time))
+                    .map(|g| g.role.clone())
+                    .collect()
+            })
             .unwrap_or_default()
     }
 
-    // Check if principal has permission (derived from roles)
-    pub fn has_permission(&self, principal: &Principal, permission: &Permission) -> bool {
-        let roles = self.role_assignments.get(principal);
-        match roles {
-            None => false,
-            Some(roles) => {
-                for role in roles {
-                    if Self::role_has_permission(role, permission) {
-                        return true;
-                    }
-                }
-                false
-            }
-        }
+    // Check if principal has permission (derived from unexpired roles)
+    pub fn has_permission(&self, principal: &Principal, permission: &Permission, current_time: u64) -> bool {
+        self.get_roles(principal, current_time)
+            .iter()
+            .any(|role| self.role_permissions(role).contains(permission))
     }
 
-    // Define which roles have which permissions
-    fn role_has_permission(role: &Role, permission: &Permission) -> bool {
-        match (role, permission) {
-            (Role::Owner, _) => true,  // Owner has all permissions
-            (Role::Operator, Permission::Execute) => true,
-            (Role::Operator, Permission::Sign) => true,
-            (Role::Operator, Permission::ViewLogs) => true,
-            (Role::Viewer, Permission::ViewLogs) => true,
-            _ => false,
+    // Define which roles have which permissions; custom roles use their admin-defined set
+    pub fn role_permissions(&self, role: &Role) -> Vec<Permission> {
+        match role {
+            Role::Owner => vec![
+                Permission::Execute,
+                Permission::Configure,
+                Permission::ViewLogs,
+                Permission::Sign,
+                Permission::Emergency,
+            ],
+            Role::Operator => vec![Permission::Execute, Permission::Sign, Permission::ViewLogs],
+            Role::Viewer => vec![Permission::ViewLogs],
+            Role::Custom(name) => self.custom_roles.get(name).cloned().unwrap_or_default(),
         }
     }
 
     // Evaluate policies for an action
-    pub fn evaluate_action(&self, action: &Action, _requester: &Principal, daily_spent: u64) -> PolicyResult {
+    // current_time is in nanoseconds since the Unix epoch (ic_cdk::api::time())
+    pub fn evaluate_action(
+        &self,
+        action: &Action,
+        requester: &Principal,
+        daily_spent: u64,
+        current_time: u64,
+    ) -> PolicyResult {
+        self.evaluate_action_with_policy(action, requester, daily_spent, current_time).0
+    }
+
+    // Same as evaluate_action, but also returns the matched policy
+    pub fn evaluate_action_with_policy(
+        &self,
+        action: &Action,
+        requester: &Principal,
+        daily_spent: u64,
+        current_time: u64,
+    ) -> (PolicyResult, Option<Policy>) {
         // Sort policies by priority
         let mut sorted_policies = self.policies.clone();
         sorted_policies.sort_by_key(|p| p.priority);
 
         for policy in &sorted_policies {
-            if self.conditions_match(&policy.conditions, action, daily_spent) {
-                return PolicyResult {
+            let ctx = EvalContext {
+                action,
+                daily_spent,
+                current_time,
+                utc_offset_minutes: policy.utc_offset_minutes.unwrap_or(0),
+                last_execution: self.last_execution(requester, Self::policy_id(policy), action),
+            };
+            if self.conditions_match(&policy.conditions, &ctx) {
+                let result = PolicyResult {
                     decision: self.policy_action_to_decision(&policy.action),
                     matched_policy: Some(policy.name.clone()),
-                    reason: format!("Matched policy: {}", policy.name),
+                    matched_policy_id: policy.id,
+                    reason: Self::with_counterparty(format!("Matched policy: {}", policy.name), action),
                 };
+                return (result, Some(policy.clone()));
             }
         }
 
         // Default: deny if no policy matches
-        PolicyResult {
+        let result = PolicyResult {
             decision: PolicyDecision::Denied,
             matched_policy: None,
-            reason: "No matching policy found".to_string(),
+            matched_policy_id: None,
+            reason: Self::with_counterparty("No matching policy found".to_string(), action),
+        };
+        (result, None)
+    }
+
+    // Append the transfer recipient or approval spender so audits show where funds could go
+    fn with_counterparty(reason: String, action: &Action) -> String {
+        match action {
+            Action::Transfer { to, .. } => format!("{} (recipient: {})", reason, to),
+            Action::ApproveToken { spender, .. } => format!("{} (spender: {})", reason, spender),
+            Action::Swap { .. } => reason,
         }
     }
 
-    fn conditions_match(&self, conditions: &[Condition], action: &Action, daily_spent: u64) -> bool {
-        let amount = self.get_action_amount(action);
-        let chain = self.get_action_chain(action);
+    // Dry-run evaluation: trace every policy and condition without side effects
+    pub fn explain_action(
+        &self,
+        action: &Action,
+        requester: &Principal,
+        daily_spent: u64,
+        current_time: u64,
+    ) -> (PolicyResult, Vec<PolicyTrace>) {
+        let mut sorted_policies = self.policies.clone();
+        sorted_policies.sort_by_key(|p| p.priority);
 
-        for condition in conditions {
-            match condition {
-                Condition::MaxAmount(max) => {
-                    if amount > *max {
-                        return false;
-                    }
-                }
-                Condition::MinAmount(min) => {
-                    if amount < *min {
-                        return false;
-                    }
-                }
-                Condition::DailyLimit(limit) => {
-                    if daily_spent + amount > *limit {
-                        return false;
-                    }
-                }
-                Condition::AllowedChains(chains) => {
-                    if !chains.contains(&chain) {
-                        return false;
-                    }
-                }
-                Condition::AllowedTokens(tokens) => {
-                    let action_tokens = self.get_action_tokens(action);
-                    for token in &action_tokens {
-                        if !tokens.contains(token) {
-                            return false;
-                        }
-                    }
-                }
-                Condition::TimeWindow { start, end } => {
-                    // For now, we'll skip time window checks
-                    // In production, would compare current time with start/end
-                    let _current_hour = 0; // TODO: implement time check
-                    if *start > *end {
-                        return false;
-                    }
+        let traces = sorted_policies
+            .iter()
+            .map(|policy| {
+                let ctx = EvalContext {
+                    action,
+                    daily_spent,
+                    current_time,
+                    utc_offset_minutes: policy.utc_offset_minutes.unwrap_or(0),
+                    last_execution: self.last_execution(requester, Self::policy_id(policy), action),
+                };
+                PolicyTrace {
+                    id: Self::policy_id(policy),
+                    name: policy.name.clone(),
+                    priority: policy.priority,
+                    action: policy.action.clone(),
+                    matched: self.conditions_match(&policy.conditions, &ctx),
+                    conditions: policy.conditions.iter().map(|c| self.trace_condition(c, &ctx)).collect(),
                 }
-                Condition::Cooldown(_seconds) => {
-                    // TODO: implement cooldown check
-                    // Would need to track last execution time
+            })
+            .collect();
+
+        // The decision itself comes from the same path request_action uses
+        (self.evaluate_action(action, requester, daily_spent, current_time), traces)
+    }
+
+    fn trace_condition(&self, condition: &Condition, ctx: &EvalContext) -> ConditionTrace {
+        let amount = self.get_action_amount(ctx.action);
+        let passed = self.condition_matches(condition, ctx);
+        let trace = |name: &str, actual: String, expected: String, nested: Vec<ConditionTrace>| ConditionTrace {
+            condition: name.to_string(),
+            passed,
+            actual,
+            expected,
+            nested,
+        };
+        let nested_traces = |conditions: &[Condition]| -> Vec<ConditionTrace> {
+            conditions.iter().map(|c| self.trace_condition(c, ctx)).collect()
+        };
+        let counterparty = |address: Option<&String>| {
+            address.cloned().unwrap_or_else(|| "n/a for this action".to_string())
+        };
+
+        match condition {
+            Condition::MaxAmount(max) => trace("MaxAmount", amount.to_string(), format!("<= {}", max), vec![]),
+            Condition::MinAmount(min) => trace("MinAmount", amount.to_string(), format!(">= {}", min), vec![]),
+            Condition::DailyLimit(limit) => trace(
+                "DailyLimit",
+                format!("{} spent + {} = {}", ctx.daily_spent, amount, ctx.daily_spent.saturating_add(amount)),
+                format!("<= {}", limit),
+                vec![],
+            ),
+            Condition::AllowedChains(chains) => trace(
+                "AllowedChains",
+                self.get_action_chain(ctx.action),
+                format!("one of {:?}", chains),
+                vec![],
+            ),
+            Condition::AllowedTokens(tokens) => trace(
+                "AllowedTokens",
+                format!("{:?}", self.get_action_tokens(ctx.action)),
+                format!("all in {:?}", tokens),
+                vec![],
+            ),
+            Condition::TimeWindow { start, end, days } => {
+                let local_secs = (ctx.current_time / 1_000_000_000) as i64 + ctx.utc_offset_minutes as i64 * 60;
+                let actual = format!(
+                    "weekday {} {:02}:{:02} (UTC{:+} min)",
+                    Self::weekday(local_secs.div_euclid(SECONDS_PER_DAY)),
+                    local_secs.rem_euclid(SECONDS_PER_DAY) / 3600,
+                    local_secs.rem_euclid(3600) / 60,
+                    ctx.utc_offset_minutes,
+                );
+                let expected = match days {
+                    Some(mask) => format!("[{:02}:00, {:02}:00) on days {:#09b}", start, end, mask),
+                    None => format!("[{:02}:00, {:02}:00)", start, end),
+                };
+                trace("TimeWindow", actual, expected, vec![])
+            }
+            Condition::Cooldown(seconds) => {
+                let actual = match ctx.last_execution {
+                    Some(last) => format!("{}s since last execution", ctx.current_time.saturating_sub(last) / 1_000_000_000),
+                    None => "no previous execution".to_string(),
+                };
+                trace("Cooldown", actual, format!(">= {}s", seconds), vec![])
+            }
+            Condition::AllowedRecipients(addresses) => {
+                let to = match ctx.action {
+                    Action::Transfer { to, .. } => Some(to),
+                    _ => None,
+                };
+                trace("AllowedRecipients", counterparty(to), format!("one of {:?}", addresses), vec![])
+            }
+            Condition::DeniedRecipients(addresses) => {
+                let to = match ctx.action {
+                    Action::Transfer { to, .. } => Some(to),
+                    _ => None,
+                };
+                trace("DeniedRecipients", counterparty(to), format!("none of {:?}", addresses), vec![])
+            }
+            Condition::AllowedSpenders(addresses) => {
+                let spender = match ctx.action {
+                    Action::ApproveToken { spender, .. } => Some(spender),
+                    _ => None,
+                };
+                trace("AllowedSpenders", counterparty(spender), format!("one of {:?}", addresses), vec![])
+            }
+            Condition::All(conditions) => {
+                let nested = nested_traces(conditions);
+                let passed_count = nested.iter().filter(|t| t.passed).count();
+                trace("All", format!("{} of {} passed", passed_count, nested.len()), "all pass".to_string(), nested)
+            }
+            Condition::Any(conditions) => {
+                let nested = nested_traces(conditions);
+                let passed_count = nested.iter().filter(|t| t.passed).count();
+                trace("Any", format!("{} of {} passed", passed_count, nested.len()), "at least one passes".to_string(), nested)
+            }
+            Condition::Not(condition) => {
+                let nested = vec![self.trace_condition(condition, ctx)];
+                let actual = if nested[0].passed { "nested passed" } else { "nested failed" };
+                trace("Not", actual.to_string(), "nested fails".to_string(), nested)
+            }
+        }
+    }
+
+    // A condition list matches when every condition matches (implicit AND)
+    fn conditions_match(&self, conditions: &[Condition], ctx: &EvalContext) -> bool {
+        conditions.iter().all(|condition| self.condition_matches(condition, ctx))
+    }
+
+    fn condition_matches(&self, condition: &Condition, ctx: &EvalContext) -> bool {
+        let amount = self.get_action_amount(ctx.action);
+
+        match condition {
+            Condition::MaxAmount(max) => amount <= *max,
+            Condition::MinAmount(min) => amount >= *min,
+            Condition::DailyLimit(limit) => ctx.daily_spent.saturating_add(amount) <= *limit,
+            Condition::AllowedChains(chains) => chains.contains(&self.get_action_chain(ctx.action)),
+            Condition::AllowedTokens(tokens) => {
+                self.get_action_tokens(ctx.action)
+                    .iter()
+                    .all(|token| tokens.contains(token))
+            }
+            Condition::TimeWindow { start, end, days } => {
+                Self::time_window_matches(*start, *end, *days, ctx.current_time, ctx.utc_offset_minutes)
+            }
+            Condition::Cooldown(seconds) => match ctx.last_execution {
+                Some(last) => {
+                    let elapsed = ctx.current_time.saturating_sub(last);
+                    elapsed >= seconds.saturating_mul(1_000_000_000)
                 }
+                None => true,
+            },
+            // Recipient/spender conditions only constrain actions that have one
+            Condition::AllowedRecipients(addresses) => match ctx.action {
+                Action::Transfer { to, .. } => Self::address_in(to, addresses),
+                _ => true,
+            },
+            Condition::DeniedRecipients(addresses) => match ctx.action {
+                Action::Transfer { to, .. } => !Self::address_in(to, addresses),
+                _ => true,
+            },
+            Condition::AllowedSpenders(addresses) => match ctx.action {
+                Action::ApproveToken { spender, .. } => Self::address_in(spender, addresses),
+                _ => true,
+            },
+            Condition::All(conditions) => self.conditions_match(conditions, ctx),
+            Condition::Any(conditions) => {
+                conditions.iter().any(|condition| self.condition_matches(condition, ctx))
             }
+            Condition::Not(condition) => !self.condition_matches(condition, ctx),
         }
-        true
+    }
+
+    // EVM addresses compare case-insensitively (EIP-55 checksums only change case)
+    fn address_in(address: &str, addresses: &[String]) -> bool {
+        let address = address.trim();
+        addresses.iter().any(|a| a.trim().eq_ignore_ascii_case(address))
+    }
+
+    // Check whether current_time falls inside [start, end) local hours.
+    // A window with start > end wraps past midnight (e.g. 22..6); the early-morning
+    // part then belongs to the previous day for the weekday mask.
+    fn time_window_matches(
+        start: u64,
+        end: u64,
+        days: Option<u8>,
+        current_time: u64,
+        utc_offset_minutes: i32,
+    ) -> bool {
+        if start > 24 || end > 24 {
+            return false;
+        }
+
+        let local_secs = (current_time / 1_000_000_000) as i64 + utc_offset_minutes as i64 * 60;
+        let day = local_secs.div_euclid(SECONDS_PER_DAY);
+        let hour = (local_secs.rem_euclid(SECONDS_PER_DAY) / 3600) as u64;
+
+        let window_day = if start <= end {
+            if hour < start || hour >= end {
+                return false;
+            }
+            day
+        } else if hour >= start {
+            day
+        } else if hour < end {
+            day - 1
+        } else {
+            return false;
+        };
+
+        match days {
+            Some(mask) => mask & (1 << Self::weekday(window_day)) != 0,
+            None => true,
+        }
+    }
+
+    // Weekday for a day count since the epoch (0 = Monday, 6 = Sunday)
+    fn weekday(days_since_epoch: i64) -> u32 {
+        // 1970-01-01 was a Thursday
+        (days_since_epoch + 3).rem_euclid(7) as u32
     }
 
     fn get_action_amount(&self, action: &Action) -> u64 {
@@ -158,6 +401,14 @@ impl AccessControl {
         }
     }
 
+    fn get_action_type(action: &Action) -> String {
+        match action {
+            Action::Swap { .. } => "swap".to_string(),
+            Action::Transfer { .. } => "transfer".to_string(),
+            Action::ApproveToken { .. } => "approve".to_string(),
+        }
+    }
+
     fn policy_action_to_decision(&self, action: &PolicyAction) -> PolicyDecision {
         match action {
             PolicyAction::Allow => PolicyDecision::Allowed,
@@ -166,61 +417,362 @@ impl AccessControl {
         }
     }
 
-    // CRUD operations for roles
-    pub fn assign_role(&mut self, principal: Principal, role: Role) {
-        let roles = self.role_assignments
-            .entry(principal)
-            .or_insert_with(Vec::new);
+    // CRUD operations for custom roles
+    pub fn create_role(&mut self, name: String, permissions: Vec<Permission>) -> ChainGuardResult<()> {
+        if name.trim().is_empty() {
+            return Err(ChainGuardError::InvalidConfiguration { reason: "Role name is empty".to_string() });
+        }
+        if Self::is_builtin_role_name(&name) || self.custom_roles.contains_key(&name) {
+            return Err(ChainGuardError::RoleAlreadyExists { name });
+        }
+        self.custom_roles.insert(name, Self::dedup_permissions(permissions));
+        Ok(())
+    }
 
-        if !roles.contains(&role) {
-            roles.push(role);
+    pub fn update_role(&mut self, name: String, permissions: Vec<Permission>) -> ChainGuardResult<()> {
+        match self.custom_roles.get_mut(&name) {
+            Some(existing) => {
+                *existing = Self::dedup_permissions(permissions);
+                Ok(())
+            }
+            None => Err(ChainGuardError::RoleNotFound { name }),
+        }
+    }
+
+    // Refused while any principal still holds the role
+    pub fn delete_role(&mut self, name: String) -> ChainGuardResult<()> {
+        if !self.custom_roles.contains_key(&name) {
+            return Err(ChainGuardError::RoleNotFound { name });
         }
+        let role = Role::Custom(name.clone());
+        if self.role_assignments.values().flatten().any(|g| g.role == role) {
+            return Err(ChainGuardError::RoleInUse { name });
+        }
+        self.custom_roles.remove(&name);
+        Ok(())
+    }
+
+    // Built-in roles first, then custom roles by name
+    pub fn list_roles(&self) -> Vec<RoleDefinition> {
+        let mut names: Vec<&String> = self.custom_roles.keys().collect();
+        names.sort();
+
+        [Role::Owner, Role::Operator, Role::Viewer]
+            .into_iter()
+            .chain(names.into_iter().map(|name| Role::Custom(name.clone())))
+            .map(|role| RoleDefinition {
+                permissions: self.role_permissions(&role),
+                role,
+            })
+            .collect()
     }
 
-    pub fn revoke_role(&mut self, principal: &Principal, role: &Role) {
-        if let Some(roles) = self.role_assignments.get_mut(principal) {
-            roles.retain(|r| r != role);
+    pub fn role_exists(&self, role: &Role) -> bool {
+        match role {
+            Role::Custom(name) => self.custom_roles.contains_key(name),
+            _ => true,
         }
     }
 
-    pub fn list_role_assignments(&self) -> Vec<(Principal, Role)> {
-        let mut assignments = Vec::new();
-        for (principal, roles) in &self.role_assignments {
-            for role in roles {
-                assignments.push((*principal, role.clone()));
+    fn is_builtin_role_name(name: &str) -> bool {
+        ["Owner", "Operator", "Viewer"].iter().any(|b| b.eq_ignore_ascii_case(name))
+    }
+
+    fn dedup_permissions(permissions: Vec<Permission>) -> Vec<Permission> {
+        let mut unique = Vec::new();
+        for permission in permissions {
+            if !unique.contains(&permission) {
+                unique.push(permission);
             }
         }
-        assignments
+        unique
+    }
+
+    // Restore custom role from stable memory (for post_upgrade)
+    pub fn restore_role_definition(&mut self, name: String, permissions: Vec<Permission>) {
+        self.custom_roles.insert(name, permissions);
+    }
+
+    pub fn list_custom_roles(&self) -> Vec<(String, Vec<Permission>)> {
+        self.custom_roles
+            .iter()
+            .map(|(name, permissions)| (name.clone(), permissions.clone()))
+            .collect()
+    }
+
+    // CRUD operations for roles
+    // Re-assigning a role the principal already holds replaces the grant (e.g. to extend expiry)
+    // Returns the grant of the same role this one replaced, if any
+    pub fn assign_role(&mut self, assignment: RoleAssignment) -> Option<RoleAssignment> {
+        let grants = self.role_assignments
+            .entry(assignment.principal)
+            .or_default();
+
+        let previous = grants.iter().position(|g| g.role == assignment.role).map(|i| grants.remove(i));
+        grants.push(assignment);
+        previous
+    }
+
+    // Refuses to revoke the Owner role unless another principal keeps a non-expiring
+    // Owner grant (expiring grants would leave the canister ownerless); returns the removed grant
+    pub fn revoke_role(
+        &mut self,
+        principal: &Principal,
+        role: &Role,
+        current_time: u64,
+    ) -> ChainGuardResult<Option<RoleAssignment>> {
+        if *role == Role::Owner
+            && self.has_role(principal, &Role::Owner, current_time)
+            && !self.has_other_permanent_owner(principal)
+        {
+            return Err(ChainGuardError::LastOwner);
+        }
+
+        let grants = match self.role_assignments.get_mut(principal) {
+            Some(grants) => grants,
+            None => return Ok(None),
+        };
+        Ok(grants.iter().position(|g| &g.role == role).map(|i| grants.remove(i)))
+    }
+
+    // Whether any principal other than `principal` holds a non-expiring Owner grant
+    pub fn has_other_permanent_owner(&self, principal: &Principal) -> bool {
+        self.role_assignments
+            .iter()
+            .filter(|(p, _)| *p != principal)
+            .any(|(_, grants)| grants.iter().any(|g| g.role == Role::Owner && g.expires_at.is_none()))
+    }
+
+    // ============== OWNERSHIP TRANSFER ==============
+
+    // First step: an Owner names the principal that should take over
+    pub fn propose_owner(
+        &mut self,
+        proposed_owner: Principal,
+        proposed_by: Principal,
+        current_time: u64,
+    ) -> ChainGuardResult<OwnershipProposal> {
+        if proposed_owner == proposed_by {
+            return Err(ChainGuardError::InvalidConfiguration {
+                reason: "Cannot propose yourself as owner".to_string(),
+            });
+        }
+
+        let proposal = OwnershipProposal {
+            proposed_owner,
+            proposed_by,
+            proposed_at: current_time,
+        };
+        self.ownership_proposal = Some(proposal.clone());
+        Ok(proposal)
+    }
+
+    // Second step: the proposed principal calls in; Owner moves from the proposer to them
+    pub fn accept_ownership(&mut self, caller: &Principal, current_time: u64) -> ChainGuardResult<OwnershipProposal> {
+        let proposal = self.ownership_proposal
+            .clone()
+            .ok_or(ChainGuardError::OwnershipProposalNotFound)?;
+
+        if proposal.proposed_owner != *caller {
+            return Err(ChainGuardError::Unauthorized);
+        }
+
+        // A proposer who lost Owner in the meantime can no longer hand it over
+        if !self.has_role(&proposal.proposed_by, &Role::Owner, current_time) {
+            self.ownership_proposal = None;
+            return Err(ChainGuardError::OwnershipProposalNotFound);
+        }
+
+        self.assign_role(RoleAssignment {
+            principal: *caller,
+            role: Role::Owner,
+            assigned_at: current_time,
+            assigned_by: proposal.proposed_by,
+            expires_at: None,
+        });
+        self.revoke_role(&proposal.proposed_by, &Role::Owner, current_time)?;
+        self.ownership_proposal = None;
+        Ok(proposal)
+    }
+
+    pub fn cancel_ownership_proposal(&mut self) -> ChainGuardResult<()> {
+        self.ownership_proposal
+            .take()
+            .map(|_| ())
+            .ok_or(ChainGuardError::OwnershipProposalNotFound)
+    }
+
+    pub fn ownership_proposal(&self) -> Option<&OwnershipProposal> {
+        self.ownership_proposal.as_ref()
+    }
+
+    // Restore pending ownership proposal from stable memory (for post_upgrade)
+    pub fn restore_ownership_proposal(&mut self, proposal: Option<OwnershipProposal>) {
+        self.ownership_proposal = proposal;
+    }
+
+    // Unexpired (principal, role) pairs
+    pub fn list_role_assignments(&self, current_time: u64) -> Vec<(Principal, Role)> {
+        self.list_role_grants()
+            .into_iter()
+            .filter(|g| g.is_active(current_time))
+            .map(|g| (g.principal, g.role))
+            .collect()
+    }
+
+    // Every grant with its metadata, including expired ones not yet pruned
+    pub fn list_role_grants(&self) -> Vec<RoleAssignment> {
+        self.role_assignments.values().flatten().cloned().collect()
+    }
+
+    // One principal's grants, including expired ones not yet pruned
+    pub fn get_role_grants(&self, principal: &Principal) -> Vec<RoleAssignment> {
+        self.role_assignments.get(principal).cloned().unwrap_or_default()
+    }
+
+    // Drop expired grants; returns what was removed
+    pub fn prune_expired_roles(&mut self, current_time: u64) -> Vec<RoleAssignment> {
+        let mut expired = Vec::new();
+        for grants in self.role_assignments.values_mut() {
+            let (active, stale): (Vec<RoleAssignment>, Vec<RoleAssignment>) =
+                grants.drain(..).partition(|g| g.is_active(current_time));
+            *grants = active;
+            expired.extend(stale);
+        }
+        self.role_assignments.retain(|_, grants| !grants.is_empty());
+        expired
     }
 
     // CRUD operations for policies
-    pub fn add_policy(&mut self, policy: Policy) -> u64 {
-        let id = self.policies.len() as u64;
+    // IDs are monotonic and never reused, so a stored ID always refers to the same rule
+    pub fn add_policy(&mut self, mut policy: Policy) -> u64 {
+        let id = self.next_policy_id;
+        self.next_policy_id += 1;
+        policy.id = Some(id);
         self.policies.push(policy);
         id
     }
 
-    pub fn update_policy(&mut self, index: usize, policy: Policy) -> bool {
-        if index < self.policies.len() {
-            self.policies[index] = policy;
-            true
-        } else {
-            false
+    pub fn update_policy(&mut self, id: u64, mut policy: Policy) -> bool {
+        match self.policies.iter_mut().find(|p| p.id == Some(id)) {
+            Some(existing) => {
+                policy.id = Some(id);
+                *existing = policy;
+                true
+            }
+            None => false,
         }
     }
 
-    pub fn remove_policy(&mut self, index: usize) -> bool {
-        if index < self.policies.len() {
-            self.policies.remove(index);
-            true
-        } else {
-            false
+    pub fn remove_policy(&mut self, id: u64) -> bool {
+        let before = self.policies.len();
+        self.policies.retain(|p| p.id != Some(id));
+        self.policies.len() < before
+    }
+
+    pub fn get_policy(&self, id: u64) -> Option<&Policy> {
+        self.policies.iter().find(|p| p.id == Some(id))
+    }
+
+    pub fn next_policy_id(&self) -> u64 {
+        self.next_policy_id
+    }
+
+    // Restore policies from stable memory (for post_upgrade); policies stored
+    // before IDs existed get fresh ones after the highest known ID
+    pub fn restore_policies(&mut self, policies: Vec<Policy>, next_policy_id: u64) {
+        let highest = policies.iter().filter_map(|p| p.id).max();
+        self.next_policy_id = next_policy_id.max(highest.map_or(0, |id| id + 1));
+
+        let (with_id, legacy): (Vec<Policy>, Vec<Policy>) =
+            policies.into_iter().partition(|p| p.id.is_some());
+        self.policies = with_id;
+        self.policies.sort_by_key(|p| p.id);
+        for policy in legacy {
+            self.add_policy(policy);
         }
     }
 
+    // Replace the whole policy set (rollback); the ID counter is kept so IDs stay unique
+    pub fn replace_policies(&mut self, policies: Vec<Policy>) {
+        self.restore_policies(policies, self.next_policy_id);
+    }
+
+    fn policy_id(policy: &Policy) -> u64 {
+        policy.id.unwrap_or_default()
+    }
+
     pub fn get_policies(&self) -> Vec<Policy> {
         self.policies.clone()
     }
+
+    // Execution tracking for Cooldown conditions; in-flight executions count as executed
+    fn last_execution(&self, requester: &Principal, policy_id: u64, action: &Action) -> Option<u64> {
+        let action_type = Self::get_action_type(action);
+        let key = (*requester, policy_id, action_type.clone());
+        let reserved = self
+            .cooldown_reservations
+            .values()
+            .filter(|r| r.requester == *requester && r.policy_id == policy_id && r.action_type == action_type)
+            .map(|r| r.executed_at)
+            .max();
+        self.last_executions.get(&key).copied().max(reserved)
+    }
+
+    // Start the cooldown for an action that has passed policy but not executed yet
+    pub fn reserve_execution(
+        &mut self,
+        audit_id: u64,
+        requester: Principal,
+        policy_id: u64,
+        action: &Action,
+        current_time: u64,
+    ) {
+        let record = ExecutionRecord {
+            requester,
+            policy_id,
+            action_type: Self::get_action_type(action),
+            executed_at: current_time,
+        };
+        self.cooldown_reservations.insert(audit_id, record);
+    }
+
+    // Drop a cooldown reservation (execution failed)
+    pub fn release_execution(&mut self, audit_id: u64) -> Option<ExecutionRecord> {
+        self.cooldown_reservations.remove(&audit_id)
+    }
+
+    // Turn a cooldown reservation into a recorded execution
+    pub fn commit_execution(&mut self, audit_id: u64, executed_at: u64) -> Option<ExecutionRecord> {
+        let record = ExecutionRecord { executed_at, ..self.cooldown_reservations.remove(&audit_id)? };
+        self.restore_execution(record.clone());
+        Some(record)
+    }
+
+    // Record a successful execution; returns the record so callers can persist it
+    pub fn record_execution(
+        &mut self,
+        requester: Principal,
+        policy_id: u64,
+        action: &Action,
+        executed_at: u64,
+    ) -> ExecutionRecord {
+        let record = ExecutionRecord {
+            requester,
+            policy_id,
+            action_type: Self::get_action_type(action),
+            executed_at,
+        };
+        self.restore_execution(record.clone());
+        record
+    }
+
+    // Restore execution record from stable memory (for post_upgrade)
+    pub fn restore_execution(&mut self, record: ExecutionRecord) {
+        let key = (record.requester, record.policy_id, record.action_type);
+        let last = self.last_executions.entry(key).or_insert(0);
+        *last = (*last).max(record.executed_at);
+    }
 }
 
 impl Default for AccessControl {
@@ -240,21 +792,107 @@ mod tests {
         Principal::from_slice(&bytes)
     }
 
+    fn permanent(principal: Principal, role: Role) -> RoleAssignment {
+        RoleAssignment {
+            principal,
+            role,
+            assigned_at: 0,
+            assigned_by: principal,
+            expires_at: None,
+        }
+    }
+
+    // 1970-01-05 was a Monday
+    const MONDAY: u64 = 4;
+
+    fn mock_time(day: u64, hour: u64, minute: u64) -> u64 {
+        seconds(((day * 24 + hour) * 60 + minute) * 60)
+    }
+
+    fn seconds(secs: u64) -> u64 {
+        secs * 1_000_000_000
+    }
+
     #[test]
     fn test_role_assignment() {
         let mut ac = AccessControl::new();
         let principal = mock_principal(1);
 
         // Initially no roles
-        assert!(!ac.has_role(&principal, &Role::Owner));
+        assert!(!ac.has_role(&principal, &Role::Owner, 0));
 
         // Assign role
-        ac.assign_role(principal, Role::Owner);
-        assert!(ac.has_role(&principal, &Role::Owner));
+        ac.assign_role(permanent(principal, Role::Owner));
+        assert!(ac.has_role(&principal, &Role::Owner, 0));
 
         // Revoke role
-        ac.revoke_role(&principal, &Role::Owner);
-        assert!(!ac.has_role(&principal, &Role::Owner));
+        assert_eq!(ac.assign_role(permanent(principal, Role::Operator)), None);
+        assert_eq!(
+            ac.revoke_role(&principal, &Role::Operator, 0),
+            Ok(Some(permanent(principal, Role::Operator)))
+        );
+        assert_eq!(ac.revoke_role(&principal, &Role::Operator, 0), Ok(None));
+        assert!(!ac.has_role(&principal, &Role::Operator, 0));
+        assert!(ac.has_role(&principal, &Role::Owner, 0));
+    }
+
+    #[test]
+    fn test_cannot_revoke_last_owner() {
+        let mut ac = AccessControl::new();
+        let owner1 = mock_principal(1);
+        let owner2 = mock_principal(2);
+
+        ac.assign_role(permanent(owner1, Role::Owner));
+        assert_eq!(ac.revoke_role(&owner1, &Role::Owner, 0), Err(ChainGuardError::LastOwner));
+        assert!(ac.has_role(&owner1, &Role::Owner, 0));
+
+        // An expiring Owner grant does not count as a remaining Owner, even while active
+        ac.assign_role(RoleAssignment {
+            expires_at: Some(seconds(100)),
+            ..permanent(owner2, Role::Owner)
+        });
+        assert!(ac.revoke_role(&owner1, &Role::Owner, seconds(200)).is_err());
+        assert_eq!(ac.revoke_role(&owner1, &Role::Owner, seconds(50)), Err(ChainGuardError::LastOwner));
+        assert!(!ac.has_other_permanent_owner(&owner1));
+
+        // With another permanent Owner the expiring one can go, then the first
+        let owner3 = mock_principal(3);
+        ac.assign_role(permanent(owner3, Role::Owner));
+        assert!(ac.revoke_role(&owner2, &Role::Owner, seconds(50)).is_ok());
+        assert!(ac.revoke_role(&owner1, &Role::Owner, seconds(50)).is_ok());
+
+        // Revoking Owner from a non-owner is a no-op, even with one Owner left
+        assert!(ac.revoke_role(&owner1, &Role::Owner, seconds(50)).is_ok());
+        assert!(ac.has_other_permanent_owner(&owner1));
+        assert!(!ac.has_other_permanent_owner(&owner3));
+    }
+
+    #[test]
+    fn test_ownership_transfer() {
+        let mut ac = AccessControl::new();
+        let owner = mock_principal(1);
+        let successor = mock_principal(2);
+        let stranger = mock_principal(3);
+        ac.assign_role(permanent(owner, Role::Owner));
+
+        assert_eq!(ac.accept_ownership(&successor, 0), Err(ChainGuardError::OwnershipProposalNotFound));
+        assert!(ac.propose_owner(owner, owner, 0).is_err());
+
+        ac.propose_owner(successor, owner, 10).unwrap();
+        assert_eq!(ac.accept_ownership(&stranger, 20), Err(ChainGuardError::Unauthorized));
+        // Proposing alone moves nothing
+        assert!(!ac.has_role(&successor, &Role::Owner, 20));
+
+        let proposal = ac.accept_ownership(&successor, 20).unwrap();
+        assert_eq!(proposal.proposed_by, owner);
+        assert!(ac.has_role(&successor, &Role::Owner, 20));
+        assert!(!ac.has_role(&owner, &Role::Owner, 20));
+        assert!(ac.ownership_proposal().is_none());
+
+        // Cancelled proposals cannot be accepted
+        ac.propose_owner(stranger, successor, 30).unwrap();
+        ac.cancel_ownership_proposal().unwrap();
+        assert_eq!(ac.accept_ownership(&stranger, 40), Err(ChainGuardError::OwnershipProposalNotFound));
     }
 
     #[test]
@@ -262,52 +900,115 @@ mod tests {
         let mut ac = AccessControl::new();
         let principal = mock_principal(1);
 
-        ac.assign_role(principal, Role::Owner);
-        ac.assign_role(principal, Role::Operator);
+        ac.assign_role(permanent(principal, Role::Owner));
+        ac.assign_role(permanent(principal, Role::Operator));
 
-        assert!(ac.has_role(&principal, &Role::Owner));
-        assert!(ac.has_role(&principal, &Role::Operator));
-        assert!(!ac.has_role(&principal, &Role::Viewer));
+        assert!(ac.has_role(&principal, &Role::Owner, 0));
+        assert!(ac.has_role(&principal, &Role::Operator, 0));
+        assert!(!ac.has_role(&principal, &Role::Viewer, 0));
     }
 
     #[test]
     fn test_permissions_owner() {
         let mut ac = AccessControl::new();
         let principal = mock_principal(1);
-        ac.assign_role(principal, Role::Owner);
+        ac.assign_role(permanent(principal, Role::Owner));
 
         // Owner has all permissions
-        assert!(ac.has_permission(&principal, &Permission::Execute));
-        assert!(ac.has_permission(&principal, &Permission::Configure));
-        assert!(ac.has_permission(&principal, &Permission::ViewLogs));
-        assert!(ac.has_permission(&principal, &Permission::Sign));
-        assert!(ac.has_permission(&principal, &Permission::Emergency));
+        assert!(ac.has_permission(&principal, &Permission::Execute, 0));
+        assert!(ac.has_permission(&principal, &Permission::Configure, 0));
+        assert!(ac.has_permission(&principal, &Permission::ViewLogs, 0));
+        assert!(ac.has_permission(&principal, &Permission::Sign, 0));
+        assert!(ac.has_permission(&principal, &Permission::Emergency, 0));
     }
 
     #[test]
     fn test_permissions_operator() {
         let mut ac = AccessControl::new();
         let principal = mock_principal(1);
-        ac.assign_role(principal, Role::Operator);
+        ac.assign_role(permanent(principal, Role::Operator));
 
-        assert!(ac.has_permission(&principal, &Permission::Execute));
-        assert!(ac.has_permission(&principal, &Permission::Sign));
-        assert!(ac.has_permission(&principal, &Permission::ViewLogs));
-        assert!(!ac.has_permission(&principal, &Permission::Configure));
-        assert!(!ac.has_permission(&principal, &Permission::Emergency));
+        assert!(ac.has_permission(&principal, &Permission::Execute, 0));
+        assert!(ac.has_permission(&principal, &Permission::Sign, 0));
+        assert!(ac.has_permission(&principal, &Permission::ViewLogs, 0));
+        assert!(!ac.has_permission(&principal, &Permission::Configure, 0));
+        assert!(!ac.has_permission(&principal, &Permission::Emergency, 0));
     }
 
     #[test]
     fn test_permissions_viewer() {
         let mut ac = AccessControl::new();
         let principal = mock_principal(1);
-        ac.assign_role(principal, Role::Viewer);
+        ac.assign_role(permanent(principal, Role::Viewer));
+
+        assert!(ac.has_permission(&principal, &Permission::ViewLogs, 0));
+        assert!(!ac.has_permission(&principal, &Permission::Execute, 0));
+        assert!(!ac.has_permission(&principal, &Permission::Configure, 0));
+        assert!(!ac.has_permission(&principal, &Permission::Sign, 0));
+        assert!(!ac.has_permission(&principal, &Permission::Emergency, 0));
+    }
 
-        assert!(ac.has_permission(&principal, &Permission::ViewLogs));
-        assert!(!ac.has_permission(&principal, &Permission::Execute));
-        assert!(!ac.has_permission(&principal, &Permission::Configure));
-        assert!(!ac.has_permission(&principal, &Permission::Sign));
-        assert!(!ac.has_permission(&principal, &Permission::Emergency));
+    #[test]
+    fn test_custom_role_permissions() {
+        let mut ac = AccessControl::new();
+        let agent = mock_principal(1);
+        let risk = mock_principal(2);
+
+        ac.create_role("TradingAgent".to_string(), vec![Permission::Execute]).unwrap();
+        ac.create_role(
+            "RiskOfficer".to_string(),
+            vec![Permission::Sign, Permission::Emergency, Permission::ViewLogs, Permission::Sign],
+        )
+        .unwrap();
+        ac.assign_role(permanent(agent, Role::Custom("TradingAgent".to_string())));
+        ac.assign_role(permanent(risk, Role::Custom("RiskOfficer".to_string())));
+
+        assert!(ac.has_permission(&agent, &Permission::Execute, 0));
+        assert!(!ac.has_permission(&agent, &Permission::ViewLogs, 0));
+        assert!(ac.has_permission(&risk, &Permission::Emergency, 0));
+        assert!(!ac.has_permission(&risk, &Permission::Execute, 0));
+
+        // Updating the role changes every holder's permissions
+        ac.update_role("TradingAgent".to_string(), vec![Permission::Execute, Permission::ViewLogs]).unwrap();
+        assert!(ac.has_permission(&agent, &Permission::ViewLogs, 0));
+
+        let roles = ac.list_roles();
+        assert_eq!(roles.len(), 5);
+        assert_eq!(roles[0].role, Role::Owner);
+        assert_eq!(
+            roles[3],
+            RoleDefinition {
+                role: Role::Custom("RiskOfficer".to_string()),
+                permissions: vec![Permission::Sign, Permission::Emergency, Permission::ViewLogs],
+            }
+        );
+    }
+
+    #[test]
+    fn test_custom_role_errors() {
+        let mut ac = AccessControl::new();
+        let agent = mock_principal(1);
+
+        assert_eq!(
+            ac.create_role("owner".to_string(), vec![]),
+            Err(ChainGuardError::RoleAlreadyExists { name: "owner".to_string() })
+        );
+        ac.create_role("TradingAgent".to_string(), vec![Permission::Execute]).unwrap();
+        assert!(ac.create_role("TradingAgent".to_string(), vec![]).is_err());
+        assert_eq!(
+            ac.update_role("Missing".to_string(), vec![]),
+            Err(ChainGuardError::RoleNotFound { name: "Missing".to_string() })
+        );
+
+        // Cannot delete a role that is still assigned
+        ac.assign_role(permanent(agent, Role::Custom("TradingAgent".to_string())));
+        assert_eq!(
+            ac.delete_role("TradingAgent".to_string()),
+            Err(ChainGuardError::RoleInUse { name: "TradingAgent".to_string() })
+        );
+        ac.revoke_role(&agent, &Role::Custom("TradingAgent".to_string()), 0).unwrap();
+        assert!(ac.delete_role("TradingAgent".to_string()).is_ok());
+        assert!(!ac.role_exists(&Role::Custom("TradingAgent".to_string())));
     }
 
     #[test]
@@ -315,10 +1016,12 @@ mod tests {
         let mut ac = AccessControl::new();
 
         let policy = Policy {
+            id: None,
             name: "Test Policy".to_string(),
             conditions: vec![Condition::MaxAmount(1000)],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         let id = ac.add_policy(policy);
@@ -335,10 +1038,12 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Allow Small".to_string(),
             conditions: vec![Condition::MaxAmount(1000)],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -350,7 +1055,7 @@ mod tests {
             amount: 500,
         };
 
-        let result = ac.evaluate_action(&action, &principal, 0);
+        let result = ac.evaluate_action(&action, &principal, 0, 0);
         assert_eq!(result.decision, PolicyDecision::Allowed);
     }
 
@@ -360,10 +1065,12 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Deny Large".to_string(),
             conditions: vec![Condition::MaxAmount(1000)],
             action: PolicyAction::Deny,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -375,7 +1082,7 @@ mod tests {
             amount: 2000,
         };
 
-        let result = ac.evaluate_action(&action, &principal, 0);
+        let result = ac.evaluate_action(&action, &principal, 0, 0);
         assert_eq!(result.decision, PolicyDecision::Denied);
     }
 
@@ -385,10 +1092,12 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Daily Limit".to_string(),
             conditions: vec![Condition::DailyLimit(5000)],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -401,11 +1110,11 @@ mod tests {
         };
 
         // First transfer - within daily limit
-        let result = ac.evaluate_action(&action, &principal, 3000);
+        let result = ac.evaluate_action(&action, &principal, 3000, 0);
         assert_eq!(result.decision, PolicyDecision::Allowed);
 
         // Second transfer - exceeds daily limit
-        let result = ac.evaluate_action(&action, &principal, 4500);
+        let result = ac.evaluate_action(&action, &principal, 4500, 0);
         assert_eq!(result.decision, PolicyDecision::Denied);
     }
 
@@ -415,10 +1124,12 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Allowed Chains".to_string(),
             conditions: vec![Condition::AllowedChains(vec!["ethereum".to_string(), "polygon".to_string()])],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -430,7 +1141,7 @@ mod tests {
             to: "0x123".to_string(),
             amount: 1000,
         };
-        let result1 = ac.evaluate_action(&action1, &principal, 0);
+        let result1 = ac.evaluate_action(&action1, &principal, 0, 0);
         assert_eq!(result1.decision, PolicyDecision::Allowed);
 
         // Disallowed chain
@@ -440,7 +1151,7 @@ mod tests {
             to: "0x123".to_string(),
             amount: 1000,
         };
-        let result2 = ac.evaluate_action(&action2, &principal, 0);
+        let result2 = ac.evaluate_action(&action2, &principal, 0, 0);
         assert_eq!(result2.decision, PolicyDecision::Denied);
     }
 
@@ -451,18 +1162,22 @@ mod tests {
 
         // Lower priority (0) - should be evaluated first
         let deny_policy = Policy {
+            id: None,
             name: "Deny Large".to_string(),
             conditions: vec![Condition::MaxAmount(10000)],
             action: PolicyAction::Deny,
             priority: 0,
+            utc_offset_minutes: None,
         };
 
         // Higher priority (1) - should be evaluated second
         let allow_policy = Policy {
+            id: None,
             name: "Allow Small".to_string(),
             conditions: vec![Condition::MaxAmount(1000)],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(allow_policy);
@@ -475,7 +1190,7 @@ mod tests {
             to: "0x123".to_string(),
         };
 
-        let result = ac.evaluate_action(&action, &principal, 0);
+        let result = ac.evaluate_action(&action, &principal, 0, 0);
         // Should match deny_policy first (lower priority number = higher priority)
         assert_eq!(result.decision, PolicyDecision::Denied);
         assert_eq!(result.matched_policy, Some("Deny Large".to_string()));
@@ -486,10 +1201,10 @@ mod tests {
         let mut ac = AccessControl::new();
         let principal = mock_principal(1);
 
-        ac.assign_role(principal, Role::Owner);
-        ac.assign_role(principal, Role::Operator);
+        ac.assign_role(permanent(principal, Role::Owner));
+        ac.assign_role(permanent(principal, Role::Operator));
 
-        let roles = ac.get_roles(&principal);
+        let roles = ac.get_roles(&principal, 0);
         assert_eq!(roles.len(), 2);
         assert!(roles.contains(&Role::Owner));
         assert!(roles.contains(&Role::Operator));
@@ -501,13 +1216,68 @@ mod tests {
         let principal1 = mock_principal(1);
         let principal2 = mock_principal(2);
 
-        ac.assign_role(principal1, Role::Owner);
-        ac.assign_role(principal2, Role::Operator);
+        ac.assign_role(permanent(principal1, Role::Owner));
+        ac.assign_role(permanent(principal2, Role::Operator));
 
-        let assignments = ac.list_role_assignments();
+        let assignments = ac.list_role_assignments(0);
         assert_eq!(assignments.len(), 2);
     }
 
+    #[test]
+    fn test_expiring_role_grant() {
+        let mut ac = AccessControl::new();
+        let contractor = mock_principal(1);
+
+        ac.assign_role(RoleAssignment {
+            expires_at: Some(seconds(1000)),
+            ..permanent(contractor, Role::Operator)
+        });
+
+        assert!(ac.has_permission(&contractor, &Permission::Execute, seconds(999)));
+        assert!(!ac.has_permission(&contractor, &Permission::Execute, seconds(1000)));
+        assert!(ac.get_roles(&contractor, seconds(1000)).is_empty());
+        assert!(ac.list_role_assignments(seconds(1000)).is_empty());
+
+        // The expired grant is still listed until pruned
+        assert_eq!(ac.list_role_grants().len(), 1);
+
+        // Re-assigning replaces the grant, e.g. to extend it
+        let previous = ac.assign_role(RoleAssignment {
+            expires_at: Some(seconds(5000)),
+            ..permanent(contractor, Role::Operator)
+        });
+        assert_eq!(previous.and_then(|g| g.expires_at), Some(seconds(1000)));
+        assert_eq!(ac.list_role_grants().len(), 1);
+        assert!(ac.has_role(&contractor, &Role::Operator, seconds(1000)));
+    }
+
+    #[test]
+    fn test_prune_expired_roles() {
+        let mut ac = AccessControl::new();
+        let owner = mock_principal(1);
+        let agent = mock_principal(2);
+
+        ac.assign_role(permanent(owner, Role::Owner));
+        ac.assign_role(RoleAssignment {
+            expires_at: Some(seconds(1000)),
+            ..permanent(owner, Role::Viewer)
+        });
+        ac.assign_role(RoleAssignment {
+            expires_at: Some(seconds(2000)),
+            ..permanent(agent, Role::Operator)
+        });
+
+        let pruned = ac.prune_expired_roles(seconds(1500));
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(pruned[0].role, Role::Viewer);
+        assert_eq!(ac.list_role_grants().len(), 2);
+
+        let pruned = ac.prune_expired_roles(seconds(2000));
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(pruned[0].principal, agent);
+        assert_eq!(ac.list_role_assignments(seconds(2000)), vec![(owner, Role::Owner)]);
+    }
+
     // ==================== Advanced Policy Priority Tests ====================
 
     #[test]
@@ -517,29 +1287,38 @@ mod tests {
 
         // Priority 0 (highest): Deny everything > 5000
         let deny_policy = Policy {
+            id: None,
             name: "Deny Large".to_string(),
             conditions: vec![Condition::MaxAmount(10000)],
             action: PolicyAction::Deny,
             priority: 0,
+            utc_offset_minutes: None,
         };
 
         // Priority 1: Allow up to 1000
         let allow_small = Policy {
+            id: None,
             name: "Allow Small".to_string(),
             conditions: vec![Condition::MaxAmount(1000)],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         // Priority 2: Require threshold for mid amounts
         let threshold_mid = Policy {
+            id: None,
             name: "Threshold Mid".to_string(),
             conditions: vec![Condition::MaxAmount(5000)],
             action: PolicyAction::RequireThreshold {
                 required: 2,
                 from_roles: vec![Role::Owner, Role::Operator],
+                expiry_seconds: None,
+                timelock_seconds: None,
+                required_weight: None,
             },
             priority: 2,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(allow_small);
@@ -553,7 +1332,7 @@ mod tests {
             to: "0x123".to_string(),
             amount: 500,
         };
-        let result1 = ac.evaluate_action(&action1, &principal, 0);
+        let result1 = ac.evaluate_action(&action1, &principal, 0, 0);
         assert_eq!(result1.decision, PolicyDecision::Denied);
         assert_eq!(result1.matched_policy, Some("Deny Large".to_string()));
 
@@ -564,7 +1343,7 @@ mod tests {
             to: "0x123".to_string(),
             amount: 2000,
         };
-        let result2 = ac.evaluate_action(&action2, &principal, 0);
+        let result2 = ac.evaluate_action(&action2, &principal, 0, 0);
         assert_eq!(result2.decision, PolicyDecision::Denied);
         assert_eq!(result2.matched_policy, Some("Deny Large".to_string()));
     }
@@ -576,17 +1355,21 @@ mod tests {
 
         // Add policies in reverse priority order
         let policy_low = Policy {
+            id: None,
             name: "Low Priority Allow".to_string(),
             conditions: vec![Condition::MaxAmount(1000)],
             action: PolicyAction::Allow,
             priority: 10,
+            utc_offset_minutes: None,
         };
 
         let policy_high = Policy {
+            id: None,
             name: "High Priority Deny".to_string(),
             conditions: vec![Condition::MaxAmount(1000)],
             action: PolicyAction::Deny,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy_low);
@@ -599,7 +1382,7 @@ mod tests {
             amount: 500,
         };
 
-        let result = ac.evaluate_action(&action, &principal, 0);
+        let result = ac.evaluate_action(&action, &principal, 0, 0);
         // High priority (lower number) should win
         assert_eq!(result.decision, PolicyDecision::Denied);
         assert_eq!(result.matched_policy, Some("High Priority Deny".to_string()));
@@ -613,6 +1396,7 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Strict Policy".to_string(),
             conditions: vec![
                 Condition::MaxAmount(10000),
@@ -621,6 +1405,7 @@ mod tests {
             ],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -633,7 +1418,7 @@ mod tests {
             amount: 5000,
         };
 
-        let result = ac.evaluate_action(&action, &principal, 0);
+        let result = ac.evaluate_action(&action, &principal, 0, 0);
         assert_eq!(result.decision, PolicyDecision::Allowed);
     }
 
@@ -643,6 +1428,7 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Strict Policy".to_string(),
             conditions: vec![
                 Condition::MaxAmount(10000),
@@ -651,6 +1437,7 @@ mod tests {
             ],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -663,7 +1450,7 @@ mod tests {
             amount: 5000,
         };
 
-        let result = ac.evaluate_action(&action, &principal, 0);
+        let result = ac.evaluate_action(&action, &principal, 0, 0);
         assert_eq!(result.decision, PolicyDecision::Denied);
     }
 
@@ -673,6 +1460,7 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Complex Policy".to_string(),
             conditions: vec![
                 Condition::MinAmount(100),
@@ -682,6 +1470,7 @@ mod tests {
             ],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -693,7 +1482,7 @@ mod tests {
             to: "0x123".to_string(),
             amount: 50,
         };
-        let result1 = ac.evaluate_action(&action1, &principal, 0);
+        let result1 = ac.evaluate_action(&action1, &principal, 0, 0);
         assert_eq!(result1.decision, PolicyDecision::Denied);
 
         // Amount too large
@@ -703,7 +1492,7 @@ mod tests {
             to: "0x123".to_string(),
             amount: 15000,
         };
-        let result2 = ac.evaluate_action(&action2, &principal, 0);
+        let result2 = ac.evaluate_action(&action2, &principal, 0, 0);
         assert_eq!(result2.decision, PolicyDecision::Denied);
 
         // Wrong chain
@@ -713,7 +1502,7 @@ mod tests {
             to: "0x123".to_string(),
             amount: 5000,
         };
-        let result3 = ac.evaluate_action(&action3, &principal, 0);
+        let result3 = ac.evaluate_action(&action3, &principal, 0, 0);
         assert_eq!(result3.decision, PolicyDecision::Denied);
 
         // Exceeds daily limit
@@ -723,7 +1512,7 @@ mod tests {
             to: "0x123".to_string(),
             amount: 5000,
         };
-        let result4 = ac.evaluate_action(&action4, &principal, 48000);
+        let result4 = ac.evaluate_action(&action4, &principal, 48000, 0);
         assert_eq!(result4.decision, PolicyDecision::Denied);
 
         // All conditions match
@@ -733,10 +1522,322 @@ mod tests {
             to: "0x123".to_string(),
             amount: 5000,
         };
-        let result5 = ac.evaluate_action(&action5, &principal, 10000);
+        let result5 = ac.evaluate_action(&action5, &principal, 10000, 0);
         assert_eq!(result5.decision, PolicyDecision::Allowed);
     }
 
+    // ==================== Boolean Condition Trees ====================
+
+    fn mock_transfer_on(chain: &str, token: &str, amount: u64) -> Action {
+        Action::Transfer {
+            chain: chain.to_string(),
+            token: token.to_string(),
+            to: "0x123".to_string(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_condition_any_all_tree() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        // (USDC or USDT) and amount < 10k, or chain == sepolia
+        ac.add_policy(Policy {
+            id: None,
+            name: "Stablecoins or Testnet".to_string(),
+            conditions: vec![Condition::Any(vec![
+                Condition::All(vec![
+                    Condition::Any(vec![
+                        Condition::AllowedTokens(vec!["USDC".to_string()]),
+                        Condition::AllowedTokens(vec!["USDT".to_string()]),
+                    ]),
+                    Condition::MaxAmount(9999),
+                ]),
+                Condition::AllowedChains(vec!["sepolia".to_string()]),
+            ])],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+
+        let cases = [
+            (mock_transfer_on("ethereum", "USDC", 5000), PolicyDecision::Allowed),
+            (mock_transfer_on("ethereum", "USDT", 9999), PolicyDecision::Allowed),
+            (mock_transfer_on("ethereum", "USDT", 10000), PolicyDecision::Denied),
+            (mock_transfer_on("ethereum", "DAI", 100), PolicyDecision::Denied),
+            (mock_transfer_on("sepolia", "DAI", 1_000_000), PolicyDecision::Allowed),
+        ];
+        for (action, expected) in cases {
+            assert_eq!(ac.evaluate_action(&action, &principal, 0, 0).decision, expected);
+        }
+    }
+
+    #[test]
+    fn test_condition_not() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        // Anything except mainnet
+        ac.add_policy(Policy {
+            id: None,
+            name: "Not Mainnet".to_string(),
+            conditions: vec![
+                Condition::Not(Box::new(Condition::AllowedChains(vec!["ethereum".to_string()]))),
+                Condition::MaxAmount(10000),
+            ],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+
+        let result = ac.evaluate_action(&mock_transfer_on("polygon", "USDC", 500), &principal, 0, 0);
+        assert_eq!(result.decision, PolicyDecision::Allowed);
+
+        let result = ac.evaluate_action(&mock_transfer_on("ethereum", "USDC", 500), &principal, 0, 0);
+        assert_eq!(result.decision, PolicyDecision::Denied);
+    }
+
+    #[test]
+    fn test_condition_empty_combinators() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        // Empty Any never matches, empty All always matches
+        ac.add_policy(Policy {
+            id: None,
+            name: "Empty Any".to_string(),
+            conditions: vec![Condition::Any(vec![])],
+            action: PolicyAction::Deny,
+            priority: 0,
+            utc_offset_minutes: None,
+        });
+        ac.add_policy(Policy {
+            id: None,
+            name: "Empty All".to_string(),
+            conditions: vec![Condition::All(vec![])],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+
+        let result = ac.evaluate_action(&mock_transfer_on("ethereum", "USDC", 500), &principal, 0, 0);
+        assert_eq!(result.matched_policy, Some("Empty All".to_string()));
+    }
+
+    #[test]
+    fn test_condition_tree_candid_roundtrip() {
+        let condition = Condition::Any(vec![
+            Condition::Not(Box::new(Condition::MaxAmount(100))),
+            Condition::All(vec![Condition::Cooldown(60)]),
+        ]);
+
+        let bytes = candid::encode_one(&condition).unwrap();
+        let decoded: Condition = candid::decode_one(&bytes).unwrap();
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", condition));
+    }
+
+    // ==================== Recipient and Spender Conditions ====================
+
+    const TREASURY: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+    const ROUTER: &str = "0x3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD";
+
+    #[test]
+    fn test_allowed_recipients_case_insensitive() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        ac.add_policy(Policy {
+            id: None,
+            name: "Treasury Only".to_string(),
+            conditions: vec![Condition::AllowedRecipients(vec![TREASURY.to_string()])],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+
+        let to_treasury = Action::Transfer {
+            chain: "ethereum".to_string(),
+            token: "USDC".to_string(),
+            to: TREASURY.to_lowercase(),
+            amount: 500,
+        };
+        let result = ac.evaluate_action(&to_treasury, &principal, 0, 0);
+        assert_eq!(result.decision, PolicyDecision::Allowed);
+        assert_eq!(
+            result.reason,
+            format!("Matched policy: Treasury Only (recipient: {})", TREASURY.to_lowercase())
+        );
+
+        let result = ac.evaluate_action(&mock_transfer_on("ethereum", "USDC", 500), &principal, 0, 0);
+        assert_eq!(result.decision, PolicyDecision::Denied);
+    }
+
+    #[test]
+    fn test_denied_recipients() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        ac.add_policy(Policy {
+            id: None,
+            name: "Block Mixer".to_string(),
+            conditions: vec![
+                Condition::DeniedRecipients(vec![TREASURY.to_uppercase()]),
+                Condition::MaxAmount(10000),
+            ],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+
+        let result = ac.evaluate_action(&mock_transfer_on("ethereum", "USDC", 500), &principal, 0, 0);
+        assert_eq!(result.decision, PolicyDecision::Allowed);
+
+        let to_denied = Action::Transfer {
+            chain: "ethereum".to_string(),
+            token: "USDC".to_string(),
+            to: TREASURY.to_string(),
+            amount: 500,
+        };
+        let result = ac.evaluate_action(&to_denied, &principal, 0, 0);
+        assert_eq!(result.decision, PolicyDecision::Denied);
+    }
+
+    #[test]
+    fn test_allowed_spenders() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        ac.add_policy(Policy {
+            id: None,
+            name: "Router Approvals".to_string(),
+            conditions: vec![
+                Condition::AllowedSpenders(vec![ROUTER.to_string()]),
+                Condition::AllowedRecipients(vec![TREASURY.to_string()]),
+            ],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+
+        let approve = |spender: &str| Action::ApproveToken {
+            chain: "ethereum".to_string(),
+            token: "USDC".to_string(),
+            spender: spender.to_string(),
+            amount: 500,
+        };
+
+        // Recipient conditions don't apply to approvals, spender conditions do
+        let result = ac.evaluate_action(&approve(&ROUTER.to_lowercase()), &principal, 0, 0);
+        assert_eq!(result.decision, PolicyDecision::Allowed);
+        assert!(result.reason.ends_with(&format!("(spender: {})", ROUTER.to_lowercase())));
+
+        let result = ac.evaluate_action(&approve("0x0000000000000000000000000000000000000001"), &principal, 0, 0);
+        assert_eq!(result.decision, PolicyDecision::Denied);
+
+        // Swaps have no external recipient or spender
+        let swap = Action::Swap {
+            chain: "ethereum".to_string(),
+            token_in: "USDC".to_string(),
+            token_out: "WETH".to_string(),
+            amount_in: 500,
+            min_amount_out: 1,
+            fee_tier: None,
+        };
+        let result = ac.evaluate_action(&swap, &principal, 0, 0);
+        assert_eq!(result.decision, PolicyDecision::Allowed);
+        assert_eq!(result.reason, "Matched policy: Router Approvals");
+    }
+
+    // ==================== Dry-run Explanation ====================
+
+    #[test]
+    fn test_explain_action_traces_every_policy() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        ac.add_policy(Policy {
+            id: None,
+            name: "Allow Small".to_string(),
+            conditions: vec![
+                Condition::MaxAmount(1000),
+                Condition::AllowedChains(vec!["ethereum".to_string()]),
+            ],
+            action: PolicyAction::Allow,
+            priority: 2,
+            utc_offset_minutes: None,
+        });
+        ac.add_policy(Policy {
+            id: None,
+            name: "Deny Polygon".to_string(),
+            conditions: vec![Condition::AllowedChains(vec!["polygon".to_string()])],
+            action: PolicyAction::Deny,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+        ac.add_policy(Policy {
+            id: None,
+            name: "Threshold Stablecoins".to_string(),
+            conditions: vec![Condition::Not(Box::new(Condition::AllowedTokens(vec!["DAI".to_string()])))],
+            action: PolicyAction::RequireThreshold { required: 2, from_roles: vec![], expiry_seconds: None, timelock_seconds: None, required_weight: None },
+            priority: 3,
+            utc_offset_minutes: None,
+        });
+
+        let action = mock_transfer_on("ethereum", "USDC", 5000);
+        let (result, traces) = ac.explain_action(&action, &principal, 0, 0);
+
+        // Same decision as evaluate_action
+        let expected = ac.evaluate_action(&action, &principal, 0, 0);
+        assert_eq!(result.decision, expected.decision);
+        assert_eq!(result.matched_policy, Some("Threshold Stablecoins".to_string()));
+
+        // Every policy in priority order
+        let names: Vec<&str> = traces.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["Deny Polygon", "Allow Small", "Threshold Stablecoins"]);
+        assert!(!traces[0].matched);
+        assert!(!traces[1].matched);
+        assert!(traces[2].matched);
+
+        // Actual vs expected for the failing amount check
+        let max_amount = &traces[1].conditions[0];
+        assert_eq!(max_amount.condition, "MaxAmount");
+        assert!(!max_amount.passed);
+        assert_eq!(max_amount.actual, "5000");
+        assert_eq!(max_amount.expected, "<= 1000");
+        assert!(traces[1].conditions[1].passed);
+
+        // Nested traces for combinators
+        let not = &traces[2].conditions[0];
+        assert!(not.passed);
+        assert_eq!(not.nested.len(), 1);
+        assert!(!not.nested[0].passed);
+    }
+
+    #[test]
+    fn test_explain_action_has_no_side_effects() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        ac.add_policy(Policy {
+            id: None,
+            name: "Cooldown Policy".to_string(),
+            conditions: vec![Condition::Cooldown(60)],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+
+        let action = mock_transfer_on("ethereum", "USDC", 500);
+        let (first, _) = ac.explain_action(&action, &principal, 0, seconds(100));
+        let (second, traces) = ac.explain_action(&action, &principal, 0, seconds(101));
+
+        assert_eq!(first.decision, PolicyDecision::Allowed);
+        assert_eq!(second.decision, PolicyDecision::Allowed);
+        assert_eq!(traces[0].conditions[0].actual, "no previous execution");
+        assert!(ac.last_executions.is_empty());
+    }
+
     // ==================== Edge Cases: Conflicting Policies ====================
 
     #[test]
@@ -746,17 +1847,21 @@ mod tests {
 
         // Conflicting policies for same amount range
         let allow_policy = Policy {
+            id: None,
             name: "Allow Transfer".to_string(),
             conditions: vec![Condition::MaxAmount(5000)],
             action: PolicyAction::Allow,
             priority: 2, // Lower priority
+            utc_offset_minutes: None,
         };
 
         let deny_policy = Policy {
+            id: None,
             name: "Deny Transfer".to_string(),
             conditions: vec![Condition::MaxAmount(5000)],
             action: PolicyAction::Deny,
             priority: 1, // Higher priority
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(allow_policy);
@@ -769,7 +1874,7 @@ mod tests {
             amount: 3000,
         };
 
-        let result = ac.evaluate_action(&action, &principal, 0);
+        let result = ac.evaluate_action(&action, &principal, 0, 0);
         // Deny should win (higher priority = lower number)
         assert_eq!(result.decision, PolicyDecision::Denied);
         assert_eq!(result.matched_policy, Some("Deny Transfer".to_string()));
@@ -782,6 +1887,7 @@ mod tests {
 
         // Policy 1: Allow small amounts on ethereum
         let policy1 = Policy {
+            id: None,
             name: "Allow Ethereum Small".to_string(),
             conditions: vec![
                 Condition::MaxAmount(1000),
@@ -789,16 +1895,19 @@ mod tests {
             ],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         // Policy 2: Deny all polygon
         let policy2 = Policy {
+            id: None,
             name: "Deny Polygon".to_string(),
             conditions: vec![
                 Condition::AllowedChains(vec!["polygon".to_string()]),
             ],
             action: PolicyAction::Deny,
             priority: 2,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy1);
@@ -811,7 +1920,7 @@ mod tests {
             to: "0x123".to_string(),
             amount: 500,
         };
-        let result1 = ac.evaluate_action(&action1, &principal, 0);
+        let result1 = ac.evaluate_action(&action1, &principal, 0, 0);
         assert_eq!(result1.decision, PolicyDecision::Allowed);
 
         // Polygon small - should be denied by default (doesn't match policy1)
@@ -821,7 +1930,7 @@ mod tests {
             to: "0x123".to_string(),
             amount: 500,
         };
-        let result2 = ac.evaluate_action(&action2, &principal, 0);
+        let result2 = ac.evaluate_action(&action2, &principal, 0, 0);
         assert_eq!(result2.decision, PolicyDecision::Denied);
     }
 
@@ -839,9 +1948,9 @@ mod tests {
             amount: 100,
         };
 
-        let result = ac.evaluate_action(&action, &principal, 0);
+        let result = ac.evaluate_action(&action, &principal, 0, 0);
         assert_eq!(result.decision, PolicyDecision::Denied);
-        assert_eq!(result.reason, "No matching policy found");
+        assert_eq!(result.reason, "No matching policy found (recipient: 0x123)");
         assert_eq!(result.matched_policy, None);
     }
 
@@ -852,10 +1961,12 @@ mod tests {
 
         // Policy only allows ethereum
         let policy = Policy {
+            id: None,
             name: "Ethereum Only".to_string(),
             conditions: vec![Condition::AllowedChains(vec!["ethereum".to_string()])],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -868,9 +1979,9 @@ mod tests {
             amount: 100,
         };
 
-        let result = ac.evaluate_action(&action, &principal, 0);
+        let result = ac.evaluate_action(&action, &principal, 0, 0);
         assert_eq!(result.decision, PolicyDecision::Denied);
-        assert_eq!(result.reason, "No matching policy found");
+        assert_eq!(result.reason, "No matching policy found (recipient: 0x123)");
     }
 
     // ==================== Threshold Tests with Different Roles ====================
@@ -881,13 +1992,18 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Require Owner Approval".to_string(),
             conditions: vec![Condition::MaxAmount(10000)],
             action: PolicyAction::RequireThreshold {
                 required: 2,
                 from_roles: vec![Role::Owner],
+                expiry_seconds: Some(3600),
+                timelock_seconds: Some(6 * 3600),
+                required_weight: Some(4),
             },
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -899,9 +2015,22 @@ mod tests {
             amount: 5000,
         };
 
-        let result = ac.evaluate_action(&action, &principal, 0);
+        let result = ac.evaluate_action(&action, &principal, 0, 0);
         assert_eq!(result.decision, PolicyDecision::RequiresThreshold);
         assert_eq!(result.matched_policy, Some("Require Owner Approval".to_string()));
+
+        // The matched policy carries its own quorum, weight, signer roles, signing window and time-lock
+        let (_, matched) = ac.evaluate_action_with_policy(&action, &principal, 0, 0);
+        match matched.unwrap().action {
+            PolicyAction::RequireThreshold { required, from_roles, expiry_seconds, timelock_seconds, required_weight } => {
+                assert_eq!(required, 2);
+                assert_eq!(required_weight, Some(4));
+                assert_eq!(from_roles, vec![Role::Owner]);
+                assert_eq!(expiry_seconds, Some(3600));
+                assert_eq!(timelock_seconds, Some(6 * 3600));
+            }
+            other => panic!("unexpected policy action: {:?}", other),
+        }
     }
 
     #[test]
@@ -910,6 +2039,7 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Require Multi-Role Approval".to_string(),
             conditions: vec![
                 Condition::MaxAmount(50000),
@@ -918,8 +2048,12 @@ mod tests {
             action: PolicyAction::RequireThreshold {
                 required: 3,
                 from_roles: vec![Role::Owner, Role::Operator],
+                expiry_seconds: None,
+                timelock_seconds: None,
+                required_weight: None,
             },
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -931,7 +2065,7 @@ mod tests {
             amount: 25000,
         };
 
-        let result = ac.evaluate_action(&action, &principal, 0);
+        let result = ac.evaluate_action(&action, &principal, 0, 0);
         assert_eq!(result.decision, PolicyDecision::RequiresThreshold);
     }
 
@@ -943,13 +2077,15 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Business Hours Only".to_string(),
             conditions: vec![
-                Condition::TimeWindow { start: 9, end: 17 }, // 9 AM to 5 PM
+                Condition::TimeWindow { start: 9, end: 17, days: None }, // 9 AM to 5 PM
                 Condition::MaxAmount(10000),
             ],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -962,7 +2098,7 @@ mod tests {
         };
 
         // Current time: 12 (noon) - within window
-        let result = ac.evaluate_action(&action, &principal, 12);
+        let result = ac.evaluate_action(&action, &principal, 0, mock_time(MONDAY, 12, 0));
         assert_eq!(result.decision, PolicyDecision::Allowed);
     }
 
@@ -972,13 +2108,15 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Business Hours Only".to_string(),
             conditions: vec![
-                Condition::TimeWindow { start: 9, end: 17 },
+                Condition::TimeWindow { start: 9, end: 17, days: None },
                 Condition::MaxAmount(10000),
             ],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -991,16 +2129,157 @@ mod tests {
         };
 
         // Current time: 20 (8 PM) - outside window
-        let result = ac.evaluate_action(&action, &principal, 20);
+        let result = ac.evaluate_action(&action, &principal, 0, mock_time(MONDAY, 20, 0));
         assert_eq!(result.decision, PolicyDecision::Denied);
     }
 
+    #[test]
+    fn test_time_window_boundaries() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        ac.add_policy(Policy {
+            id: None,
+            name: "Business Hours Only".to_string(),
+            conditions: vec![Condition::TimeWindow { start: 9, end: 17, days: None }],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+
+        let action = Action::Transfer {
+            chain: "ethereum".to_string(),
+            token: "USDC".to_string(),
+            to: "0x123".to_string(),
+            amount: 5000,
+        };
+
+        // Start is inclusive, end is exclusive
+        let cases = [
+            (mock_time(MONDAY, 8, 59), PolicyDecision::Denied),
+            (mock_time(MONDAY, 9, 0), PolicyDecision::Allowed),
+            (mock_time(MONDAY, 16, 59), PolicyDecision::Allowed),
+            (mock_time(MONDAY, 17, 0), PolicyDecision::Denied),
+        ];
+        for (time, expected) in cases {
+            assert_eq!(ac.evaluate_action(&action, &principal, 0, time).decision, expected);
+        }
+    }
+
+    #[test]
+    fn test_time_window_crosses_midnight() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        // Overnight desk: 22:00 - 06:00, Monday nights only
+        ac.add_policy(Policy {
+            id: None,
+            name: "Overnight Desk".to_string(),
+            conditions: vec![Condition::TimeWindow { start: 22, end: 6, days: Some(0b0000001) }],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+
+        let action = Action::Transfer {
+            chain: "ethereum".to_string(),
+            token: "USDC".to_string(),
+            to: "0x123".to_string(),
+            amount: 5000,
+        };
+
+        let cases = [
+            (mock_time(MONDAY, 21, 59), PolicyDecision::Denied),
+            (mock_time(MONDAY, 22, 0), PolicyDecision::Allowed),
+            (mock_time(MONDAY, 23, 59), PolicyDecision::Allowed),
+            // Tuesday early morning still belongs to Monday's window
+            (mock_time(MONDAY + 1, 3, 0), PolicyDecision::Allowed),
+            (mock_time(MONDAY + 1, 6, 0), PolicyDecision::Denied),
+            (mock_time(MONDAY + 1, 22, 0), PolicyDecision::Denied),
+            // Monday early morning belongs to Sunday's window
+            (mock_time(MONDAY, 3, 0), PolicyDecision::Denied),
+        ];
+        for (time, expected) in cases {
+            assert_eq!(ac.evaluate_action(&action, &principal, 0, time).decision, expected);
+        }
+    }
+
+    #[test]
+    fn test_time_window_weekday_mask() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        // Weekdays only (Monday - Friday)
+        ac.add_policy(Policy {
+            id: None,
+            name: "Weekday Trading".to_string(),
+            conditions: vec![Condition::TimeWindow { start: 9, end: 17, days: Some(0b0011111) }],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+
+        let action = Action::Transfer {
+            chain: "ethereum".to_string(),
+            token: "USDC".to_string(),
+            to: "0x123".to_string(),
+            amount: 5000,
+        };
+
+        let friday = ac.evaluate_action(&action, &principal, 0, mock_time(MONDAY + 4, 12, 0));
+        assert_eq!(friday.decision, PolicyDecision::Allowed);
+
+        let saturday = ac.evaluate_action(&action, &principal, 0, mock_time(MONDAY + 5, 12, 0));
+        assert_eq!(saturday.decision, PolicyDecision::Denied);
+
+        let sunday = ac.evaluate_action(&action, &principal, 0, mock_time(MONDAY + 6, 12, 0));
+        assert_eq!(sunday.decision, PolicyDecision::Denied);
+    }
+
+    #[test]
+    fn test_time_window_utc_offset() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        // 9-17 in UTC-5 is 14:00-22:00 UTC
+        ac.add_policy(Policy {
+            id: None,
+            name: "New York Desk".to_string(),
+            conditions: vec![Condition::TimeWindow { start: 9, end: 17, days: Some(0b0011111) }],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: Some(-300),
+        });
+
+        let action = Action::Transfer {
+            chain: "ethereum".to_string(),
+            token: "USDC".to_string(),
+            to: "0x123".to_string(),
+            amount: 5000,
+        };
+
+        let cases = [
+            (mock_time(MONDAY, 13, 59), PolicyDecision::Denied),
+            (mock_time(MONDAY, 14, 0), PolicyDecision::Allowed),
+            (mock_time(MONDAY, 21, 59), PolicyDecision::Allowed),
+            (mock_time(MONDAY, 22, 0), PolicyDecision::Denied),
+            // Saturday 01:00 UTC is still Friday 20:00 local, outside hours
+            (mock_time(MONDAY + 5, 1, 0), PolicyDecision::Denied),
+            // Saturday 15:00 UTC is Saturday 10:00 local, masked out
+            (mock_time(MONDAY + 5, 15, 0), PolicyDecision::Denied),
+        ];
+        for (time, expected) in cases {
+            assert_eq!(ac.evaluate_action(&action, &principal, 0, time).decision, expected);
+        }
+    }
+
     #[test]
     fn test_cooldown_respected() {
         let mut ac = AccessControl::new();
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Cooldown Policy".to_string(),
             conditions: vec![
                 Condition::Cooldown(3600), // 1 hour cooldown
@@ -1008,9 +2287,10 @@ mod tests {
             ],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
-        ac.add_policy(policy);
+        let policy_id = ac.add_policy(policy);
 
         let action = Action::Transfer {
             chain: "ethereum".to_string(),
@@ -1019,14 +2299,144 @@ mod tests {
             amount: 5000,
         };
 
+        // No previous execution - allowed
+        let result0 = ac.evaluate_action(&action, &principal, 0, seconds(500));
+        assert_eq!(result0.decision, PolicyDecision::Allowed);
+
         // Last action at time 1000, cooldown is 3600
+        ac.record_execution(principal, policy_id, &action, seconds(1000));
+
         // Current time 2000 - not enough time passed
-        let result1 = ac.evaluate_action(&action, &principal, 2000);
+        let result1 = ac.evaluate_action(&action, &principal, 0, seconds(2000));
         assert_eq!(result1.decision, PolicyDecision::Denied);
 
+        // Current time 4599 - one second short
+        let result2 = ac.evaluate_action(&action, &principal, 0, seconds(4599));
+        assert_eq!(result2.decision, PolicyDecision::Denied);
+
         // Current time 5000 - enough time passed
-        let result2 = ac.evaluate_action(&action, &principal, 5000);
-        assert_eq!(result2.decision, PolicyDecision::Allowed);
+        let result3 = ac.evaluate_action(&action, &principal, 0, seconds(5000));
+        assert_eq!(result3.decision, PolicyDecision::Allowed);
+    }
+
+    #[test]
+    fn test_cooldown_scoped_per_requester_and_action_type() {
+        let mut ac = AccessControl::new();
+        let agent1 = mock_principal(1);
+        let agent2 = mock_principal(2);
+
+        let policy_id = ac.add_policy(Policy {
+            id: None,
+            name: "Cooldown Policy".to_string(),
+            conditions: vec![Condition::Cooldown(3600)],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+
+        let transfer = Action::Transfer {
+            chain: "ethereum".to_string(),
+            token: "USDC".to_string(),
+            to: "0x123".to_string(),
+            amount: 5000,
+        };
+        let swap = Action::Swap {
+            chain: "ethereum".to_string(),
+            token_in: "USDC".to_string(),
+            token_out: "WETH".to_string(),
+            amount_in: 5000,
+            min_amount_out: 1,
+            fee_tier: None,
+        };
+
+        ac.record_execution(agent1, policy_id, &transfer, seconds(1000));
+
+        // Same requester and action type is throttled
+        let result = ac.evaluate_action(&transfer, &agent1, 0, seconds(1060));
+        assert_eq!(result.decision, PolicyDecision::Denied);
+
+        // Other action types and other requesters are not
+        let result = ac.evaluate_action(&swap, &agent1, 0, seconds(1060));
+        assert_eq!(result.decision, PolicyDecision::Allowed);
+        let result = ac.evaluate_action(&transfer, &agent2, 0, seconds(1060));
+        assert_eq!(result.decision, PolicyDecision::Allowed);
+
+        // Executions under another policy do not count
+        let mut ac2 = AccessControl::new();
+        let policy_id = ac2.add_policy(Policy {
+            id: None,
+            name: "Cooldown Policy".to_string(),
+            conditions: vec![Condition::Cooldown(3600)],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+        ac2.record_execution(agent1, policy_id + 1, &transfer, seconds(1000));
+        let result = ac2.evaluate_action(&transfer, &agent1, 0, seconds(1060));
+        assert_eq!(result.decision, PolicyDecision::Allowed);
+    }
+
+    #[test]
+    fn test_cooldown_reserved_while_execution_in_flight() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        let policy_id = ac.add_policy(Policy {
+            id: None,
+            name: "Cooldown Policy".to_string(),
+            conditions: vec![Condition::Cooldown(3600)],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        });
+
+        let action = Action::Transfer {
+            chain: "ethereum".to_string(),
+            token: "USDC".to_string(),
+            to: "0x123".to_string(),
+            amount: 5000,
+        };
+
+        // A second request while the first is executing is throttled
+        ac.reserve_execution(7, principal, policy_id, &action, seconds(1000));
+        let result = ac.evaluate_action(&action, &principal, 0, seconds(1001));
+        assert_eq!(result.decision, PolicyDecision::Denied);
+
+        // A failed execution frees the cooldown
+        assert!(ac.release_execution(7).is_some());
+        let result = ac.evaluate_action(&action, &principal, 0, seconds(1001));
+        assert_eq!(result.decision, PolicyDecision::Allowed);
+
+        // A successful one records the execution time
+        ac.reserve_execution(8, principal, policy_id, &action, seconds(1001));
+        let record = ac.commit_execution(8, seconds(1010)).unwrap();
+        assert_eq!(record.executed_at, seconds(1010));
+        assert!(ac.commit_execution(8, seconds(1010)).is_none());
+        let result = ac.evaluate_action(&action, &principal, 0, seconds(4609));
+        assert_eq!(result.decision, PolicyDecision::Denied);
+        let result = ac.evaluate_action(&action, &principal, 0, seconds(4610));
+        assert_eq!(result.decision, PolicyDecision::Allowed);
+    }
+
+    #[test]
+    fn test_restore_execution_keeps_latest() {
+        let mut ac = AccessControl::new();
+        let principal = mock_principal(1);
+
+        let action = Action::Transfer {
+            chain: "ethereum".to_string(),
+            token: "USDC".to_string(),
+            to: "0x123".to_string(),
+            amount: 5000,
+        };
+
+        let record = ac.record_execution(principal, 0, &action, seconds(2000));
+        assert_eq!(record.action_type, "transfer");
+
+        ac.restore_execution(ExecutionRecord { executed_at: seconds(1000), ..record });
+
+        assert_eq!(ac.last_executions.len(), 1);
+        assert_eq!(ac.last_execution(&principal, 0, &action), Some(seconds(2000)));
     }
 
     // ==================== Swap Action Tests ====================
@@ -1037,6 +2447,7 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Allow Swaps".to_string(),
             conditions: vec![
                 Condition::MaxAmount(1000000), // Max input amount
@@ -1044,6 +2455,7 @@ mod tests {
             ],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -1057,7 +2469,7 @@ mod tests {
             fee_tier: Some(3000),
         };
 
-        let result = ac.evaluate_action(&swap_action, &principal, 0);
+        let result = ac.evaluate_action(&swap_action, &principal, 0, 0);
         assert_eq!(result.decision, PolicyDecision::Allowed);
     }
 
@@ -1067,10 +2479,12 @@ mod tests {
         let principal = mock_principal(1);
 
         let policy = Policy {
+            id: None,
             name: "Limit Swaps".to_string(),
             conditions: vec![Condition::MaxAmount(100000)],
             action: PolicyAction::Deny,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         ac.add_policy(policy);
@@ -1084,7 +2498,7 @@ mod tests {
             fee_tier: None,
         };
 
-        let result = ac.evaluate_action(&swap_action, &principal, 0);
+        let result = ac.evaluate_action(&swap_action, &principal, 0, 0);
         assert_eq!(result.decision, PolicyDecision::Denied);
     }
 
@@ -1096,19 +2510,23 @@ mod tests {
         let principal = mock_principal(1);
 
         let initial_policy = Policy {
+            id: None,
             name: "Initial".to_string(),
             conditions: vec![Condition::MaxAmount(1000)],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         let id = ac.add_policy(initial_policy);
 
         let updated_policy = Policy {
+            id: None,
             name: "Updated".to_string(),
             conditions: vec![Condition::MaxAmount(5000)],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         assert!(ac.update_policy(id, updated_policy));
@@ -1120,7 +2538,7 @@ mod tests {
             amount: 3000,
         };
 
-        let result = ac.evaluate_action(&action, &principal, 0);
+        let result = ac.evaluate_action(&action, &principal, 0, 0);
         assert_eq!(result.decision, PolicyDecision::Allowed);
         assert_eq!(result.matched_policy, Some("Updated".to_string()));
     }
@@ -1130,10 +2548,12 @@ mod tests {
         let mut ac = AccessControl::new();
 
         let policy = Policy {
+            id: None,
             name: "Test".to_string(),
             conditions: vec![Condition::MaxAmount(1000)],
             action: PolicyAction::Allow,
             priority: 1,
+            utc_offset_minutes: None,
         };
 
         assert!(!ac.update_policy(999, policy));
@@ -1144,4 +2564,65 @@ mod tests {
         let mut ac = AccessControl::new();
         assert!(!ac.remove_policy(999));
     }
+
+    fn named_policy(name: &str) -> Policy {
+        Policy {
+            id: None,
+            name: name.to_string(),
+            conditions: vec![],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        }
+    }
+
+    #[test]
+    fn test_policy_ids_never_reused() {
+        let mut ac = AccessControl::new();
+
+        let a = ac.add_policy(named_policy("A"));
+        let b = ac.add_policy(named_policy("B"));
+        assert_eq!((a, b), (0, 1));
+
+        // Removing A must not shift B or free its ID
+        assert!(ac.remove_policy(a));
+        let c = ac.add_policy(named_policy("C"));
+        assert_eq!(c, 2);
+        assert!(!ac.update_policy(a, named_policy("Stale")));
+
+        assert!(ac.update_policy(b, named_policy("B2")));
+        assert_eq!(ac.get_policy(b).map(|p| p.name.as_str()), Some("B2"));
+        assert_eq!(ac.get_policy(b).and_then(|p| p.id), Some(b));
+        assert_eq!(ac.get_policy(c).map(|p| p.name.as_str()), Some("C"));
+    }
+
+    #[test]
+    fn test_matched_policy_id() {
+        let mut ac = AccessControl::new();
+        ac.add_policy(named_policy("Removed"));
+        let id = ac.add_policy(named_policy("Kept"));
+        ac.remove_policy(0);
+
+        let result = ac.evaluate_action(&mock_transfer_on("ethereum", "USDC", 100), &mock_principal(1), 0, 0);
+        assert_eq!(result.matched_policy_id, Some(id));
+        assert_eq!(result.matched_policy, Some("Kept".to_string()));
+    }
+
+    #[test]
+    fn test_restore_policies() {
+        let mut ac = AccessControl::new();
+        ac.add_policy(named_policy("A"));
+        let b = ac.add_policy(named_policy("B"));
+        ac.remove_policy(b);
+
+        // Highest ID was removed; the persisted counter still prevents reuse
+        let mut restored = AccessControl::new();
+        restored.restore_policies(
+            vec![named_policy("Legacy"), ac.get_policy(0).cloned().unwrap()],
+            ac.next_policy_id(),
+        );
+        assert_eq!(restored.get_policy(0).map(|p| p.name.as_str()), Some("A"));
+        assert_eq!(restored.get_policy(2).map(|p| p.name.as_str()), Some("Legacy"));
+        assert_eq!(restored.add_policy(named_policy("C")), 3);
+    }
 }
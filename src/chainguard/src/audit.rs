@@ -1,17 +1,76 @@
+use crate::stable_memory::decode_audit_entry;
 use crate::types::*;
 use candid::Principal;
-
-pub struct AuditLog {
-    entries: Vec<AuditEntry>,
+use ic_stable_structures::{Memory, StableBTreeMap};
+use sha2::{Digest, Sha256};
+use std::ops::Range;
+
+const GENESIS_HASH: [u8; 32] = [0u8; 32];
+const DEFAULT_PAGE_SIZE: u64 = 50;
+
+// Secondary index tags. Filter keys are tag | value length | value | entry id;
+// execution keys are tag | request id and map to the ActionExecuted entry id;
+// count keys are tag | filter prefix and map to the number of ids under it.
+const INDEX_CALLER: u8 = 0;
+const INDEX_ACTION_TYPE: u8 = 1;
+const INDEX_DECISION: u8 = 2;
+const INDEX_CHAIN: u8 = 3;
+const INDEX_TOKEN: u8 = 4;
+const INDEX_THRESHOLD_REQUEST: u8 = 5;
+const INDEX_SUCCESS: u8 = 6;
+const INDEX_EXECUTION: u8 = 7;
+const INDEX_COUNT: u8 = 8;
+
+// Append-only log of typed events: agent action requests plus governance,
+// signer and emergency changes. Each entry commits to the hash of the one
+// before it, so the tip hash (published as certified data) pins the whole log.
+// Entries and their secondary indexes are written to stable memory as they
+// are logged, so upgrades copy nothing.
+pub struct AuditLog<M: Memory> {
+    entries: StableBTreeMap<u64, Vec<u8>, M>,
+    index: StableBTreeMap<Vec<u8>, u64, M>,
     next_id: u64,
+    tip_hash: Vec<u8>,
 }
 
-impl AuditLog {
-    pub fn new() -> Self {
-        Self {
-            entries: Vec::new(),
+impl<M: Memory> AuditLog<M> {
+    // Open the log stored in the given memories. Entries written before hash
+    // chaining or before the indexes existed are migrated once, here.
+    pub fn init(entries_memory: M, index_memory: M) -> Self {
+        let mut log = Self {
+            entries: StableBTreeMap::init(entries_memory),
+            index: StableBTreeMap::init(index_memory),
             next_id: 0,
+            tip_hash: GENESIS_HASH.to_vec(),
+        };
+
+        if let Some(last) = log.entries.last_key_value().and_then(|(_, bytes)| decode_audit_entry(&bytes)) {
+            log.next_id = last.id + 1;
+            log.tip_hash = last.hash;
+        }
+        log
+    }
+
+    // Rewrite every entry in the current format, chaining unhashed entries
+    // and rebuilding the indexes (schema migration; fails on undecodable entries)
+    pub fn rebuild(&mut self) -> Result<(), String> {
+        self.next_id = 0;
+        self.tip_hash = GENESIS_HASH.to_vec();
+        self.index.clear_new();
+
+        let ids: Vec<u64> = self.entries.keys().collect();
+        for id in ids {
+            let mut entry = self.get_entry(id)
+                .ok_or_else(|| format!("Failed to decode audit entry {}", id))?;
+            if entry.hash.is_empty() {
+                entry.prev_hash = self.tip_hash.clone();
+                entry.hash = entry_hash(&entry.prev_hash, entry.id, entry.timestamp, &entry.caller, &entry.event);
+            }
+            self.next_id = entry.id + 1;
+            self.tip_hash = entry.hash.clone();
+            self.insert(&entry);
         }
+        Ok(())
     }
 
     pub fn log_action(
@@ -20,105 +79,401 @@ impl AuditLog {
         requester: Principal,
         policy_result: PolicyResult,
         threshold_request_id: Option<u64>,
+        policy_version: u64,
         current_time: u64,
     ) -> u64 {
+        let event = AuditEvent::ActionRequested(ActionEvent {
+            action_type: action_type_string(action),
+            action_params: action_to_json(action),
+            policy_result,
+            threshold_request_id,
+            execution_result: None,
+            policy_version: Some(policy_version),
+        });
+        self.log_event(requester, event, current_time)
+    }
+
+    // Record a governance, signer or emergency event; returns the entry id
+    pub fn log_event(&mut self, caller: Principal, event: AuditEvent, current_time: u64) -> u64 {
         let id = self.next_id;
         self.next_id += 1;
 
-        let entry = AuditEntry {
+        let prev_hash = self.tip_hash.clone();
+        let hash = entry_hash(&prev_hash, id, current_time, &caller, &event);
+        self.tip_hash = hash.clone();
+
+        self.insert(&AuditEntry {
             id,
             timestamp: current_time,
-            action_type: Self::action_type_string(action),
-            action_params: Self::action_to_json(action),
-            requester,
-            policy_result,
-            threshold_request_id,
-            execution_result: None,
-        };
-
-        self.entries.push(entry);
+            caller,
+            event,
+            prev_hash,
+            hash,
+        });
         id
     }
 
-    pub fn update_execution_result(
+    // Append the outcome of an executed action as a new entry linked to its request
+    pub fn log_execution(
         &mut self,
-        entry_id: u64,
+        audit_id: u64,
+        caller: Principal,
         result: ExecutionResult,
-    ) -> Result<(), String> {
-        let entry = self.entries
-            .iter_mut()
-            .find(|e| e.id == entry_id)
-            .ok_or("Entry not found")?;
+        current_time: u64,
+    ) -> Result<u64, String> {
+        let entry = self.get_entry(audit_id).ok_or("Entry not found")?;
+        if entry.action().is_none() {
+            return Err("Entry is not an action request".to_string());
+        }
+        Ok(self.log_event(caller, AuditEvent::ActionExecuted { audit_id, result }, current_time))
+    }
 
-        entry.execution_result = Some(result);
-        Ok(())
+    // Write an entry and its index keys
+    fn insert(&mut self, entry: &AuditEntry) {
+        let bytes = candid::encode_one(entry).expect("audit entry is candid-encodable");
+        self.entries.insert(entry.id, bytes);
+
+        self.insert_indexed(INDEX_CALLER, entry.caller.as_slice(), entry.id);
+        match &entry.event {
+            AuditEvent::ActionRequested(action) => {
+                let (chain, tokens) = Self::action_scope(action);
+                self.insert_indexed(INDEX_ACTION_TYPE, action.action_type.as_bytes(), entry.id);
+                self.insert_indexed(INDEX_DECISION, decision_name(&action.policy_result.decision).as_bytes(), entry.id);
+                if let Some(chain) = chain {
+                    self.insert_indexed(INDEX_CHAIN, chain.to_lowercase().as_bytes(), entry.id);
+                }
+                for token in tokens {
+                    self.insert_indexed(INDEX_TOKEN, token.to_lowercase().as_bytes(), entry.id);
+                }
+                if let Some(request_id) = action.threshold_request_id {
+                    self.insert_indexed(INDEX_THRESHOLD_REQUEST, &request_id.to_be_bytes(), entry.id);
+                }
+                if let Some(result) = &action.execution_result {
+                    self.insert_indexed(INDEX_SUCCESS, &[result.success as u8], entry.id);
+                }
+            }
+            AuditEvent::ActionExecuted { audit_id, result } => {
+                self.index.insert(Self::execution_key(*audit_id), entry.id);
+                self.insert_indexed(INDEX_SUCCESS, &[result.success as u8], *audit_id);
+            }
+            _ => {}
+        }
+    }
+
+    // Add an id under an index value and bump the value's count (once per id)
+    fn insert_indexed(&mut self, tag: u8, value: &[u8], id: u64) {
+        if self.index.insert(Self::index_key(tag, value, id), id).is_none() {
+            let count_key = Self::count_key(tag, value);
+            let count = self.index.get(&count_key).unwrap_or(0);
+            self.index.insert(count_key, count + 1);
+        }
+    }
+
+    fn count_key(tag: u8, value: &[u8]) -> Vec<u8> {
+        let mut key = vec![INDEX_COUNT];
+        key.extend_from_slice(&Self::index_prefix(tag, value));
+        key
+    }
+
+    fn index_prefix(tag: u8, value: &[u8]) -> Vec<u8> {
+        // Long values are truncated; matches() re-checks the full value
+        let value = &value[..value.len().min(u8::MAX as usize)];
+        let mut key = Vec::with_capacity(value.len() + 10);
+        key.push(tag);
+        key.push(value.len() as u8);
+        key.extend_from_slice(value);
+        key
+    }
+
+    fn index_key(tag: u8, value: &[u8], id: u64) -> Vec<u8> {
+        let mut key = Self::index_prefix(tag, value);
+        key.extend_from_slice(&id.to_be_bytes());
+        key
+    }
+
+    fn execution_key(audit_id: u64) -> Vec<u8> {
+        let mut key = vec![INDEX_EXECUTION];
+        key.extend_from_slice(&audit_id.to_be_bytes());
+        key
+    }
+
+    // Entry ids under an index value within an id range, newest first
+    fn indexed_ids(&self, tag: u8, value: &[u8], ids: Range<u64>) -> impl Iterator<Item = u64> + '_ {
+        let first = Self::index_key(tag, value, ids.start);
+        let end = Self::index_key(tag, value, ids.end);
+        self.index.range(first..end).rev().map(|(_, id)| id)
+    }
+
+    // Ids of the entries inside the query's time range; entries are logged in
+    // time order, so the bounds are found by binary search
+    fn id_window(&self, query: &AuditQuery) -> Range<u64> {
+        let start = query.start.map_or(0, |start| self.first_id_where(|timestamp| timestamp >= start));
+        let end = query.end.map_or(self.next_id, |end| self.first_id_where(|timestamp| timestamp > end));
+        start..end.max(start)
+    }
+
+    // Lowest id whose timestamp satisfies `reached` (next_id if none does)
+    fn first_id_where(&self, reached: impl Fn(u64) -> bool) -> u64 {
+        let mut low = self.entries.first_key_value().map_or(0, |(id, _)| id);
+        let mut high = self.next_id;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.get_entry(mid).is_some_and(|e| reached(e.timestamp)) {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        low
+    }
+
+    // Hash of the newest entry (32 zero bytes while the log is empty)
+    pub fn tip_hash(&self) -> &[u8] {
+        &self.tip_hash
+    }
+
+    // Entries from start_id (inclusive), at most limit, with the current tip
+    pub fn get_range(&self, start_id: u64, limit: u64) -> (Vec<AuditEntry>, Option<u64>) {
+        let entries = self.entries
+            .range(start_id..)
+            .take(limit as usize)
+            .filter_map(|(_, bytes)| decode_audit_entry(&bytes))
+            .collect();
+        (entries, self.next_id.checked_sub(1))
     }
 
     pub fn get_entries(&self, start: Option<u64>, end: Option<u64>) -> Vec<AuditEntry> {
         self.entries
             .iter()
+            .filter_map(|(_, bytes)| decode_audit_entry(&bytes))
             .filter(|e| {
                 let after_start = start.map(|s| e.timestamp >= s).unwrap_or(true);
                 let before_end = end.map(|e_time| e.timestamp <= e_time).unwrap_or(true);
                 after_start && before_end
             })
-            .cloned()
             .collect()
     }
 
-    pub fn get_entry(&self, id: u64) -> Option<&AuditEntry> {
-        self.entries.iter().find(|e| e.id == id)
+    pub fn get_entry(&self, id: u64) -> Option<AuditEntry> {
+        self.entries.get(&id).and_then(|bytes| decode_audit_entry(&bytes))
     }
 
-    // Restore entry from stable memory (for post_upgrade)
-    pub fn restore_entry(&mut self, entry: AuditEntry) -> Result<(), String> {
-        // Update next_id if needed
-        if entry.id >= self.next_id {
-            self.next_id = entry.id + 1;
+    // One page of entries matching the query, newest first, capped at max_limit.
+    // Candidates come from the most selective index among the filters given,
+    // inside the time range; the scan starts at the cursor and stops once the
+    // page is full.
+    pub fn query(&self, query: &AuditQuery, max_limit: u64) -> AuditPage {
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(max_limit) as usize;
+        let window = self.id_window(query);
+        let page_end = query.cursor.map_or(window.end, |cursor| cursor.clamp(window.start, window.end));
+        let ids = window.start..page_end;
+        let candidates: Box<dyn Iterator<Item = u64> + '_> = match Self::query_index(query) {
+            Some((tag, value)) => Box::new(self.indexed_ids(tag, &value, ids)),
+            None => Box::new(self.entries.keys_range(ids).rev()),
+        };
+
+        // One extra match tells whether another page follows
+        let mut entries: Vec<AuditEntry> = candidates
+            .filter_map(|id| self.get_entry(id))
+            .filter(|e| self.matches(e, query))
+            .take(limit + 1)
+            .collect();
+        let has_more = entries.len() > limit;
+        entries.truncate(limit);
+
+        let results = entries
+            .iter()
+            .filter_map(|e| self.index.get(&Self::execution_key(e.id)))
+            .filter_map(|id| self.get_entry(id))
+            .collect();
+        let next_cursor = if has_more { entries.last().map(|e| e.id) } else { None };
+
+        AuditPage { entries, results, next_cursor, total: self.count(query, window) }
+    }
+
+    // Index to scan for a query: the most selective of the filters given
+    fn query_index(query: &AuditQuery) -> Option<(u8, Vec<u8>)> {
+        if let Some(request_id) = query.threshold_request_id {
+            Some((INDEX_THRESHOLD_REQUEST, request_id.to_be_bytes().to_vec()))
+        } else if let Some(requester) = &query.requester {
+            Some((INDEX_CALLER, requester.as_slice().to_vec()))
+        } else if let Some(token) = &query.token {
+            Some((INDEX_TOKEN, token.to_lowercase().into_bytes()))
+        } else if let Some(chain) = &query.chain {
+            Some((INDEX_CHAIN, chain.to_lowercase().into_bytes()))
+        } else if let Some(action_type) = &query.action_type {
+            Some((INDEX_ACTION_TYPE, action_type.as_bytes().to_vec()))
+        } else if let Some(success) = query.success {
+            Some((INDEX_SUCCESS, vec![success as u8]))
+        } else {
+            query.decision.as_ref().map(|decision| (INDEX_DECISION, decision_name(decision).as_bytes().to_vec()))
         }
+    }
 
-        // Insert the entry
-        self.entries.push(entry);
-        Ok(())
+    // Matching entries across all pages. The maintained counts answer a single
+    // filter over the whole log; time ranges count the ids in their window, and
+    // further filters are checked over the selected index inside it.
+    fn count(&self, query: &AuditQuery, window: Range<u64>) -> u64 {
+        let whole_log = query.start.is_none() && query.end.is_none();
+        let filters = [
+            query.requester.is_some(),
+            query.action_type.is_some(),
+            query.decision.is_some(),
+            query.chain.is_some(),
+            query.token.is_some(),
+            query.success.is_some(),
+            query.threshold_request_id.is_some(),
+        ];
+        let (tag, value) = match Self::query_index(query) {
+            Some(index) => index,
+            None if whole_log => return self.entries.len(),
+            None => return window.end - window.start,
+        };
+        match filters.iter().filter(|set| **set).count() {
+            1 if whole_log => self.index.get(&Self::count_key(tag, &value)).unwrap_or(0),
+            1 => self.indexed_ids(tag, &value, window).count() as u64,
+            _ => self.indexed_ids(tag, &value, window)
+                .filter_map(|id| self.get_entry(id))
+                .filter(|e| self.matches(e, query))
+                .count() as u64,
+        }
     }
 
-    fn action_type_string(action: &Action) -> String {
-        match action {
-            Action::Swap { .. } => "swap".to_string(),
-            Action::Transfer { .. } => "transfer".to_string(),
-            Action::ApproveToken { .. } => "approve".to_string(),
+    // Entry created when a threshold request was opened
+    pub fn find_by_threshold_request(&self, request_id: u64) -> Option<AuditEntry> {
+        self.indexed_ids(INDEX_THRESHOLD_REQUEST, &request_id.to_be_bytes(), 0..self.next_id)
+            .filter_map(|id| self.get_entry(id))
+            .find(|e| e.action().is_some_and(|a| a.threshold_request_id == Some(request_id)))
+    }
+
+    // Outcome of an action request: its ActionExecuted entry, or the result
+    // stored on entries recorded before results were appended
+    pub fn execution_result(&self, entry: &AuditEntry) -> Option<ExecutionResult> {
+        let action = entry.action()?;
+        if let Some(result) = &action.execution_result {
+            return Some(result.clone());
+        }
+        let executed = self.get_entry(self.index.get(&Self::execution_key(entry.id))?)?;
+        match executed.event {
+            AuditEvent::ActionExecuted { result, .. } => Some(result),
+            _ => None,
         }
     }
 
-    fn action_to_json(action: &Action) -> String {
-        // Simple JSON serialization
-        match action {
-            Action::Swap { chain, token_in, token_out, amount_in, min_amount_out, fee_tier } => {
-                let fee_tier_str = fee_tier.map_or("null".to_string(), |ft| ft.to_string());
-                format!(
-                    r#"{{"chain":"{}","token_in":"{}","token_out":"{}","amount_in":{},"min_amount_out":{},"fee_tier":{}}}"#,
-                    chain, token_in, token_out, amount_in, min_amount_out, fee_tier_str
-                )
+    fn matches(&self, entry: &AuditEntry, query: &AuditQuery) -> bool {
+        if query.start.is_some_and(|start| entry.timestamp < start)
+            || query.end.is_some_and(|end| entry.timestamp > end)
+            || query.requester.is_some_and(|requester| entry.caller != requester)
+        {
+            return false;
+        }
+
+        let filters_actions = query.action_type.is_some()
+            || query.decision.is_some()
+            || query.chain.is_some()
+            || query.token.is_some()
+            || query.success.is_some()
+            || query.threshold_request_id.is_some();
+        if !filters_actions {
+            return true;
+        }
+        let action = match entry.action() {
+            Some(action) => action,
+            None => return false,
+        };
+
+        if query.action_type.as_ref().is_some_and(|t| &action.action_type != t)
+            || query.decision.as_ref().is_some_and(|d| &action.policy_result.decision != d)
+            || query.threshold_request_id.is_some_and(|id| action.threshold_request_id != Some(id))
+        {
+            return false;
+        }
+        if query.chain.is_some() || query.token.is_some() {
+            let (chain, tokens) = Self::action_scope(action);
+            if query.chain.as_ref().is_some_and(|c| !chain.is_some_and(|chain| chain.eq_ignore_ascii_case(c))) {
+                return false;
             }
-            Action::Transfer { chain, token, to, amount } => {
-                format!(
-                    r#"{{"chain":"{}","token":"{}","to":"{}","amount":{}}}"#,
-                    chain, token, to, amount
-                )
+            if query.token.as_ref().is_some_and(|t| !tokens.iter().any(|token| token.eq_ignore_ascii_case(t))) {
+                return false;
             }
-            Action::ApproveToken { chain, token, spender, amount } => {
-                format!(
-                    r#"{{"chain":"{}","token":"{}","spender":"{}","amount":{}}}"#,
-                    chain, token, spender, amount
-                )
+        }
+        if let Some(success) = query.success {
+            if self.execution_result(entry).map(|r| r.success) != Some(success) {
+                return false;
             }
         }
+        true
+    }
+
+    // Chain and tokens named in the recorded action parameters
+    fn action_scope(action: &ActionEvent) -> (Option<String>, Vec<String>) {
+        let params: serde_json::Value = match serde_json::from_str(&action.action_params) {
+            Ok(params) => params,
+            Err(_) => return (None, Vec::new()),
+        };
+        let chain = params["chain"].as_str().map(str::to_string);
+        let tokens = ["token", "token_in", "token_out"]
+            .iter()
+            .filter_map(|key| params[*key].as_str().map(str::to_string))
+            .collect();
+        (chain, tokens)
     }
 }
 
-impl Default for AuditLog {
-    fn default() -> Self {
-        Self::new()
+// SHA-256 over the previous hash and the candid encoding of the entry contents
+pub fn entry_hash(
+    prev_hash: &[u8],
+    id: u64,
+    timestamp: u64,
+    caller: &Principal,
+    event: &AuditEvent,
+) -> Vec<u8> {
+    let contents = candid::encode_args((id, timestamp, caller, event))
+        .expect("audit event is candid-encodable");
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash);
+    hasher.update(&contents);
+    hasher.finalize().to_vec()
+}
+
+fn decision_name(decision: &PolicyDecision) -> &'static str {
+    match decision {
+        PolicyDecision::Allowed => "Allowed",
+        PolicyDecision::Denied => "Denied",
+        PolicyDecision::RequiresThreshold => "RequiresThreshold",
+    }
+}
+
+fn action_type_string(action: &Action) -> String {
+    match action {
+        Action::Swap { .. } => "swap".to_string(),
+        Action::Transfer { .. } => "transfer".to_string(),
+        Action::ApproveToken { .. } => "approve".to_string(),
+    }
+}
+
+fn action_to_json(action: &Action) -> String {
+    // Simple JSON serialization
+    match action {
+        Action::Swap { chain, token_in, token_out, amount_in, min_amount_out, fee_tier } => {
+            let fee_tier_str = fee_tier.map_or("null".to_string(), |ft| ft.to_string());
+            format!(
+                r#"{{"chain":"{}","token_in":"{}","token_out":"{}","amount_in":{},"min_amount_out":{},"fee_tier":{}}}"#,
+                chain, token_in, token_out, amount_in, min_amount_out, fee_tier_str
+            )
+        }
+        Action::Transfer { chain, token, to, amount } => {
+            format!(
+                r#"{{"chain":"{}","token":"{}","to":"{}","amount":{}}}"#,
+                chain, token, to, amount
+            )
+        }
+        Action::ApproveToken { chain, token, spender, amount } => {
+            format!(
+                r#"{{"chain":"{}","token":"{}","spender":"{}","amount":{}}}"#,
+                chain, token, spender, amount
+            )
+        }
     }
 }
 
@@ -126,6 +481,7 @@ impl Default for AuditLog {
 mod tests {
     use super::*;
     use candid::Principal;
+    use ic_stable_structures::DefaultMemoryImpl;
 
     fn mock_principal(id: u8) -> Principal {
         let mut bytes = [0u8; 29];
@@ -133,6 +489,10 @@ mod tests {
         Principal::from_slice(&bytes)
     }
 
+    fn new_log() -> AuditLog<DefaultMemoryImpl> {
+        AuditLog::init(DefaultMemoryImpl::default(), DefaultMemoryImpl::default())
+    }
+
     fn mock_action() -> Action {
         Action::Transfer {
             chain: "ethereum".to_string(),
@@ -146,18 +506,19 @@ mod tests {
         PolicyResult {
             decision: PolicyDecision::Allowed,
             matched_policy: Some("Test Policy".to_string()),
+            matched_policy_id: Some(0),
             reason: "Allowed by policy".to_string(),
         }
     }
 
     #[test]
     fn test_log_action() {
-        let mut audit = AuditLog::new();
+        let mut audit = new_log();
         let principal = mock_principal(1);
         let action = mock_action();
         let policy_result = mock_policy_result_allowed();
 
-        let entry_id = audit.log_action(&action, principal, policy_result, None, 1000);
+        let entry_id = audit.log_action(&action, principal, policy_result, None, 1, 1000);
 
         assert_eq!(entry_id, 0);
         assert_eq!(audit.entries.len(), 1);
@@ -165,21 +526,57 @@ mod tests {
         let entry = audit.get_entry(entry_id).unwrap();
         assert_eq!(entry.id, 0);
         assert_eq!(entry.timestamp, 1000);
-        assert_eq!(entry.action_type, "transfer");
-        assert_eq!(entry.requester, principal);
-        assert!(entry.execution_result.is_none());
+        assert_eq!(entry.caller, principal);
+        let action = entry.action().unwrap();
+        assert_eq!(action.action_type, "transfer");
+        assert_eq!(action.policy_version, Some(1));
+        assert!(action.execution_result.is_none());
+    }
+
+    #[test]
+    fn test_log_event() {
+        let mut audit = new_log();
+        let admin = mock_principal(1);
+        let agent = mock_principal(2);
+
+        let before = RoleAssignment {
+            principal: agent,
+            role: Role::Operator,
+            assigned_at: 500,
+            assigned_by: admin,
+            expires_at: None,
+        };
+        audit.log_action(&mock_action(), agent, mock_policy_result_allowed(), None, 1, 1000);
+        let id = audit.log_event(admin, AuditEvent::RoleRevoked { before: before.clone() }, 2000);
+        audit.log_event(admin, AuditEvent::Paused, 3000);
+
+        assert_eq!(id, 1);
+        let entry = audit.get_entry(id).unwrap();
+        assert_eq!(entry.caller, admin);
+        assert_eq!(entry.timestamp, 2000);
+        assert!(entry.action().is_none());
+        assert!(matches!(&entry.event, AuditEvent::RoleRevoked { before: b } if *b == before));
+
+        // Governance events are not action requests
+        assert!(audit.log_execution(id, admin, ExecutionResult {
+            success: true,
+            chain: "ethereum".to_string(),
+            tx_hash: None,
+            error: None,
+        }, 2500).is_err());
+        assert_eq!(audit.get_entries(Some(2000), None).len(), 2);
     }
 
     #[test]
     fn test_multiple_log_entries() {
-        let mut audit = AuditLog::new();
+        let mut audit = new_log();
         let principal = mock_principal(1);
         let action = mock_action();
         let policy_result = mock_policy_result_allowed();
 
-        let id1 = audit.log_action(&action, principal, policy_result.clone(), None, 1000);
-        let id2 = audit.log_action(&action, principal, policy_result.clone(), None, 2000);
-        let id3 = audit.log_action(&action, principal, policy_result, None, 3000);
+        let id1 = audit.log_action(&action, principal, policy_result.clone(), None, 1, 1000);
+        let id2 = audit.log_action(&action, principal, policy_result.clone(), None, 1, 2000);
+        let id3 = audit.log_action(&action, principal, policy_result, None, 1, 3000);
 
         assert_eq!(id1, 0);
         assert_eq!(id2, 1);
@@ -188,13 +585,13 @@ mod tests {
     }
 
     #[test]
-    fn test_update_execution_result() {
-        let mut audit = AuditLog::new();
+    fn test_log_execution() {
+        let mut audit = new_log();
         let principal = mock_principal(1);
         let action = mock_action();
         let policy_result = mock_policy_result_allowed();
 
-        let entry_id = audit.log_action(&action, principal, policy_result, None, 1000);
+        let entry_id = audit.log_action(&action, principal, policy_result, None, 1, 1000);
 
         let exec_result = ExecutionResult {
             success: true,
@@ -203,19 +600,26 @@ mod tests {
             error: None,
         };
 
-        let result = audit.update_execution_result(entry_id, exec_result.clone());
-        assert!(result.is_ok());
-
-        let entry = audit.get_entry(entry_id).unwrap();
-        assert!(entry.execution_result.is_some());
-        let stored_result = entry.execution_result.as_ref().unwrap();
-        assert_eq!(stored_result.success, true);
-        assert_eq!(stored_result.tx_hash, Some("0xabc123".to_string()));
+        let result_id = audit.log_execution(entry_id, principal, exec_result, 2000).unwrap();
+        assert_eq!(result_id, 1);
+
+        // The request entry is left untouched; the result is a new linked entry
+        assert!(audit.get_entry(entry_id).unwrap().action().unwrap().execution_result.is_none());
+        let entry = audit.get_entry(result_id).unwrap();
+        assert_eq!(entry.timestamp, 2000);
+        match &entry.event {
+            AuditEvent::ActionExecuted { audit_id, result } => {
+                assert_eq!(*audit_id, entry_id);
+                assert!(result.success);
+                assert_eq!(result.tx_hash, Some("0xabc123".to_string()));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
     }
 
     #[test]
-    fn test_update_execution_result_not_found() {
-        let mut audit = AuditLog::new();
+    fn test_log_execution_not_found() {
+        let mut audit = new_log();
 
         let exec_result = ExecutionResult {
             success: true,
@@ -224,21 +628,21 @@ mod tests {
             error: None,
         };
 
-        let result = audit.update_execution_result(999, exec_result);
+        let result = audit.log_execution(999, mock_principal(1), exec_result, 1000);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), "Entry not found");
     }
 
     #[test]
     fn test_get_entries_all() {
-        let mut audit = AuditLog::new();
+        let mut audit = new_log();
         let principal = mock_principal(1);
         let action = mock_action();
         let policy_result = mock_policy_result_allowed();
 
-        audit.log_action(&action, principal, policy_result.clone(), None, 1000);
-        audit.log_action(&action, principal, policy_result.clone(), None, 2000);
-        audit.log_action(&action, principal, policy_result, None, 3000);
+        audit.log_action(&action, principal, policy_result.clone(), None, 1, 1000);
+        audit.log_action(&action, principal, policy_result.clone(), None, 1, 2000);
+        audit.log_action(&action, principal, policy_result, None, 1, 3000);
 
         let entries = audit.get_entries(None, None);
         assert_eq!(entries.len(), 3);
@@ -246,14 +650,14 @@ mod tests {
 
     #[test]
     fn test_get_entries_with_start() {
-        let mut audit = AuditLog::new();
+        let mut audit = new_log();
         let principal = mock_principal(1);
         let action = mock_action();
         let policy_result = mock_policy_result_allowed();
 
-        audit.log_action(&action, principal, policy_result.clone(), None, 1000);
-        audit.log_action(&action, principal, policy_result.clone(), None, 2000);
-        audit.log_action(&action, principal, policy_result, None, 3000);
+        audit.log_action(&action, principal, policy_result.clone(), None, 1, 1000);
+        audit.log_action(&action, principal, policy_result.clone(), None, 1, 2000);
+        audit.log_action(&action, principal, policy_result, None, 1, 3000);
 
         let entries = audit.get_entries(Some(2000), None);
         assert_eq!(entries.len(), 2);
@@ -262,14 +666,14 @@ mod tests {
 
     #[test]
     fn test_get_entries_with_end() {
-        let mut audit = AuditLog::new();
+        let mut audit = new_log();
         let principal = mock_principal(1);
         let action = mock_action();
         let policy_result = mock_policy_result_allowed();
 
-        audit.log_action(&action, principal, policy_result.clone(), None, 1000);
-        audit.log_action(&action, principal, policy_result.clone(), None, 2000);
-        audit.log_action(&action, principal, policy_result, None, 3000);
+        audit.log_action(&action, principal, policy_result.clone(), None, 1, 1000);
+        audit.log_action(&action, principal, policy_result.clone(), None, 1, 2000);
+        audit.log_action(&action, principal, policy_result, None, 1, 3000);
 
         let entries = audit.get_entries(None, Some(2000));
         assert_eq!(entries.len(), 2);
@@ -278,15 +682,15 @@ mod tests {
 
     #[test]
     fn test_get_entries_with_range() {
-        let mut audit = AuditLog::new();
+        let mut audit = new_log();
         let principal = mock_principal(1);
         let action = mock_action();
         let policy_result = mock_policy_result_allowed();
 
-        audit.log_action(&action, principal, policy_result.clone(), None, 1000);
-        audit.log_action(&action, principal, policy_result.clone(), None, 2000);
-        audit.log_action(&action, principal, policy_result.clone(), None, 3000);
-        audit.log_action(&action, principal, policy_result, None, 4000);
+        audit.log_action(&action, principal, policy_result.clone(), None, 1, 1000);
+        audit.log_action(&action, principal, policy_result.clone(), None, 1, 2000);
+        audit.log_action(&action, principal, policy_result.clone(), None, 1, 3000);
+        audit.log_action(&action, principal, policy_result, None, 1, 4000);
 
         let entries = audit.get_entries(Some(2000), Some(3000));
         assert_eq!(entries.len(), 2);
@@ -295,12 +699,12 @@ mod tests {
 
     #[test]
     fn test_get_entry() {
-        let mut audit = AuditLog::new();
+        let mut audit = new_log();
         let principal = mock_principal(1);
         let action = mock_action();
         let policy_result = mock_policy_result_allowed();
 
-        let entry_id = audit.log_action(&action, principal, policy_result, None, 1000);
+        let entry_id = audit.log_action(&action, principal, policy_result, None, 1, 1000);
 
         let entry = audit.get_entry(entry_id);
         assert!(entry.is_some());
@@ -309,7 +713,7 @@ mod tests {
 
     #[test]
     fn test_get_entry_not_found() {
-        let audit = AuditLog::new();
+        let audit = new_log();
         let entry = audit.get_entry(999);
         assert!(entry.is_none());
     }
@@ -339,9 +743,9 @@ mod tests {
             amount: 1000,
         };
 
-        assert_eq!(AuditLog::action_type_string(&swap), "swap");
-        assert_eq!(AuditLog::action_type_string(&transfer), "transfer");
-        assert_eq!(AuditLog::action_type_string(&approve), "approve");
+        assert_eq!(action_type_string(&swap), "swap");
+        assert_eq!(action_type_string(&transfer), "transfer");
+        assert_eq!(action_type_string(&approve), "approve");
     }
 
     #[test]
@@ -353,7 +757,7 @@ mod tests {
             amount: 1000,
         };
 
-        let json = AuditLog::action_to_json(&action);
+        let json = action_to_json(&action);
         assert!(json.contains("ethereum"));
         assert!(json.contains("USDC"));
         assert!(json.contains("0x123"));
@@ -371,7 +775,7 @@ mod tests {
             fee_tier: None,
         };
 
-        let json = AuditLog::action_to_json(&action);
+        let json = action_to_json(&action);
         assert!(json.contains("ethereum"));
         assert!(json.contains("USDC"));
         assert!(json.contains("WETH"));
@@ -381,18 +785,237 @@ mod tests {
 
     #[test]
     fn test_log_with_threshold_request_id() {
-        let mut audit = AuditLog::new();
+        let mut audit = new_log();
         let principal = mock_principal(1);
         let action = mock_action();
         let policy_result = PolicyResult {
             decision: PolicyDecision::RequiresThreshold,
             matched_policy: Some("Threshold Policy".to_string()),
+            matched_policy_id: Some(1),
             reason: "Requires 2 signatures".to_string(),
         };
 
-        let entry_id = audit.log_action(&action, principal, policy_result, Some(42), 1000);
+        let entry_id = audit.log_action(&action, principal, policy_result, Some(42), 1, 1000);
 
         let entry = audit.get_entry(entry_id).unwrap();
-        assert_eq!(entry.threshold_request_id, Some(42));
+        assert_eq!(entry.action().unwrap().threshold_request_id, Some(42));
+
+        let found = audit.find_by_threshold_request(42).unwrap();
+        assert_eq!(found.id, entry_id);
+        assert!(audit.find_by_threshold_request(43).is_none());
+    }
+
+    #[test]
+    fn test_hash_chain() {
+        let mut audit = new_log();
+        let principal = mock_principal(1);
+        assert_eq!(audit.tip_hash(), &GENESIS_HASH[..]);
+
+        audit.log_action(&mock_action(), principal, mock_policy_result_allowed(), None, 1, 1000);
+        audit.log_event(principal, AuditEvent::Paused, 2000);
+        audit.log_event(principal, AuditEvent::Resumed, 3000);
+
+        let entries = audit.get_entries(None, None);
+        assert_eq!(entries[0].prev_hash, GENESIS_HASH.to_vec());
+        for pair in entries.windows(2) {
+            assert_eq!(pair[1].prev_hash, pair[0].hash);
+        }
+        for entry in &entries {
+            let expected = entry_hash(&entry.prev_hash, entry.id, entry.timestamp, &entry.caller, &entry.event);
+            assert_eq!(entry.hash, expected);
+        }
+        assert_eq!(audit.tip_hash(), &entries[2].hash[..]);
+
+        // Rewriting an entry changes its hash, breaking the link to the next one
+        let forged = entry_hash(&entries[1].prev_hash, 1, 2000, &principal, &AuditEvent::Resumed);
+        assert_ne!(forged, entries[2].prev_hash);
+
+        let (range, tip_id) = audit.get_range(1, 1);
+        assert_eq!(range.len(), 1);
+        assert_eq!(range[0].id, 1);
+        assert_eq!(tip_id, Some(2));
+    }
+
+    #[test]
+    fn test_reopen_from_stable_memory() {
+        let entries_memory = DefaultMemoryImpl::default();
+        let index_memory = DefaultMemoryImpl::default();
+        let principal = mock_principal(1);
+
+        let mut audit = AuditLog::init(entries_memory.clone(), index_memory.clone());
+        audit.log_action(&mock_action(), principal, mock_policy_result_allowed(), Some(3), 1, 1000);
+        audit.log_event(principal, AuditEvent::Paused, 2000);
+        let tip = audit.tip_hash().to_vec();
+
+        // Reopening (as after an upgrade) reads the tip and indexes in place
+        let mut reopened = AuditLog::init(entries_memory, index_memory);
+        assert_eq!(reopened.tip_hash(), &tip[..]);
+        assert_eq!(reopened.find_by_threshold_request(3).unwrap().id, 0);
+        assert_eq!(reopened.log_event(principal, AuditEvent::Resumed, 3000), 2);
+        assert_eq!(reopened.get_entry(2).unwrap().prev_hash, tip);
+    }
+
+    #[test]
+    fn test_migrate_unchained_entries() {
+        let entries_memory = DefaultMemoryImpl::default();
+        let principal = mock_principal(1);
+
+        let mut audit = new_log();
+        audit.log_action(&mock_action(), principal, mock_policy_result_allowed(), Some(3), 1, 1000);
+        audit.log_event(principal, AuditEvent::Paused, 2000);
+
+        // Entries written by a version without hashes or indexes
+        let mut stored: StableBTreeMap<u64, Vec<u8>, DefaultMemoryImpl> = StableBTreeMap::init(entries_memory.clone());
+        for mut entry in audit.get_entries(None, None) {
+            entry.prev_hash.clear();
+            entry.hash.clear();
+            stored.insert(entry.id, candid::encode_one(&entry).unwrap());
+        }
+
+        let mut migrated = AuditLog::init(entries_memory, DefaultMemoryImpl::default());
+        migrated.rebuild().unwrap();
+        assert_eq!(migrated.tip_hash(), audit.tip_hash());
+        assert_eq!(migrated.get_entry(1).unwrap().prev_hash, audit.get_entry(0).unwrap().hash);
+        assert_eq!(migrated.find_by_threshold_request(3).unwrap().id, 0);
+
+        // Entries that no longer decode fail the migration instead of vanishing
+        stored.insert(2, vec![0xff]);
+        assert!(migrated.rebuild().is_err());
+    }
+
+    #[test]
+    fn test_query_pagination() {
+        let mut audit = new_log();
+        let principal = mock_principal(1);
+        for i in 0..5 {
+            audit.log_action(&mock_action(), principal, mock_policy_result_allowed(), None, 1, 1000 * (i + 1));
+        }
+
+        let query = AuditQuery { limit: Some(2), ..Default::default() };
+        let page = audit.query(&query, 1000);
+        assert_eq!(page.total, 5);
+        assert_eq!(page.entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![4, 3]);
+        assert_eq!(page.next_cursor, Some(3));
+
+        let page = audit.query(&AuditQuery { cursor: page.next_cursor, ..query.clone() }, 1000);
+        assert_eq!(page.entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2, 1]);
+
+        let page = audit.query(&AuditQuery { cursor: page.next_cursor, ..query.clone() }, 1000);
+        assert_eq!(page.entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(page.total, 5);
+
+        // The server cap wins over the requested limit; 0 counts only
+        assert_eq!(audit.query(&AuditQuery { limit: Some(10), ..Default::default() }, 3).entries.len(), 3);
+        let count = audit.query(&AuditQuery { limit: Some(0), decision: Some(PolicyDecision::Allowed), ..Default::default() }, 1000);
+        assert!(count.entries.is_empty());
+        assert_eq!(count.total, 5);
+
+        // Time ranges bound both the page and the count, cursor included
+        let ranged = AuditQuery { start: Some(2000), end: Some(4000), limit: Some(2), ..Default::default() };
+        let page = audit.query(&ranged, 1000);
+        assert_eq!(page.entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3, 2]);
+        assert_eq!(page.total, 3);
+        let page = audit.query(&AuditQuery { cursor: page.next_cursor, ..ranged }, 1000);
+        assert_eq!(page.entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(page.total, 3);
+        assert_eq!(audit.query(&AuditQuery { start: Some(5001), ..Default::default() }, 1000).total, 0);
+    }
+
+    #[test]
+    fn test_query_counts() {
+        let mut audit = new_log();
+        let agent = mock_principal(1);
+        let first = audit.log_action(&mock_action(), agent, mock_policy_result_allowed(), None, 1, 1000);
+        audit.log_action(&mock_action(), agent, mock_policy_result_allowed(), None, 1, 2000);
+        audit.log_event(mock_principal(2), AuditEvent::Paused, 3000);
+        audit.log_execution(first, agent, ExecutionResult {
+            success: true,
+            chain: "ethereum".to_string(),
+            tx_hash: None,
+            error: None,
+        }, 4000).unwrap();
+
+        fn total(audit: &AuditLog<DefaultMemoryImpl>, query: AuditQuery) -> u64 {
+            audit.query(&AuditQuery { limit: Some(0), ..query }, 1000).total
+        }
+        assert_eq!(total(&audit, AuditQuery::default()), 4);
+        assert_eq!(total(&audit, AuditQuery { requester: Some(agent), ..Default::default() }), 3);
+        assert_eq!(total(&audit, AuditQuery { success: Some(true), ..Default::default() }), 1);
+        assert_eq!(total(&audit, AuditQuery { token: Some("usdc".to_string()), ..Default::default() }), 2);
+        assert_eq!(total(&audit, AuditQuery { token: Some("WETH".to_string()), ..Default::default() }), 0);
+        assert_eq!(
+            total(&audit, AuditQuery { requester: Some(agent), action_type: Some("transfer".to_string()), ..Default::default() }),
+            2
+        );
+
+        // Time ranges and several filters are counted inside the range
+        let since = |start: u64| AuditQuery { start: Some(start), ..Default::default() };
+        assert_eq!(total(&audit, since(2000)), 3);
+        assert_eq!(total(&audit, AuditQuery { requester: Some(agent), ..since(2000) }), 2);
+        assert_eq!(total(&audit, AuditQuery { success: Some(true), end: Some(1500), ..Default::default() }), 1);
+        assert_eq!(total(&audit, AuditQuery { success: Some(true), ..since(1500) }), 0);
+        assert_eq!(
+            total(&audit, AuditQuery { requester: Some(agent), decision: Some(PolicyDecision::Allowed), ..since(1500) }),
+            1
+        );
+
+        // Counts survive a rebuild unchanged
+        audit.rebuild().unwrap();
+        assert_eq!(total(&audit, AuditQuery { requester: Some(agent), ..Default::default() }), 3);
+        assert_eq!(total(&audit, AuditQuery { success: Some(true), ..Default::default() }), 1);
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let mut audit = new_log();
+        let agent = mock_principal(1);
+        let admin = mock_principal(2);
+        let swap = Action::Swap {
+            chain: "sepolia".to_string(),
+            token_in: "USDC".to_string(),
+            token_out: "WETH".to_string(),
+            amount_in: 1000,
+            min_amount_out: 500,
+            fee_tier: None,
+        };
+        let threshold = PolicyResult {
+            decision: PolicyDecision::RequiresThreshold,
+            matched_policy: None,
+            matched_policy_id: None,
+            reason: "Requires 2 signatures".to_string(),
+        };
+
+        let transfer_id = audit.log_action(&mock_action(), agent, mock_policy_result_allowed(), None, 1, 1000);
+        let swap_id = audit.log_action(&swap, agent, threshold, Some(7), 1, 2000);
+        audit.log_event(admin, AuditEvent::Paused, 3000);
+        audit.log_execution(transfer_id, agent, ExecutionResult {
+            success: true,
+            chain: "ethereum".to_string(),
+            tx_hash: Some("0xabc".to_string()),
+            error: None,
+        }, 4000).unwrap();
+
+        let ids = |query: AuditQuery| -> Vec<u64> {
+            audit.query(&query, 1000).entries.iter().map(|e| e.id).collect()
+        };
+        assert_eq!(ids(AuditQuery { requester: Some(admin), ..Default::default() }), vec![2]);
+        assert_eq!(ids(AuditQuery { action_type: Some("swap".to_string()), ..Default::default() }), vec![swap_id]);
+        assert_eq!(
+            ids(AuditQuery { decision: Some(PolicyDecision::Allowed), ..Default::default() }),
+            vec![transfer_id]
+        );
+        assert_eq!(ids(AuditQuery { chain: Some("Sepolia".to_string()), ..Default::default() }), vec![swap_id]);
+        assert_eq!(ids(AuditQuery { token: Some("weth".to_string()), ..Default::default() }), vec![swap_id]);
+        assert_eq!(ids(AuditQuery { token: Some("USDC".to_string()), ..Default::default() }), vec![swap_id, transfer_id]);
+        assert_eq!(ids(AuditQuery { threshold_request_id: Some(7), ..Default::default() }), vec![swap_id]);
+        assert_eq!(ids(AuditQuery { success: Some(true), ..Default::default() }), vec![transfer_id]);
+        assert!(ids(AuditQuery { success: Some(false), ..Default::default() }).is_empty());
+
+        // Execution results of the page's requests come along
+        let page = audit.query(&AuditQuery { success: Some(true), ..Default::default() }, 1000);
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.results[0].id, 3);
     }
 }
@@ -1,11 +1,18 @@
 use candid::{CandidType, Deserialize};
 use serde::Serialize;
 
-#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+use crate::types::{Permission, Role};
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub enum ChainGuardError {
     // Permission errors
     Unauthorized,
-    InsufficientPermissions { required: String },
+    InsufficientPermissions { required: Permission },
+    RoleNotFound { name: String },
+    RoleAlreadyExists { name: String },
+    RoleInUse { name: String },
+    LastOwner,
+    OwnershipProposalNotFound,
 
     // Configuration errors
     NotInitialized,
@@ -14,15 +21,32 @@ pub enum ChainGuardError {
 
     // Policy errors
     PolicyNotFound { id: u64 },
+    PolicyVersionNotFound { version: u64 },
     PolicyEvaluationFailed { reason: String },
+    PolicyDenied { policy_id: Option<u64>, reason: String },
 
     // Threshold errors
     RequestNotFound { id: u64 },
     RequestExpired,
     RequestAlreadySigned,
+    RequestAlreadyRejected,
     RequestNotApproved,
+    SignerNotEligible { required_roles: Vec<Role> },
+    RequesterCannotSign,
+    DailyApprovalLimitReached { limit: u64 },
+    RequestTimelocked { executable_at: u64 },
     InvalidRequestStatus { expected: String, actual: String },
 
+    // Agent session errors
+    SessionNotFound,
+    SessionExpired,
+    SessionRevoked,
+    SessionActionNotAllowed { action_type: String },
+    SessionChainNotAllowed { chain: String },
+    SessionTokenNotAllowed { token: String },
+    SessionTransactionLimitExceeded { limit: u64, requested: u64 },
+    SessionBudgetExceeded { remaining: u64, requested: u64 },
+
     // Execution errors
     ExecutionFailed { reason: String },
     ChainNotSupported { chain: String },
@@ -37,26 +61,68 @@ impl ChainGuardError {
         match self {
             ChainGuardError::Unauthorized => "Unauthorized access".to_string(),
             ChainGuardError::InsufficientPermissions { required } => {
-                format!("Insufficient permissions. Required: {}", required)
+                format!("Insufficient permissions. Required: {:?}", required)
             }
+            ChainGuardError::RoleNotFound { name } => format!("Role not found: {}", name),
+            ChainGuardError::RoleAlreadyExists { name } => format!("Role already exists: {}", name),
+            ChainGuardError::RoleInUse { name } => format!("Role still assigned: {}", name),
+            ChainGuardError::LastOwner => "Cannot remove the last Owner".to_string(),
+            ChainGuardError::OwnershipProposalNotFound => "No pending ownership proposal".to_string(),
             ChainGuardError::NotInitialized => "ChainGuard not initialized".to_string(),
             ChainGuardError::AlreadyInitialized => "ChainGuard already initialized".to_string(),
             ChainGuardError::InvalidConfiguration { reason } => {
                 format!("Invalid configuration: {}", reason)
             }
             ChainGuardError::PolicyNotFound { id } => format!("Policy not found: {}", id),
+            ChainGuardError::PolicyVersionNotFound { version } => {
+                format!("Policy version not found: {}", version)
+            }
             ChainGuardError::PolicyEvaluationFailed { reason } => {
                 format!("Policy evaluation failed: {}", reason)
             }
+            ChainGuardError::PolicyDenied { reason, .. } => format!("Denied by policy: {}", reason),
             ChainGuardError::RequestNotFound { id } => format!("Request not found: {}", id),
             ChainGuardError::RequestExpired => "Request has expired".to_string(),
             ChainGuardError::RequestAlreadySigned => {
                 "Request already signed by this principal".to_string()
             }
+            ChainGuardError::RequestAlreadyRejected => {
+                "Request already rejected by this principal".to_string()
+            }
             ChainGuardError::RequestNotApproved => "Request not yet approved".to_string(),
+            ChainGuardError::SignerNotEligible { required_roles } => {
+                format!("Signer role not eligible. Required one of: {:?}", required_roles)
+            }
+            ChainGuardError::RequesterCannotSign => {
+                "Requesters cannot sign their own requests".to_string()
+            }
+            ChainGuardError::DailyApprovalLimitReached { limit } => {
+                format!("Daily approval limit for this requester reached: {}", limit)
+            }
+            ChainGuardError::RequestTimelocked { executable_at } => {
+                format!("Request is time-locked until {}", executable_at)
+            }
             ChainGuardError::InvalidRequestStatus { expected, actual } => {
                 format!("Invalid request status. Expected: {}, Actual: {}", expected, actual)
             }
+            ChainGuardError::SessionNotFound => "Agent session not found".to_string(),
+            ChainGuardError::SessionExpired => "Agent session has expired".to_string(),
+            ChainGuardError::SessionRevoked => "Agent session has been revoked".to_string(),
+            ChainGuardError::SessionActionNotAllowed { action_type } => {
+                format!("Action type not allowed for this session: {}", action_type)
+            }
+            ChainGuardError::SessionChainNotAllowed { chain } => {
+                format!("Chain not allowed for this session: {}", chain)
+            }
+            ChainGuardError::SessionTokenNotAllowed { token } => {
+                format!("Token not covered by this session's budget: {}", token)
+            }
+            ChainGuardError::SessionTransactionLimitExceeded { limit, requested } => {
+                format!("Session per-transaction limit exceeded: {} > {}", requested, limit)
+            }
+            ChainGuardError::SessionBudgetExceeded { remaining, requested } => {
+                format!("Session budget exceeded: requested {}, remaining {}", requested, remaining)
+            }
             ChainGuardError::ExecutionFailed { reason } => {
                 format!("Execution failed: {}", reason)
             }
@@ -1,8 +1,8 @@
 use candid::Principal;
 use ic_cdk::api::time;
-use ic_cdk_macros::{init, pre_upgrade, post_upgrade, query, update};
+use ic_cdk_macros::{init, post_upgrade, query, update};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::time::Duration;
 
 mod types;
 mod access_control;
@@ -15,12 +15,28 @@ mod config;
 mod abi;
 mod universal_router;
 mod stable_memory;
+mod spend_ledger;
+mod policy_history;
+mod sessions;
+mod migrations;
 
 use types::*;
 use access_control::AccessControl;
 use threshold::ThresholdSigner;
 use audit::AuditLog;
 use executor::ChainExecutor;
+use spend_ledger::SpendLedger;
+use policy_history::PolicyHistory;
+use sessions::SessionManager;
+use errors::{ChainGuardError, ChainGuardResult};
+
+// How often expired role grants are pruned
+const ROLE_EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_secs(3600);
+// How often overdue threshold requests are expired
+const REQUEST_EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_secs(300);
+// How often time-locked requests past their window are executed
+const TIMELOCK_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_AUDIT_RANGE: u64 = 1000;
 
 thread_local! {
     static STATE: RefCell<ChainGuardState> = RefCell::new(ChainGuardState::default());
@@ -30,11 +46,12 @@ struct ChainGuardState {
     config: Option<ChainGuardConfig>,
     access_control: AccessControl,
     threshold_signer: ThresholdSigner,
-    audit_log: AuditLog,
+    audit_log: AuditLog<stable_memory::Memory>,
     executor: ChainExecutor,
     paused: bool,
-    daily_volume: u64,
-    last_reset: u64,
+    spend_ledger: SpendLedger,
+    policy_history: PolicyHistory,
+    sessions: SessionManager,
 }
 
 impl Default for ChainGuardState {
@@ -43,11 +60,12 @@ impl Default for ChainGuardState {
             config: None,
             access_control: AccessControl::default(),
             threshold_signer: ThresholdSigner::default(),
-            audit_log: AuditLog::default(),
+            audit_log: AuditLog::init(stable_memory::audit_memory(), stable_memory::audit_index_memory()),
             executor: ChainExecutor::default(),
             paused: false,
-            daily_volume: 0,
-            last_reset: 0,
+            spend_ledger: SpendLedger::default(),
+            policy_history: PolicyHistory::default(),
+            sessions: SessionManager::default(),
         }
     }
 }
@@ -60,12 +78,63 @@ fn init() {
     let caller = ic_cdk::caller();
     STATE.with(|state| {
         let mut state = state.borrow_mut();
-        state.access_control.assign_role(caller, Role::Owner);
+        state.access_control.assign_role(RoleAssignment {
+            principal: caller,
+            role: Role::Owner,
+            assigned_at: time(),
+            assigned_by: caller,
+            expires_at: None,
+        });
+        persist_roles(&state, &caller);
+        persist_executor_config(&state);
+        certify_audit_tip(&state);
+    });
+    // A fresh canister starts at the current schema; without the version the next
+    // upgrade would re-run every migration over current data
+    if let Err(e) = migrations::init() {
+        ic_cdk::trap(&format!("Failed to record schema version: {}", e));
+    }
+
+    start_role_expiry_timer();
+    start_request_expiry_timer();
+    start_timelock_timer();
+}
+
+// Periodically drop expired role grants (has_permission already ignores them)
+fn start_role_expiry_timer() {
+    ic_cdk_timers::set_timer_interval(ROLE_EXPIRY_SWEEP_INTERVAL, || {
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            for before in state.access_control.prune_expired_roles(time()) {
+                persist_roles(&state, &before.principal);
+                log_event(&mut state, ic_cdk::id(), AuditEvent::RoleExpired { before });
+            }
+        });
+    });
+}
+
+// Periodically expire threshold requests whose signing window has passed
+fn start_request_expiry_timer() {
+    ic_cdk_timers::set_timer_interval(REQUEST_EXPIRY_SWEEP_INTERVAL, || {
+        STATE.with(|state| expire_requests(&mut state.borrow_mut(), time()));
+    });
+}
+
+// Periodically execute time-locked requests whose window has passed (skipped while paused)
+fn start_timelock_timer() {
+    ic_cdk_timers::set_timer_interval(TIMELOCK_SWEEP_INTERVAL, || {
+        let current_time = time();
+        let due = STATE.with(|state| state.borrow().threshold_signer.due_requests(current_time));
+        for request_id in due {
+            ic_cdk::spawn(async move {
+                let _ = execute_timelocked_request(request_id, ic_cdk::id(), current_time).await;
+            });
+        }
     });
 }
 
 #[update]
-fn initialize(config: ChainGuardConfig) -> Result<(), String> {
+fn initialize(config: ChainGuardConfig) -> ChainGuardResult<()> {
     let caller = ic_cdk::caller();
 
     STATE.with(|state| {
@@ -73,19 +142,25 @@ fn initialize(config: ChainGuardConfig) -> Result<(), String> {
 
         // Check if already initialized
         if state.config.is_some() {
-            return Err("Already initialized".to_string());
+            return Err(ChainGuardError::AlreadyInitialized);
         }
 
         // Only owner can initialize
-        if !state.access_control.has_role(&caller, &Role::Owner) {
-            return Err("Only owner can initialize".to_string());
+        if !state.access_control.has_role(&caller, &Role::Owner, time()) {
+            return Err(ChainGuardError::Unauthorized);
         }
 
         // Add policies from config
         for policy in &config.policies {
             state.access_control.add_policy(policy.clone());
         }
+        if !config.policies.is_empty() {
+            persist_policies(&state);
+            record_policy_version(&mut state, PolicyChange::Initialized, caller);
+        }
 
+        write_through(stable_memory::store_config(&config));
+        log_event(&mut state, caller, AuditEvent::Initialized { config: config.clone() });
         state.config = Some(config);
         Ok(())
     })
@@ -94,42 +169,140 @@ fn initialize(config: ChainGuardConfig) -> Result<(), String> {
 // ============== ROLE MANAGEMENT ==============
 
 #[update]
-fn assign_role(principal: Principal, role: Role) -> Result<(), String> {
+fn assign_role(principal: Principal, role: Role, expires_at: Option<u64>) -> ChainGuardResult<()> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+
+        if !state.access_control.has_permission(&caller, &Permission::Configure, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Configure });
+        }
+
+        if let Role::Custom(name) = &role {
+            if !state.access_control.role_exists(&role) {
+                return Err(ChainGuardError::RoleNotFound { name: name.clone() });
+            }
+        }
+
+        let now = time();
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(ChainGuardError::InvalidConfiguration {
+                reason: "Role grant expiry must be in the future".to_string(),
+            });
+        }
+
+        // An expiring grant must not replace the last permanent Owner
+        if role == Role::Owner
+            && expires_at.is_some()
+            && !state.access_control.has_other_permanent_owner(&principal)
+        {
+            return Err(ChainGuardError::LastOwner);
+        }
+
+        let after = RoleAssignment {
+            principal,
+            role,
+            assigned_at: now,
+            assigned_by: caller,
+            expires_at,
+        };
+        let before = state.access_control.assign_role(after.clone());
+        persist_roles(&state, &principal);
+        log_event(&mut state, caller, AuditEvent::RoleAssigned { before, after });
+        Ok(())
+    })
+}
+
+#[update]
+fn revoke_role(principal: Principal, role: Role) -> ChainGuardResult<()> {
     let caller = ic_cdk::caller();
 
     STATE.with(|state| {
         let mut state = state.borrow_mut();
 
-        if !state.access_control.has_permission(&caller, &Permission::Configure) {
-            return Err("No permission to assign roles".to_string());
+        if !state.access_control.has_permission(&caller, &Permission::Configure, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Configure });
+        }
+
+        if let Some(before) = state.access_control.revoke_role(&principal, &role, time())? {
+            persist_roles(&state, &principal);
+            log_event(&mut state, caller, AuditEvent::RoleRevoked { before });
         }
+        Ok(())
+    })
+}
+
+// Two-step ownership transfer: an Owner proposes, the new principal accepts
+#[update]
+fn propose_owner(new_owner: Principal) -> ChainGuardResult<OwnershipProposal> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+
+        if !state.access_control.has_role(&caller, &Role::Owner, time()) {
+            return Err(ChainGuardError::Unauthorized);
+        }
+
+        let proposal = state.access_control.propose_owner(new_owner, caller, time())?;
+        persist_ownership_proposal(&state);
+        log_event(&mut state, caller, AuditEvent::OwnershipProposed { proposal: proposal.clone() });
+        Ok(proposal)
+    })
+}
+
+// Called by the proposed principal; Owner moves from the proposer to the caller
+#[update]
+fn accept_ownership() -> ChainGuardResult<()> {
+    let caller = ic_cdk::caller();
 
-        state.access_control.assign_role(principal, role);
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        // A proposal whose proposer lost Owner is dropped even though acceptance fails
+        let accepted = state.access_control.accept_ownership(&caller, time());
+        persist_ownership_proposal(&state);
+        let proposal = accepted?;
+        persist_roles(&state, &proposal.proposed_by);
+        persist_roles(&state, &caller);
+        log_event(&mut state, caller, AuditEvent::OwnershipTransferred { proposal });
         Ok(())
     })
 }
 
 #[update]
-fn revoke_role(principal: Principal, role: Role) -> Result<(), String> {
+fn cancel_ownership_proposal() -> ChainGuardResult<()> {
     let caller = ic_cdk::caller();
 
     STATE.with(|state| {
         let mut state = state.borrow_mut();
 
-        if !state.access_control.has_permission(&caller, &Permission::Configure) {
-            return Err("No permission to revoke roles".to_string());
+        if !state.access_control.has_role(&caller, &Role::Owner, time()) {
+            return Err(ChainGuardError::Unauthorized);
         }
 
-        state.access_control.revoke_role(&principal, &role);
+        let proposal = state.access_control.ownership_proposal().cloned();
+        state.access_control.cancel_ownership_proposal()?;
+        persist_ownership_proposal(&state);
+        if let Some(proposal) = proposal {
+            log_event(&mut state, caller, AuditEvent::OwnershipProposalCancelled { proposal });
+        }
         Ok(())
     })
 }
 
+#[query]
+fn get_ownership_proposal() -> Option<OwnershipProposal> {
+    STATE.with(|state| {
+        state.borrow().access_control.ownership_proposal().cloned()
+    })
+}
+
 #[query]
 fn get_roles(principal: Principal) -> Vec<Role> {
     STATE.with(|state| {
         let state = state.borrow();
-        state.access_control.get_roles(&principal)
+        state.access_control.get_roles(&principal, time())
     })
 }
 
@@ -137,62 +310,152 @@ fn get_roles(principal: Principal) -> Vec<Role> {
 fn list_role_assignments() -> Vec<(Principal, Role)> {
     STATE.with(|state| {
         let state = state.borrow();
-        state.access_control.list_role_assignments()
+        state.access_control.list_role_assignments(time())
     })
 }
 
-// ============== POLICY MANAGEMENT ==============
+// All grants with assigner, grant time and expiry
+#[query]
+fn list_role_grants() -> Vec<RoleAssignment> {
+    STATE.with(|state| {
+        let state = state.borrow();
+        state.access_control.list_role_grants()
+    })
+}
 
 #[update]
-fn add_policy(policy: Policy) -> Result<u64, String> {
+fn create_role(name: String, permissions: Vec<Permission>) -> ChainGuardResult<()> {
     let caller = ic_cdk::caller();
 
     STATE.with(|state| {
         let mut state = state.borrow_mut();
 
-        if !state.access_control.has_permission(&caller, &Permission::Configure) {
-            return Err("No permission to add policies".to_string());
+        if !state.access_control.has_permission(&caller, &Permission::Configure, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Configure });
         }
 
-        Ok(state.access_control.add_policy(policy))
+        state.access_control.create_role(name.clone(), permissions)?;
+        persist_custom_roles(&state);
+        let after = Some(state.access_control.role_permissions(&Role::Custom(name.clone())));
+        log_event(&mut state, caller, AuditEvent::RoleDefinitionChanged { name, before: None, after });
+        Ok(())
     })
 }
 
 #[update]
-fn update_policy(index: u64, policy: Policy) -> Result<(), String> {
+fn update_role(name: String, permissions: Vec<Permission>) -> ChainGuardResult<()> {
     let caller = ic_cdk::caller();
 
     STATE.with(|state| {
         let mut state = state.borrow_mut();
 
-        if !state.access_control.has_permission(&caller, &Permission::Configure) {
-            return Err("No permission to update policies".to_string());
+        if !state.access_control.has_permission(&caller, &Permission::Configure, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Configure });
         }
 
-        if state.access_control.update_policy(index as usize, policy) {
-            Ok(())
-        } else {
-            Err("Policy not found".to_string())
+        let role = Role::Custom(name.clone());
+        let before = Some(state.access_control.role_permissions(&role));
+        state.access_control.update_role(name.clone(), permissions)?;
+        persist_custom_roles(&state);
+        let after = Some(state.access_control.role_permissions(&role));
+        log_event(&mut state, caller, AuditEvent::RoleDefinitionChanged { name, before, after });
+        Ok(())
+    })
+}
+
+#[update]
+fn delete_role(name: String) -> ChainGuardResult<()> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+
+        if !state.access_control.has_permission(&caller, &Permission::Configure, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Configure });
         }
+
+        let before = Some(state.access_control.role_permissions(&Role::Custom(name.clone())));
+        state.access_control.delete_role(name.clone())?;
+        persist_custom_roles(&state);
+        log_event(&mut state, caller, AuditEvent::RoleDefinitionChanged { name, before, after: None });
+        Ok(())
+    })
+}
+
+#[query]
+fn list_roles() -> Vec<RoleDefinition> {
+    STATE.with(|state| {
+        let state = state.borrow();
+        state.access_control.list_roles()
     })
 }
 
+// ============== POLICY MANAGEMENT ==============
+
 #[update]
-fn remove_policy(index: u64) -> Result<(), String> {
+fn add_policy(policy: Policy) -> ChainGuardResult<u64> {
     let caller = ic_cdk::caller();
 
     STATE.with(|state| {
         let mut state = state.borrow_mut();
 
-        if !state.access_control.has_permission(&caller, &Permission::Configure) {
-            return Err("No permission to remove policies".to_string());
+        if !state.access_control.has_permission(&caller, &Permission::Configure, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Configure });
         }
 
-        if state.access_control.remove_policy(index as usize) {
-            Ok(())
-        } else {
-            Err("Policy not found".to_string())
+        let id = state.access_control.add_policy(policy);
+        persist_policies(&state);
+        let version = record_policy_version(&mut state, PolicyChange::Added(id), caller);
+        if let Some(after) = state.access_control.get_policy(id).cloned() {
+            log_event(&mut state, caller, AuditEvent::PolicyAdded { version, after });
+        }
+        Ok(id)
+    })
+}
+
+#[update]
+fn update_policy(id: u64, policy: Policy) -> ChainGuardResult<()> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+
+        if !state.access_control.has_permission(&caller, &Permission::Configure, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Configure });
+        }
+
+        let before = state.access_control.get_policy(id)
+            .cloned()
+            .ok_or(ChainGuardError::PolicyNotFound { id })?;
+        state.access_control.update_policy(id, policy);
+        persist_policies(&state);
+        let version = record_policy_version(&mut state, PolicyChange::Updated(id), caller);
+        if let Some(after) = state.access_control.get_policy(id).cloned() {
+            log_event(&mut state, caller, AuditEvent::PolicyUpdated { version, before, after });
+        }
+        Ok(())
+    })
+}
+
+#[update]
+fn remove_policy(id: u64) -> ChainGuardResult<()> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+
+        if !state.access_control.has_permission(&caller, &Permission::Configure, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Configure });
         }
+
+        let before = state.access_control.get_policy(id)
+            .cloned()
+            .ok_or(ChainGuardError::PolicyNotFound { id })?;
+        state.access_control.remove_policy(id);
+        persist_policies(&state);
+        let version = record_policy_version(&mut state, PolicyChange::Removed(id), caller);
+        log_event(&mut state, caller, AuditEvent::PolicyRemoved { version, before });
+        Ok(())
     })
 }
 
@@ -204,6 +467,81 @@ fn list_policies() -> Vec<Policy> {
     })
 }
 
+#[query]
+fn get_policy(id: u64) -> Option<Policy> {
+    STATE.with(|state| {
+        let state = state.borrow();
+        state.access_control.get_policy(id).cloned()
+    })
+}
+
+// ============== POLICY HISTORY ==============
+
+// Snapshot the current policy set as a new version
+fn record_policy_version(state: &mut ChainGuardState, change: PolicyChange, author: Principal) -> u64 {
+    let policies = state.access_control.get_policies();
+    let version = state.policy_history.record(change, policies, author, time());
+    if let Some(recorded) = state.policy_history.get_version(version) {
+        write_through(stable_memory::store_policy_version(recorded));
+    }
+    version
+}
+
+#[query]
+fn list_policy_versions() -> Vec<PolicyVersionSummary> {
+    STATE.with(|state| {
+        let state = state.borrow();
+        state.policy_history.list_versions()
+    })
+}
+
+#[query]
+fn get_policy_version(version: u64) -> Option<PolicyVersion> {
+    STATE.with(|state| {
+        let state = state.borrow();
+        state.policy_history.get_version(version).cloned()
+    })
+}
+
+#[query]
+fn diff_policy_versions(from_version: u64, to_version: u64) -> ChainGuardResult<PolicyDiff> {
+    STATE.with(|state| {
+        let state = state.borrow();
+        state.policy_history.diff(from_version, to_version)
+    })
+}
+
+// Restore the policy set of an earlier version; recorded as a new version
+#[update]
+fn rollback_policies(version: u64) -> ChainGuardResult<u64> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+
+        if !state.access_control.has_permission(&caller, &Permission::Configure, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Configure });
+        }
+
+        let policies = state.policy_history.get_version(version)
+            .map(|v| v.policies.clone())
+            .ok_or(ChainGuardError::PolicyVersionNotFound { version })?;
+
+        let before = state.access_control.get_policies();
+        state.access_control.replace_policies(policies);
+        persist_policies(&state);
+        let new_version = record_policy_version(&mut state, PolicyChange::RolledBack(version), caller);
+        let after = state.access_control.get_policies();
+        log_event(&mut state, caller, AuditEvent::PoliciesRolledBack {
+            version: new_version,
+            restored_version: version,
+            before,
+            after,
+        });
+        Ok(new_version)
+    })
+}
+
 // ============== ACTION EXECUTION ==============
 
 #[update]
@@ -212,57 +550,103 @@ async fn request_action(action: Action) -> ActionResult {
     let current_time = time();
 
     // Evaluate policy and create audit entry
-    let (decision, audit_id_opt) = STATE.with(|state| {
+    let outcome = STATE.with(|state| -> ChainGuardResult<(PolicyDecision, u64)> {
         let mut state = state.borrow_mut();
 
         // Check if paused
         if state.paused {
-            return (None, None);
+            return Err(ChainGuardError::SystemPaused);
         }
 
-        // Check permission
-        if !state.access_control.has_permission(&caller, &Permission::Execute) {
-            return (None, None);
+        // Check permission; an agent session grants Execute within its scope
+        let has_session = state.sessions.get(&caller).is_some();
+        if !has_session && !state.access_control.has_permission(&caller, &Permission::Execute, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Execute });
         }
-
-        // Evaluate policies
-        let policy_result = state.access_control.evaluate_action(&action, &caller, state.daily_volume);
+        state.sessions.authorize(&caller, &action, current_time)?;
+
+        // Evaluate policies against this requester's rolling 24h volume (including reservations)
+        let daily_spent = state.spend_ledger.spent_for_action(&action, &caller, current_time);
+        let (policy_result, matched_policy) = state.access_control.evaluate_action_with_policy(
+            &action,
+            &caller,
+            daily_spent,
+            current_time,
+        );
+        let policy_version = state.policy_history.current_version();
 
         match policy_result.decision {
             PolicyDecision::Denied => {
-                state.audit_log.log_action(&action, caller, policy_result.clone(), None, current_time);
-                (Some(PolicyDecision::Denied), None)
+                state.audit_log.log_action(&action, caller, policy_result.clone(), None, policy_version, current_time);
+                certify_audit_tip(&state);
+                Err(ChainGuardError::PolicyDenied {
+                    policy_id: policy_result.matched_policy_id,
+                    reason: policy_result.reason,
+                })
             }
             PolicyDecision::RequiresThreshold => {
-                let required_sigs = state.config.as_ref().unwrap().default_threshold.required;
+                // Use the matched policy's quorum, weight, signer roles, signing window and
+                // time-lock, falling back to the default threshold and expiry
+                let default_required = state.config.as_ref().unwrap().default_threshold.required;
+                let (required_sigs, required_weight, from_roles, expiry_seconds, timelock_seconds) = match matched_policy.map(|p| p.action) {
+                    Some(PolicyAction::RequireThreshold { required, from_roles, expiry_seconds, timelock_seconds, required_weight }) => {
+                        let required = if required > 0 { required } else { default_required };
+                        (required, required_weight.filter(|w| *w > 0), from_roles, expiry_seconds, timelock_seconds)
+                    }
+                    _ => (default_required, None, Vec::new(), None, None),
+                };
+                let to_nanos = |seconds: Option<u64>| {
+                    seconds.filter(|s| *s > 0).map(|s| s.saturating_mul(1_000_000_000))
+                };
                 let request = state.threshold_signer.create_request(
                     action.clone(),
                     caller,
                     required_sigs,
+                    required_weight,
+                    from_roles,
+                    to_nanos(expiry_seconds),
+                    to_nanos(timelock_seconds),
                     current_time,
                 );
-                state.audit_log.log_action(&action, caller, policy_result, Some(request.id), current_time);
-                (Some(PolicyDecision::RequiresThreshold), Some(request.id))
+                write_through(stable_memory::store_threshold_request(&request));
+                let audit_id = state.audit_log.log_action(&action, caller, policy_result, Some(request.id), policy_version, current_time);
+                certify_audit_tip(&state);
+
+                // Hold the volume while signatures are collected
+                state.spend_ledger.reserve(audit_id, &action, caller, current_time);
+                persist_spend_ledger(&state);
+                charge_session(&mut state, caller, audit_id);
+                Ok((PolicyDecision::RequiresThreshold, request.id))
             }
             PolicyDecision::Allowed => {
-                let audit_id = state.audit_log.log_action(&action, caller, policy_result, None, current_time);
-                (Some(PolicyDecision::Allowed), Some(audit_id))
+                let matched_policy_id = policy_result.matched_policy_id;
+                let audit_id = state.audit_log.log_action(&action, caller, policy_result, None, policy_version, current_time);
+                certify_audit_tip(&state);
+
+                // Hold the volume and start the cooldown while the execution is in flight,
+                // so concurrent requests are evaluated against them
+                state.spend_ledger.reserve(audit_id, &action, caller, current_time);
+                persist_spend_ledger(&state);
+                if let Some(policy_id) = matched_policy_id {
+                    state.access_control.reserve_execution(audit_id, caller, policy_id, &action, current_time);
+                }
+                charge_session(&mut state, caller, audit_id);
+                Ok((PolicyDecision::Allowed, audit_id))
             }
         }
     });
 
-    // Handle paused state
-    if decision.is_none() {
-        return ActionResult::Denied { reason: "System is paused or no permission".to_string() };
-    }
+    // Paused, missing permission, outside the agent session or denied by policy
+    let (decision, id) = match outcome {
+        Ok(outcome) => outcome,
+        Err(error) => return ActionResult::Denied { error },
+    };
 
-    match decision.unwrap() {
-        PolicyDecision::Denied => {
-            ActionResult::Denied { reason: "Policy denied".to_string() }
-        }
+    match decision {
+        PolicyDecision::Denied => unreachable!("denials are returned as errors above"),
         PolicyDecision::RequiresThreshold => {
             let request = STATE.with(|state| {
-                state.borrow().threshold_signer.get_request(audit_id_opt.unwrap()).cloned()
+                state.borrow().threshold_signer.get_request(id).cloned()
             });
             ActionResult::PendingSignatures(request.unwrap())
         }
@@ -275,10 +659,25 @@ async fn request_action(action: Action) -> ActionResult {
             // Execute action using ChainExecutor
             let result = executor.execute_action(&action).await;
 
-            // Update audit log with execution result
+            // Append the execution result to the audit log
             STATE.with(|state| {
                 let mut state = state.borrow_mut();
-                let _ = state.audit_log.update_execution_result(audit_id_opt.unwrap(), result.clone());
+                let audit_id = id;
+                if state.audit_log.log_execution(audit_id, caller, result.clone(), time()).is_ok() {
+                    certify_audit_tip(&state);
+                }
+
+                // Count executed volume and the cooldown; failed executions free their reservations
+                if result.success {
+                    state.spend_ledger.commit(audit_id, time());
+                    persist_spend_ledger(&state);
+                    if let Some(record) = state.access_control.commit_execution(audit_id, time()) {
+                        write_through(stable_memory::store_execution_record(&record));
+                    }
+                } else {
+                    release_spend(&mut state, audit_id);
+                    state.access_control.release_execution(audit_id);
+                }
             });
 
             ActionResult::Executed(result)
@@ -286,6 +685,36 @@ async fn request_action(action: Action) -> ActionResult {
     }
 }
 
+// Dry-run policy evaluation with a full trace (no audit entry, no execution)
+#[query]
+fn explain_action(action: Action, requester: Option<Principal>) -> ChainGuardResult<ActionExplanation> {
+    let caller = ic_cdk::caller();
+    let requester = requester.unwrap_or(caller);
+    let current_time = time();
+
+    STATE.with(|state| {
+        let state = state.borrow();
+
+        if requester != caller && !state.access_control.has_permission(&caller, &Permission::ViewLogs, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::ViewLogs });
+        }
+
+        let daily_spent = state.spend_ledger.spent_for_action(&action, &requester, current_time);
+        let (result, policies) = state.access_control.explain_action(&action, &requester, daily_spent, current_time);
+
+        Ok(ActionExplanation {
+            result,
+            policies,
+            requester,
+            daily_spent,
+            evaluated_at: current_time,
+            paused: state.paused,
+            has_execute_permission: state.sessions.get(&requester).is_some()
+                || state.access_control.has_permission(&requester, &Permission::Execute, time()),
+        })
+    })
+}
+
 // ============== THRESHOLD SIGNING ==============
 
 #[query]
@@ -295,76 +724,395 @@ fn get_pending_requests() -> Vec<PendingRequest> {
     })
 }
 
+// Approved requests still inside their time-lock, so Emergency holders can review and cancel them
+#[query]
+fn get_timelocked_requests() -> Vec<PendingRequest> {
+    STATE.with(|state| {
+        state.borrow().threshold_signer.get_timelocked_requests()
+    })
+}
+
 #[update]
-async fn sign_request(request_id: u64) -> Result<PendingRequest, String> {
+async fn sign_request(request_id: u64) -> ChainGuardResult<PendingRequest> {
     let caller = ic_cdk::caller();
     let current_time = time();
 
     // Sign the request and check if approved
-    let (request_opt, action_opt) = STATE.with(|state| {
+    let (request, execute_now) = STATE.with(|state| {
         let mut state = state.borrow_mut();
 
         // Check permission
-        if !state.access_control.has_permission(&caller, &Permission::Sign) {
-            return (None, None);
+        if !state.access_control.has_permission(&caller, &Permission::Sign, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Sign });
         }
 
-        match state.threshold_signer.sign_request(request_id, caller, current_time) {
-            Ok(request) => {
-                if request.status == RequestStatus::Approved {
-                    // Extract action for execution
-                    (Some(request.clone()), Some(request.action.clone()))
-                } else {
-                    (Some(request), None)
+        // Expire overdue requests first so their volume is released and logged
+        expire_requests(&mut state, current_time);
+
+        let signer_roles = state.access_control.get_roles(&caller, current_time);
+        let request = state.threshold_signer.sign_request(request_id, caller, &signer_roles, current_time)?;
+        write_through(stable_memory::store_threshold_request(&request));
+        log_event(&mut state, caller, AuditEvent::RequestSigned {
+            request_id,
+            signatures: request.collected_signatures.len() as u64,
+            required: request.required_signatures,
+            status: request.status.clone(),
+            current_weight: Some(request.current_weight),
+            required_weight: Some(request.required_weight),
+            executable_at: request.executable_at,
+        });
+        // Time-locked approvals wait for their window (timer or execute_request)
+        let execute_now = request.status == RequestStatus::Approved && request.executable_at.is_none();
+        Ok((request, execute_now))
+    })?;
+
+    // If approved, execute the action
+    if execute_now {
+        execute_threshold_request(&request, caller).await;
+    }
+
+    Ok(request)
+}
+
+// Run an approved request's action and settle its volume, cooldown and audit entry
+async fn execute_threshold_request(request: &PendingRequest, caller: Principal) -> ExecutionResult {
+    // Clone executor to avoid borrow issues across await
+    let executor = STATE.with(|state| {
+        state.borrow().executor.clone()
+    });
+
+    // Execute action using ChainExecutor
+    let execution_result = executor.execute_action(&request.action).await;
+
+    // Settle the request and update audit log; a failed time-locked execution stays
+    // Approved (keeping its reserved volume) so it can be retried or cancelled
+    let retry = !execution_result.success && request.executable_at.is_some();
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if retry {
+            let _ = state.threshold_signer.mark_failed(request.id, time());
+        } else {
+            let _ = state.threshold_signer.mark_executed(request.id);
+        }
+        persist_request(&state, request.id);
+
+        // Find and update the corresponding audit entry
+        // (audit entry was created when threshold request was made)
+        if let Some(audit_entry) = state.audit_log.find_by_threshold_request(request.id) {
+            // Count executed volume; settled failures free their reservation
+            if execution_result.success {
+                state.spend_ledger.commit(audit_entry.id, time());
+                persist_spend_ledger(&state);
+            } else if !retry {
+                release_spend(&mut state, audit_entry.id);
+            }
+
+            // Track last execution for Cooldown conditions
+            if execution_result.success {
+                if let Some(policy_id) = audit_entry.action().and_then(|a| a.policy_result.matched_policy_id) {
+                    let record = state.access_control.record_execution(request.requester, policy_id, &request.action, time());
+                    write_through(stable_memory::store_execution_record(&record));
                 }
             }
-            Err(_) => (None, None),
+
+            if state.audit_log.log_execution(audit_entry.id, caller, execution_result.clone(), time()).is_ok() {
+                certify_audit_tip(&state);
+            }
         }
     });
 
-    let request = request_opt.ok_or("Failed to sign request or no permission".to_string())?;
+    execution_result
+}
 
-    // If approved, execute the action
-    if let Some(action) = action_opt {
-        // Clone executor to avoid borrow issues across await
-        let executor = STATE.with(|state| {
-            state.borrow().executor.clone()
-        });
+// Execute a time-locked request once its window has passed
+#[update]
+async fn execute_request(request_id: u64) -> ChainGuardResult<ExecutionResult> {
+    let caller = ic_cdk::caller();
 
-        // Execute action using ChainExecutor
-        let execution_result = executor.execute_action(&action).await;
+    let has_permission = STATE.with(|state| {
+        state.borrow().access_control.has_permission(&caller, &Permission::Sign, time())
+    });
+    if !has_permission {
+        return Err(ChainGuardError::InsufficientPermissions { required: Permission::Sign });
+    }
 
-        // Mark as executed and update audit log
-        STATE.with(|state| {
-            let mut state = state.borrow_mut();
-            let _ = state.threshold_signer.mark_executed(request_id);
-
-            // Find and update the corresponding audit entry
-            // (audit entry was created when threshold request was made)
-            if let Some(audit_entry) = state.audit_log.get_entries(None, None)
-                .iter()
-                .find(|e| e.threshold_request_id == Some(request_id))
-            {
-                let _ = state.audit_log.update_execution_result(audit_entry.id, execution_result);
-            }
+    execute_timelocked_request(request_id, caller, time()).await
+}
+
+// Emergency holders can cancel a time-locked request before it executes
+#[update]
+fn cancel_request(request_id: u64, reason: String) -> ChainGuardResult<()> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+
+        if !state.access_control.has_permission(&caller, &Permission::Emergency, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Emergency });
+        }
+
+        state.threshold_signer.cancel_request(request_id)?;
+        persist_request(&state, request_id);
+        release_request_volume(&mut state, request_id);
+        log_event(&mut state, caller, AuditEvent::RequestCancelled { request_id, reason });
+        Ok(())
+    })
+}
+
+#[update]
+fn reject_request(request_id: u64, reason: String) -> ChainGuardResult<()> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+
+        if !state.access_control.has_permission(&caller, &Permission::Sign, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Sign });
+        }
+
+        let current_time = time();
+        expire_requests(&mut state, current_time);
+
+        let signer_roles = state.access_control.get_roles(&caller, current_time);
+        let request = state.threshold_signer.reject_request(request_id, caller, &signer_roles, reason.clone(), current_time)?;
+        persist_request(&state, request_id);
+
+        // Only a rejection that reaches the quorum (or a veto) frees the volume
+        if request.status == RequestStatus::Rejected {
+            release_request_volume(&mut state, request_id);
+        }
+        log_event(&mut state, caller, AuditEvent::RequestRejected {
+            request_id,
+            reason,
+            rejections: Some(request.rejections.len() as u64),
+            required_rejections: Some(request.required_rejections),
+            veto: request.rejections.last().map(|r| r.veto),
+            status: Some(request.status),
         });
+        Ok(())
+    })
+}
+
+// Set (or clear with None) the vote weight of a principal or role
+#[update]
+fn set_signer_weight(target: WeightTarget, weight: Option<u64>) -> ChainGuardResult<()> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+
+        if !state.access_control.has_permission(&caller, &Permission::Configure, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Configure });
+        }
+
+        let before = state.threshold_signer.set_signer_weight(target.clone(), weight)?;
+        write_through(stable_memory::store_signer_weights(&state.threshold_signer.list_signer_weights()));
+        log_event(&mut state, caller, AuditEvent::SignerWeightChanged { target, before, after: weight });
+        Ok(())
+    })
+}
+
+#[query]
+fn list_signer_weights() -> Vec<SignerWeight> {
+    STATE.with(|state| {
+        state.borrow().threshold_signer.list_signer_weights()
+    })
+}
+
+// Rejections needed to reject a request, and principals whose rejection alone does
+#[update]
+fn set_rejection_config(config: RejectionConfig) -> ChainGuardResult<()> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+
+        if !state.access_control.has_permission(&caller, &Permission::Configure, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Configure });
+        }
+
+        let before = state.threshold_signer.set_rejection_config(config.clone())?;
+        write_through(stable_memory::store_rejection_config(&config));
+        log_event(&mut state, caller, AuditEvent::RejectionConfigChanged { before, after: config });
+        Ok(())
+    })
+}
+
+#[query]
+fn get_rejection_config() -> RejectionConfig {
+    STATE.with(|state| {
+        state.borrow().threshold_signer.rejection_config().clone()
+    })
+}
+
+// Separation of duties: whether requesters may sign their own requests, and how
+// many of one requester's requests a signer may approve per day
+#[update]
+fn set_signing_rules(rules: SigningRules) -> ChainGuardResult<()> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+
+        if !state.access_control.has_permission(&caller, &Permission::Configure, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Configure });
+        }
+
+        let before = state.threshold_signer.set_signing_rules(rules.clone())?;
+        write_through(stable_memory::store_signing_rules(&rules));
+        log_event(&mut state, caller, AuditEvent::SigningRulesChanged { before, after: rules });
+        Ok(())
+    })
+}
+
+#[query]
+fn get_signing_rules() -> SigningRules {
+    STATE.with(|state| {
+        state.borrow().threshold_signer.signing_rules().clone()
+    })
+}
+
+// Claim a time-locked request past its window and execute it
+async fn execute_timelocked_request(request_id: u64, caller: Principal, current_time: u64) -> ChainGuardResult<ExecutionResult> {
+    let request = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.paused {
+            return Err(ChainGuardError::SystemPaused);
+        }
+        state.threshold_signer.start_execution(request_id, current_time)
+    })?;
+    Ok(execute_threshold_request(&request, caller).await)
+}
+
+// Expire overdue requests: free their reserved volume and record each expiry
+fn expire_requests(state: &mut ChainGuardState, current_time: u64) {
+    for request_id in state.threshold_signer.cleanup_expired(current_time) {
+        persist_request(state, request_id);
+        release_request_volume(state, request_id);
+        log_event(state, ic_cdk::id(), AuditEvent::RequestExpired { request_id });
     }
+}
 
-    Ok(request)
+// Release the daily volume reserved by a threshold request
+fn release_request_volume(state: &mut ChainGuardState, request_id: u64) {
+    if let Some(audit_id) = state.audit_log.find_by_threshold_request(request_id).map(|e| e.id) {
+        release_spend(state, audit_id);
+    }
+}
+
+// Append a governance, signer or emergency event to the audit log
+fn log_event(state: &mut ChainGuardState, caller: Principal, event: AuditEvent) -> u64 {
+    let id = state.audit_log.log_event(caller, event, time());
+    certify_audit_tip(state);
+    id
+}
+
+// Publish the audit log's tip hash as the canister's certified data
+fn certify_audit_tip(state: &ChainGuardState) {
+    ic_cdk::api::set_certified_data(state.audit_log.tip_hash());
+}
+
+// Deduct a reserved action from the requester's agent session budget, if any
+fn charge_session(state: &mut ChainGuardState, requester: Principal, audit_id: u64) {
+    let amount = state.spend_ledger.get_reservation(audit_id).map(|e| e.amount);
+    if let Some(amount) = amount {
+        state.sessions.charge(&requester, amount);
+        persist_session(state, &requester);
+    }
+}
+
+// Drop a volume reservation and give its amount back to the agent session budget
+fn release_spend(state: &mut ChainGuardState, audit_id: u64) {
+    if let Some(entry) = state.spend_ledger.release(audit_id) {
+        persist_spend_ledger(state);
+        state.sessions.refund(&entry.requester, entry.amount);
+        persist_session(state, &entry.requester);
+    }
+}
+
+#[query]
+fn get_spend_summary(requester: Principal, token: String, chain: String) -> ChainGuardResult<SpendSummary> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let state = state.borrow();
+
+        if caller != requester && !state.access_control.has_permission(&caller, &Permission::ViewLogs, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::ViewLogs });
+        }
+
+        Ok(state.spend_ledger.summary(&requester, &token, &chain, time()))
+    })
+}
+
+// ============== AGENT SESSIONS ==============
+
+#[update]
+fn create_session(config: SessionConfig) -> ChainGuardResult<AgentSession> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+
+        if !state.access_control.has_role(&caller, &Role::Owner, time()) {
+            return Err(ChainGuardError::Unauthorized);
+        }
+
+        let before = state.sessions.get(&config.principal).cloned();
+        let after = state.sessions.create(config, caller, time())?;
+        write_through(stable_memory::store_session(&after));
+        log_event(&mut state, caller, AuditEvent::SessionCreated { before, after: after.clone() });
+        Ok(after)
+    })
 }
 
+// Emergency holders can revoke; effective on the agent's next request
 #[update]
-fn reject_request(request_id: u64, reason: String) -> Result<(), String> {
+fn revoke_session(principal: Principal) -> ChainGuardResult<()> {
     let caller = ic_cdk::caller();
 
     STATE.with(|state| {
         let mut state = state.borrow_mut();
 
-        if !state.access_control.has_permission(&caller, &Permission::Sign) {
-            return Err("No sign permission".to_string());
+        if !state.access_control.has_permission(&caller, &Permission::Emergency, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Emergency });
+        }
+
+        let before = state.sessions.get(&principal).cloned().ok_or(ChainGuardError::SessionNotFound)?;
+        state.sessions.revoke(&principal)?;
+        persist_session(&state, &principal);
+        log_event(&mut state, caller, AuditEvent::SessionRevoked { before });
+        Ok(())
+    })
+}
+
+#[query]
+fn get_session(principal: Principal) -> ChainGuardResult<Option<AgentSession>> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let state = state.borrow();
+
+        if caller != principal && !state.access_control.has_permission(&caller, &Permission::ViewLogs, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::ViewLogs });
+        }
+
+        Ok(state.sessions.get(&principal).cloned())
+    })
+}
+
+#[query]
+fn list_sessions() -> ChainGuardResult<Vec<AgentSession>> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let state = state.borrow();
+
+        if !state.access_control.has_permission(&caller, &Permission::ViewLogs, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::ViewLogs });
         }
 
-        state.threshold_signer.reject_request(request_id, reason)
+        Ok(state.sessions.list())
     })
 }
 
@@ -377,7 +1125,7 @@ fn get_audit_logs(start: Option<u64>, end: Option<u64>) -> Vec<AuditEntry> {
     STATE.with(|state| {
         let state = state.borrow();
 
-        if !state.access_control.has_permission(&caller, &Permission::ViewLogs) {
+        if !state.access_control.has_permission(&caller, &Permission::ViewLogs, time()) {
             return vec![];
         }
 
@@ -392,44 +1140,88 @@ fn get_audit_entry(id: u64) -> Option<AuditEntry> {
     STATE.with(|state| {
         let state = state.borrow();
 
-        if !state.access_control.has_permission(&caller, &Permission::ViewLogs) {
+        if !state.access_control.has_permission(&caller, &Permission::ViewLogs, time()) {
             return None;
         }
 
-        state.audit_log.get_entry(id).cloned()
+        state.audit_log.get_entry(id)
+    })
+}
+
+// Filtered page of audit entries, newest first; pass next_cursor to continue
+#[query]
+fn get_audit_page(query: AuditQuery) -> ChainGuardResult<AuditPage> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let state = state.borrow();
+
+        if !state.access_control.has_permission(&caller, &Permission::ViewLogs, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::ViewLogs });
+        }
+
+        Ok(state.audit_log.query(&query, MAX_AUDIT_RANGE))
+    })
+}
+
+// Audit entries from start_id with the certificate over the log tip. A verifier
+// checks the certificate against tip_hash, then the hash links of the entries
+// up to tip_id (fetching further ranges if the range stops short of the tip).
+#[query]
+fn get_certified_audit_range(start_id: u64, limit: u64) -> ChainGuardResult<CertifiedAuditRange> {
+    let caller = ic_cdk::caller();
+
+    STATE.with(|state| {
+        let state = state.borrow();
+
+        if !state.access_control.has_permission(&caller, &Permission::ViewLogs, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::ViewLogs });
+        }
+
+        let (entries, tip_id) = state.audit_log.get_range(start_id, limit.min(MAX_AUDIT_RANGE));
+        Ok(CertifiedAuditRange {
+            entries,
+            tip_id,
+            tip_hash: state.audit_log.tip_hash().to_vec(),
+            certificate: ic_cdk::api::data_certificate(),
+        })
     })
 }
 
 // ============== EMERGENCY ==============
 
 #[update]
-fn pause() -> Result<(), String> {
+fn pause() -> ChainGuardResult<()> {
     let caller = ic_cdk::caller();
 
     STATE.with(|state| {
         let mut state = state.borrow_mut();
 
-        if !state.access_control.has_permission(&caller, &Permission::Emergency) {
-            return Err("No emergency permission".to_string());
+        if !state.access_control.has_permission(&caller, &Permission::Emergency, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Emergency });
         }
 
         state.paused = true;
+        write_through(stable_memory::store_paused(true));
+        log_event(&mut state, caller, AuditEvent::Paused);
         Ok(())
     })
 }
 
 #[update]
-fn resume() -> Result<(), String> {
+fn resume() -> ChainGuardResult<()> {
     let caller = ic_cdk::caller();
 
     STATE.with(|state| {
         let mut state = state.borrow_mut();
 
-        if !state.access_control.has_permission(&caller, &Permission::Emergency) {
-            return Err("No emergency permission".to_string());
+        if !state.access_control.has_permission(&caller, &Permission::Emergency, time()) {
+            return Err(ChainGuardError::InsufficientPermissions { required: Permission::Emergency });
         }
 
         state.paused = false;
+        write_through(stable_memory::store_paused(false));
+        log_event(&mut state, caller, AuditEvent::Resumed);
         Ok(())
     })
 }
@@ -447,7 +1239,7 @@ fn get_config() -> Option<ChainGuardConfig> {
 }
 
 #[update]
-async fn get_eth_address() -> Result<String, String> {
+async fn get_eth_address() -> ChainGuardResult<String> {
     use crate::evm_rpc::EvmRpcExecutor;
 
     let (key_name, derivation_path) = STATE.with(|state| {
@@ -455,101 +1247,163 @@ async fn get_eth_address() -> Result<String, String> {
         (s.executor.key_name.clone(), s.executor.derivation_path.clone())
     });
 
-    let evm_executor = EvmRpcExecutor::new(key_name, derivation_path)?;
+    let evm_executor = EvmRpcExecutor::new(key_name, derivation_path)
+        .map_err(|msg| ChainGuardError::InternalError { msg })?;
     evm_executor.get_eth_address().await
+        .map_err(|reason| ChainGuardError::ExecutionFailed { reason })
 }
 
-// ============== UPGRADE HOOKS ==============
+// ============== PERSISTENCE ==============
+// Mutations write what they changed straight to stable memory, so an
+// upgrade only has to read it back (there is no pre_upgrade snapshot)
 
-#[pre_upgrade]
-fn pre_upgrade() {
-    ic_cdk::println!("Starting pre_upgrade hook...");
+// Stable memory is the only copy that survives an upgrade: trap on a failed
+// write so the whole update call rolls back instead of diverging from the heap
+fn write_through(result: Result<(), String>) {
+    if let Err(e) = result {
+        ic_cdk::trap(&format!("Failed to write stable memory: {}", e));
+    }
+}
 
-    // Extract complete state from heap memory
-    STATE.with(|state| {
-        let state = state.borrow();
+// Write a principal's grants; a principal left without grants loses its key
+fn persist_roles(state: &ChainGuardState, principal: &Principal) {
+    write_through(stable_memory::store_role(principal, &state.access_control.get_role_grants(principal)));
+}
 
-        // Store config
-        if let Some(config) = &state.config {
-            let _ = stable_memory::store_config(config);
-        }
+fn persist_ownership_proposal(state: &ChainGuardState) {
+    write_through(stable_memory::store_ownership_proposal(state.access_control.ownership_proposal()));
+}
 
-        // Store all roles
-        let roles = state.access_control.list_role_assignments();
-        let role_map: HashMap<Principal, Vec<Role>> = roles.iter()
-            .fold(HashMap::new(), |mut acc, (principal, role)| {
-                acc.entry(*principal)
-                    .or_insert_with(Vec::new)
-                    .push(role.clone());
-                acc
-            });
+// Custom roles and policies are small sets, rewritten whole so deleted entries disappear
+fn persist_custom_roles(state: &ChainGuardState) {
+    write_through(stable_memory::store_custom_roles(&state.access_control.list_custom_roles()));
+}
 
-        for (principal, roles) in role_map.iter() {
-            let _ = stable_memory::store_role(principal, roles);
-        }
+fn persist_policies(state: &ChainGuardState) {
+    write_through(stable_memory::store_policies(
+        &state.access_control.get_policies(),
+        state.access_control.next_policy_id(),
+    ));
+}
 
-        // Store all policies
-        let policies = state.access_control.get_policies();
-        for (index, policy) in policies.iter().enumerate() {
-            let _ = stable_memory::store_policy(index as u64, policy);
-        }
+// Requests are kept in every status so their history survives upgrades
+fn persist_request(state: &ChainGuardState, request_id: u64) {
+    if let Some(request) = state.threshold_signer.get_request(request_id) {
+        write_through(stable_memory::store_threshold_request(request));
+    }
+}
 
-        // Store all pending requests
-        let pending_requests = state.threshold_signer.get_pending_requests();
-        for request in pending_requests {
-            let _ = stable_memory::store_pending_request(&request);
-        }
+fn persist_spend_ledger(state: &ChainGuardState) {
+    write_through(stable_memory::store_spend_ledger(
+        &state.spend_ledger.get_entries(),
+        &state.spend_ledger.get_reservations(),
+    ));
+}
 
-        // Store all audit entries
-        let audit_entries = state.audit_log.get_entries(None, None);
-        for entry in audit_entries {
-            let _ = stable_memory::store_audit_entry(&entry);
-        }
+// Revoked sessions are kept so they stay revoked
+fn persist_session(state: &ChainGuardState, principal: &Principal) {
+    if let Some(session) = state.sessions.get(principal) {
+        write_through(stable_memory::store_session(session));
+    }
+}
 
-        ic_cdk::println!("Pre_upgrade complete. State serialized to stable memory.");
-    });
+fn persist_executor_config(state: &ChainGuardState) {
+    write_through(stable_memory::store_executor_config(&stable_memory::ExecutorConfig {
+        key_name: state.executor.key_name.clone(),
+        derivation_path: state.executor.derivation_path.clone(),
+    }));
 }
 
+// ============== UPGRADE HOOKS ==============
+
 #[post_upgrade]
 fn post_upgrade() {
     ic_cdk::println!("Starting post_upgrade hook...");
 
-    STATE.with(|state| {
-        let mut state = state.borrow_mut();
+    // Bring stable memory to the current layout before anything reads it;
+    // trapping rolls the upgrade back instead of dropping records
+    match migrations::run() {
+        Ok(from) if from < migrations::SCHEMA_VERSION => ic_cdk::println!(
+            "Migrated stable memory from schema version {} to {}",
+            from,
+            migrations::SCHEMA_VERSION
+        ),
+        Ok(_) => {}
+        Err(e) => ic_cdk::trap(&e),
+    }
 
-        // Restore config
-        if let Some(config) = stable_memory::load_config() {
-            state.config = Some(config);
-        }
+    if let Err(e) = STATE.with(|state| restore_state(&mut state.borrow_mut())) {
+        ic_cdk::trap(&format!("Failed to restore state: {}", e));
+    }
 
-        // Restore roles
-        let roles = stable_memory::load_all_roles();
-        for (principal, role_list) in roles {
-            for role in role_list {
-                state.access_control.assign_role(principal, role);
-            }
-        }
+    // Timers do not survive upgrades
+    start_role_expiry_timer();
+    start_request_expiry_timer();
+    start_timelock_timer();
+}
 
-        // Restore policies
-        let policies = stable_memory::load_all_policies();
-        for policy in policies {
-            state.access_control.add_policy(policy);
-        }
+// Read every component back from stable memory
+fn restore_state(state: &mut ChainGuardState) -> Result<(), String> {
+    // Restore config, pause flag and signing key
+    state.config = stable_memory::load_config()?;
+    state.paused = stable_memory::load_paused()?;
+    if let Some(executor_config) = stable_memory::load_executor_config()? {
+        state.executor.key_name = executor_config.key_name;
+        state.executor.derivation_path = executor_config.derivation_path;
+    }
+    // Canisters that never stored one keep the default key from now on
+    persist_executor_config(state);
 
-        // Restore pending requests
-        let pending_requests = stable_memory::load_all_pending_requests();
-        for request in pending_requests {
-            let _ = state.threshold_signer.restore_request(request);
-        }
+    // Restore custom role definitions (before assignments that reference them)
+    for (name, permissions) in stable_memory::load_custom_roles()? {
+        state.access_control.restore_role_definition(name, permissions);
+    }
 
-        // Restore audit entries
-        let audit_entries = stable_memory::load_all_audit_entries();
-        for entry in audit_entries {
-            let _ = state.audit_log.restore_entry(entry);
-        }
+    // Restore role grants
+    for grant in stable_memory::load_all_roles()? {
+        state.access_control.assign_role(grant);
+    }
 
-        ic_cdk::println!("Post_upgrade complete. State restored from stable memory.");
-    });
+    // Restore pending ownership transfer
+    state.access_control.restore_ownership_proposal(stable_memory::load_ownership_proposal()?);
+
+    // Restore policies
+    let policies = stable_memory::load_all_policies()?;
+    let next_policy_id = stable_memory::load_next_policy_id()?;
+    state.access_control.restore_policies(policies, next_policy_id);
+
+    // Restore signer weights, rejection rules and threshold requests in every status
+    state.threshold_signer.restore_signer_weights(stable_memory::load_signer_weights()?);
+    state.threshold_signer.restore_rejection_config(stable_memory::load_rejection_config()?);
+    state.threshold_signer.restore_signing_rules(stable_memory::load_signing_rules()?);
+    for request in stable_memory::load_all_threshold_requests()? {
+        state.threshold_signer.restore_request(request)?;
+    }
+
+    // Audit entries are read from stable memory in place; republish the tip
+    certify_audit_tip(state);
+
+    // Restore last execution records
+    for record in stable_memory::load_all_execution_records()? {
+        state.access_control.restore_execution(record);
+    }
+
+    // Restore spend ledger
+    let (spend_entries, spend_reservations) = stable_memory::load_spend_ledger()?;
+    state.spend_ledger.restore(spend_entries, spend_reservations);
+
+    // Restore policy version history
+    for version in stable_memory::load_all_policy_versions()? {
+        state.policy_history.restore_version(version);
+    }
+
+    // Restore agent sessions
+    for session in stable_memory::load_all_sessions()? {
+        state.sessions.restore_session(session);
+    }
+
+    ic_cdk::println!("Post_upgrade complete. State restored from stable memory.");
+    Ok(())
 }
 
 // Export Candid interface
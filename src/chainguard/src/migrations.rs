@@ -0,0 +1,135 @@
+use crate::audit::AuditLog;
+use crate::stable_memory;
+
+// Layout version of the stable structures written by this build. Bump it
+// together with a new entry in `MIGRATIONS` whenever a stored type changes
+// shape, so records are rewritten instead of failing to decode.
+pub const SCHEMA_VERSION: u32 = 4;
+
+type Migration = fn() -> Result<(), String>;
+
+// (version it migrates to, description, migration); run in order from the stored version
+const MIGRATIONS: &[(u32, &str, Migration)] = &[
+    (1, "rewrite legacy role grants and chain and index the audit log", migrate_to_v1),
+    (2, "add signature weights to threshold requests", stable_memory::migrate_unweighted_requests),
+    (3, "add rejection records to threshold requests", stable_memory::migrate_requests_without_rejections),
+    (4, "count audit index entries", rebuild_audit_log),
+];
+
+// Bring stable memory up to SCHEMA_VERSION; returns the version it started from.
+// Canisters written before versioning start at 0.
+pub fn run() -> Result<u32, String> {
+    let stored = stable_memory::load_schema_version()?.unwrap_or(0);
+    if stored > SCHEMA_VERSION {
+        return Err(format!(
+            "Stable memory schema version {} is newer than this build ({})",
+            stored, SCHEMA_VERSION
+        ));
+    }
+
+    for (version, description, migrate) in MIGRATIONS {
+        if *version <= stored {
+            continue;
+        }
+        migrate().map_err(|e| format!("Migration to schema version {} ({}) failed: {}", version, description, e))?;
+        stable_memory::store_schema_version(*version)?;
+    }
+    Ok(stored)
+}
+
+// Mark fresh stable memory as written in the current layout
+pub fn init() -> Result<(), String> {
+    stable_memory::store_schema_version(SCHEMA_VERSION)
+}
+
+// Before versioning, role grants could be a bare Vec<Role> and audit entries
+// could be unchained or unindexed
+fn migrate_to_v1() -> Result<(), String> {
+    stable_memory::migrate_legacy_role_grants()?;
+    rebuild_audit_log()
+}
+
+// Re-chain and re-index the audit log from its entries
+fn rebuild_audit_log() -> Result<(), String> {
+    AuditLog::init(stable_memory::audit_memory(), stable_memory::audit_index_memory()).rebuild()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+    use candid::{CandidType, Principal};
+
+    // Threshold request layout of the first release (no signer roles, weights or rejections)
+    #[derive(CandidType)]
+    struct BaselineSignature {
+        signer: Principal,
+        signed_at: u64,
+    }
+
+    #[derive(CandidType)]
+    struct BaselineRequest {
+        id: u64,
+        action: Action,
+        requester: Principal,
+        created_at: u64,
+        expires_at: u64,
+        required_signatures: u8,
+        collected_signatures: Vec<BaselineSignature>,
+        status: RequestStatus,
+    }
+
+    #[test]
+    fn test_run_from_unversioned() {
+        assert_eq!(run(), Ok(0));
+        assert_eq!(stable_memory::load_schema_version(), Ok(Some(SCHEMA_VERSION)));
+
+        // Already current: nothing to do
+        assert_eq!(run(), Ok(SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn test_refuse_newer_schema() {
+        stable_memory::store_schema_version(SCHEMA_VERSION + 1).unwrap();
+        assert!(run().is_err());
+        assert_eq!(stable_memory::load_schema_version(), Ok(Some(SCHEMA_VERSION + 1)));
+    }
+
+    #[test]
+    fn test_registry_is_ordered() {
+        let versions: Vec<u32> = MIGRATIONS.iter().map(|(version, _, _)| *version).collect();
+        assert_eq!(versions, (1..=SCHEMA_VERSION).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_migrate_baseline_threshold_request() {
+        let signer = Principal::from_slice(&[2; 29]);
+        let request = BaselineRequest {
+            id: 4,
+            action: Action::Transfer {
+                chain: "ethereum".to_string(),
+                token: "USDC".to_string(),
+                to: "0x123".to_string(),
+                amount: 1000,
+            },
+            requester: Principal::from_slice(&[1; 29]),
+            created_at: 1000,
+            expires_at: 2000,
+            required_signatures: 2,
+            collected_signatures: vec![BaselineSignature { signer, signed_at: 1500 }],
+            status: RequestStatus::Pending,
+        };
+        stable_memory::store_raw_threshold_request(4, candid::encode_one(&request).unwrap());
+
+        assert_eq!(run(), Ok(0));
+        let migrated = stable_memory::load_all_threshold_requests().unwrap();
+        assert_eq!(migrated.len(), 1);
+        assert_eq!(migrated[0].id, 4);
+        assert!(migrated[0].from_roles.is_empty());
+        assert_eq!(migrated[0].collected_signatures[0].signer, signer);
+        assert_eq!(migrated[0].collected_signatures[0].weight, 1);
+        assert_eq!((migrated[0].current_weight, migrated[0].required_weight), (1, 2));
+        assert!(migrated[0].rejections.is_empty());
+        assert_eq!(migrated[0].required_rejections, 1);
+    }
+}
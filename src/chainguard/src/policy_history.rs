@@ -0,0 +1,205 @@
+use crate::errors::{ChainGuardError, ChainGuardResult};
+use crate::types::*;
+use candid::Principal;
+
+// Append-only history of the policy set. Every add, update, remove or
+// rollback produces a new immutable version holding a full snapshot.
+pub struct PolicyHistory {
+    versions: Vec<PolicyVersion>,
+}
+
+impl PolicyHistory {
+    pub fn new() -> Self {
+        Self {
+            versions: Vec::new(),
+        }
+    }
+
+    // Record the policy set after a change; returns the new version number
+    pub fn record(
+        &mut self,
+        change: PolicyChange,
+        policies: Vec<Policy>,
+        author: Principal,
+        current_time: u64,
+    ) -> u64 {
+        let previous_version = self.versions.last().map(|v| v.version);
+        let version = previous_version.map_or(1, |v| v + 1);
+
+        self.versions.push(PolicyVersion {
+            version,
+            previous_version,
+            author,
+            timestamp: current_time,
+            change,
+            policies,
+        });
+        version
+    }
+
+    // Version currently in force (0 = no policy change recorded yet)
+    pub fn current_version(&self) -> u64 {
+        self.versions.last().map_or(0, |v| v.version)
+    }
+
+    pub fn get_version(&self, version: u64) -> Option<&PolicyVersion> {
+        self.versions.iter().find(|v| v.version == version)
+    }
+
+    pub fn list_versions(&self) -> Vec<PolicyVersionSummary> {
+        self.versions
+            .iter()
+            .map(|v| PolicyVersionSummary {
+                version: v.version,
+                previous_version: v.previous_version,
+                author: v.author,
+                timestamp: v.timestamp,
+                change: v.change.clone(),
+                policy_count: v.policies.len() as u64,
+            })
+            .collect()
+    }
+
+    // Policies added, removed or modified going from one version to another (matched by ID)
+    pub fn diff(&self, from_version: u64, to_version: u64) -> ChainGuardResult<PolicyDiff> {
+        let from = self.get_version(from_version)
+            .ok_or(ChainGuardError::PolicyVersionNotFound { version: from_version })?;
+        let to = self.get_version(to_version)
+            .ok_or(ChainGuardError::PolicyVersionNotFound { version: to_version })?;
+
+        let find = |set: &[Policy], id: Option<u64>| set.iter().find(|p| p.id == id).cloned();
+
+        let added = to.policies
+            .iter()
+            .filter(|p| find(&from.policies, p.id).is_none())
+            .cloned()
+            .collect();
+        let removed = from.policies
+            .iter()
+            .filter(|p| find(&to.policies, p.id).is_none())
+            .cloned()
+            .collect();
+        let modified = from.policies
+            .iter()
+            .filter_map(|before| {
+                let after = find(&to.policies, before.id)?;
+                if *before == after {
+                    return None;
+                }
+                Some(PolicyModification {
+                    id: before.id.unwrap_or_default(),
+                    before: before.clone(),
+                    after,
+                })
+            })
+            .collect();
+
+        Ok(PolicyDiff {
+            from_version,
+            to_version,
+            added,
+            removed,
+            modified,
+        })
+    }
+
+    #[cfg(test)]
+    pub fn get_versions(&self) -> &[PolicyVersion] {
+        &self.versions
+    }
+
+    // Restore version from stable memory (for post_upgrade)
+    pub fn restore_version(&mut self, version: PolicyVersion) {
+        self.versions.push(version);
+        self.versions.sort_by_key(|v| v.version);
+    }
+}
+
+impl Default for PolicyHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_principal(id: u8) -> Principal {
+        let mut bytes = [0u8; 29];
+        bytes[0] = id;
+        Principal::from_slice(&bytes)
+    }
+
+    fn mock_policy(id: u64, max: u64) -> Policy {
+        Policy {
+            id: Some(id),
+            name: format!("Policy {}", id),
+            conditions: vec![Condition::MaxAmount(max)],
+            action: PolicyAction::Allow,
+            priority: 1,
+            utc_offset_minutes: None,
+        }
+    }
+
+    #[test]
+    fn test_record_versions() {
+        let mut history = PolicyHistory::new();
+        let admin = mock_principal(1);
+        assert_eq!(history.current_version(), 0);
+
+        let v1 = history.record(PolicyChange::Added(0), vec![mock_policy(0, 100)], admin, 1000);
+        let v2 = history.record(PolicyChange::Removed(0), vec![], admin, 2000);
+        assert_eq!((v1, v2), (1, 2));
+        assert_eq!(history.current_version(), 2);
+
+        let version = history.get_version(2).unwrap();
+        assert_eq!(version.previous_version, Some(1));
+        assert_eq!(version.author, admin);
+        assert_eq!(version.timestamp, 2000);
+
+        let summaries = history.list_versions();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].policy_count, 1);
+        assert_eq!(summaries[0].previous_version, None);
+        assert_eq!(summaries[1].change, PolicyChange::Removed(0));
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut history = PolicyHistory::new();
+        let admin = mock_principal(1);
+
+        history.record(PolicyChange::Initialized, vec![mock_policy(0, 100), mock_policy(1, 200)], admin, 1000);
+        history.record(PolicyChange::Updated(1), vec![mock_policy(0, 100), mock_policy(1, 500)], admin, 2000);
+        history.record(PolicyChange::Added(2), vec![mock_policy(1, 500), mock_policy(2, 50)], admin, 3000);
+
+        let diff = history.diff(1, 3).unwrap();
+        assert_eq!(diff.added, vec![mock_policy(2, 50)]);
+        assert_eq!(diff.removed, vec![mock_policy(0, 100)]);
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].id, 1);
+        assert_eq!(diff.modified[0].before, mock_policy(1, 200));
+        assert_eq!(diff.modified[0].after, mock_policy(1, 500));
+
+        let unchanged = history.diff(2, 2).unwrap();
+        assert!(unchanged.added.is_empty() && unchanged.removed.is_empty() && unchanged.modified.is_empty());
+
+        assert_eq!(history.diff(1, 9).unwrap_err(), ChainGuardError::PolicyVersionNotFound { version: 9 });
+    }
+
+    #[test]
+    fn test_restore_version() {
+        let mut history = PolicyHistory::new();
+        let admin = mock_principal(1);
+        history.record(PolicyChange::Added(0), vec![mock_policy(0, 100)], admin, 1000);
+        history.record(PolicyChange::Added(1), vec![mock_policy(0, 100), mock_policy(1, 200)], admin, 2000);
+
+        let mut restored = PolicyHistory::new();
+        for version in history.get_versions().iter().rev() {
+            restored.restore_version(version.clone());
+        }
+        assert_eq!(restored.current_version(), 2);
+        assert_eq!(restored.record(PolicyChange::Removed(0), vec![], admin, 3000), 3);
+    }
+}
@@ -0,0 +1,341 @@
+use crate::errors::{ChainGuardError, ChainGuardResult};
+use crate::types::*;
+use candid::Principal;
+use std::collections::HashMap;
+
+const ACTION_TYPES: [&str; 3] = ["swap", "transfer", "approve"];
+
+// Scoped agent sessions. A principal with a session may execute actions
+// within its expiry, budget, action-type and chain limits, on top of the
+// global policies. Budgets are counted in a single token, so amounts of
+// different tokens are never added up. A session record scopes its
+// principal until re-minted.
+pub struct SessionManager {
+    sessions: HashMap<Principal, AgentSession>,
+}
+
+impl SessionManager {
+    pub fn new() -> Self {
+        Self {
+            sessions: HashMap::new(),
+        }
+    }
+
+    // Mint a session; replaces any previous session of the same principal
+    pub fn create(
+        &mut self,
+        config: SessionConfig,
+        created_by: Principal,
+        current_time: u64,
+    ) -> ChainGuardResult<AgentSession> {
+        if config.expires_at <= current_time {
+            return Err(ChainGuardError::InvalidConfiguration {
+                reason: "Session expiry must be in the future".to_string(),
+            });
+        }
+        if config.budget_token.is_empty() {
+            return Err(ChainGuardError::InvalidConfiguration {
+                reason: "Session budget token is required".to_string(),
+            });
+        }
+        if config.max_per_transaction > config.total_budget {
+            return Err(ChainGuardError::InvalidConfiguration {
+                reason: "Per-transaction limit exceeds the total budget".to_string(),
+            });
+        }
+        if let Some(unknown) = config.allowed_action_types
+            .iter()
+            .find(|t| !ACTION_TYPES.contains(&t.as_str()))
+        {
+            return Err(ChainGuardError::InvalidConfiguration {
+                reason: format!("Unknown action type: {}", unknown),
+            });
+        }
+
+        let session = AgentSession {
+            principal: config.principal,
+            created_by,
+            created_at: current_time,
+            expires_at: config.expires_at,
+            total_budget: config.total_budget,
+            spent: 0,
+            max_per_transaction: config.max_per_transaction,
+            allowed_action_types: config.allowed_action_types,
+            allowed_chains: config.allowed_chains,
+            revoked: false,
+            budget_token: Some(config.budget_token),
+        };
+        self.sessions.insert(session.principal, session.clone());
+        Ok(session)
+    }
+
+    // Revoke a session; takes effect on the next request
+    pub fn revoke(&mut self, principal: &Principal) -> ChainGuardResult<()> {
+        let session = self.sessions.get_mut(principal).ok_or(ChainGuardError::SessionNotFound)?;
+        session.revoked = true;
+        Ok(())
+    }
+
+    pub fn get(&self, principal: &Principal) -> Option<&AgentSession> {
+        self.sessions.get(principal)
+    }
+
+    pub fn list(&self) -> Vec<AgentSession> {
+        let mut sessions: Vec<AgentSession> = self.sessions.values().cloned().collect();
+        sessions.sort_by_key(|s| (s.created_at, s.principal));
+        sessions
+    }
+
+    // Check an action against the principal's session (no session = nothing to enforce)
+    pub fn authorize(&self, principal: &Principal, action: &Action, current_time: u64) -> ChainGuardResult<()> {
+        let session = match self.sessions.get(principal) {
+            Some(session) => session,
+            None => return Ok(()),
+        };
+
+        if session.revoked {
+            return Err(ChainGuardError::SessionRevoked);
+        }
+        if current_time >= session.expires_at {
+            return Err(ChainGuardError::SessionExpired);
+        }
+
+        let (action_type, chain, token, amount) = Self::action_scope(action);
+        if !session.allowed_action_types.is_empty() && !session.allowed_action_types.contains(&action_type) {
+            return Err(ChainGuardError::SessionActionNotAllowed { action_type });
+        }
+        if !session.allowed_chains.is_empty() && !session.allowed_chains.contains(&chain) {
+            return Err(ChainGuardError::SessionChainNotAllowed { chain });
+        }
+        if session.budget_token.as_ref() != Some(&token) {
+            return Err(ChainGuardError::SessionTokenNotAllowed { token });
+        }
+        if amount > session.max_per_transaction {
+            return Err(ChainGuardError::SessionTransactionLimitExceeded {
+                limit: session.max_per_transaction,
+                requested: amount,
+            });
+        }
+        let remaining = session.total_budget.saturating_sub(session.spent);
+        if amount > remaining {
+            return Err(ChainGuardError::SessionBudgetExceeded { remaining, requested: amount });
+        }
+        Ok(())
+    }
+
+    // Deduct an accepted action from the session budget
+    pub fn charge(&mut self, principal: &Principal, amount: u64) {
+        if let Some(session) = self.sessions.get_mut(principal) {
+            session.spent = session.spent.saturating_add(amount);
+        }
+    }
+
+    // Give back budget for an action that was rejected, expired or failed
+    pub fn refund(&mut self, principal: &Principal, amount: u64) {
+        if let Some(session) = self.sessions.get_mut(principal) {
+            session.spent = session.spent.saturating_sub(amount);
+        }
+    }
+
+    // (action type, chain, token, amount) a session is checked against; swaps spend token_in
+    fn action_scope(action: &Action) -> (String, String, String, u64) {
+        match action {
+            Action::Swap { chain, token_in, amount_in, .. } => {
+                ("swap".to_string(), chain.clone(), token_in.clone(), *amount_in)
+            }
+            Action::Transfer { chain, token, amount, .. } => {
+                ("transfer".to_string(), chain.clone(), token.clone(), *amount)
+            }
+            Action::ApproveToken { chain, token, amount, .. } => {
+                ("approve".to_string(), chain.clone(), token.clone(), *amount)
+            }
+        }
+    }
+
+    // Restore session from stable memory (for post_upgrade)
+    pub fn restore_session(&mut self, session: AgentSession) {
+        self.sessions.insert(session.principal, session);
+    }
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3600 * 1_000_000_000;
+
+    fn mock_principal(id: u8) -> Principal {
+        let mut bytes = [0u8; 29];
+        bytes[0] = id;
+        Principal::from_slice(&bytes)
+    }
+
+    fn mock_config(agent: Principal) -> SessionConfig {
+        SessionConfig {
+            principal: agent,
+            expires_at: 10 * HOUR,
+            total_budget: 1000,
+            max_per_transaction: 400,
+            allowed_action_types: vec!["transfer".to_string()],
+            allowed_chains: vec!["sepolia".to_string()],
+            budget_token: "USDC".to_string(),
+        }
+    }
+
+    fn mock_transfer(chain: &str, amount: u64) -> Action {
+        Action::Transfer {
+            chain: chain.to_string(),
+            token: "USDC".to_string(),
+            to: "0x123".to_string(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_create_session_validation() {
+        let mut sessions = SessionManager::new();
+        let owner = mock_principal(1);
+        let agent = mock_principal(2);
+
+        let expired = SessionConfig { expires_at: HOUR, ..mock_config(agent) };
+        assert!(matches!(
+            sessions.create(expired, owner, 2 * HOUR),
+            Err(ChainGuardError::InvalidConfiguration { .. })
+        ));
+
+        let oversized = SessionConfig { max_per_transaction: 2000, ..mock_config(agent) };
+        assert!(sessions.create(oversized, owner, 0).is_err());
+
+        let unknown = SessionConfig { allowed_action_types: vec!["bridge".to_string()], ..mock_config(agent) };
+        assert!(sessions.create(unknown, owner, 0).is_err());
+
+        let no_token = SessionConfig { budget_token: String::new(), ..mock_config(agent) };
+        assert!(sessions.create(no_token, owner, 0).is_err());
+
+        let session = sessions.create(mock_config(agent), owner, HOUR).unwrap();
+        assert_eq!(session.created_by, owner);
+        assert_eq!(session.created_at, HOUR);
+        assert_eq!(session.spent, 0);
+        assert_eq!(sessions.list().len(), 1);
+    }
+
+    #[test]
+    fn test_authorize_scope() {
+        let mut sessions = SessionManager::new();
+        let agent = mock_principal(2);
+        sessions.create(mock_config(agent), mock_principal(1), 0).unwrap();
+
+        assert!(sessions.authorize(&agent, &mock_transfer("sepolia", 400), HOUR).is_ok());
+        assert_eq!(
+            sessions.authorize(&agent, &mock_transfer("ethereum", 100), HOUR),
+            Err(ChainGuardError::SessionChainNotAllowed { chain: "ethereum".to_string() })
+        );
+        assert_eq!(
+            sessions.authorize(&agent, &mock_transfer("sepolia", 401), HOUR),
+            Err(ChainGuardError::SessionTransactionLimitExceeded { limit: 400, requested: 401 })
+        );
+
+        let swap = Action::Swap {
+            chain: "sepolia".to_string(),
+            token_in: "USDC".to_string(),
+            token_out: "WETH".to_string(),
+            amount_in: 100,
+            min_amount_out: 1,
+            fee_tier: None,
+        };
+        assert_eq!(
+            sessions.authorize(&agent, &swap, HOUR),
+            Err(ChainGuardError::SessionActionNotAllowed { action_type: "swap".to_string() })
+        );
+
+        assert_eq!(
+            sessions.authorize(&agent, &mock_transfer("sepolia", 100), 10 * HOUR),
+            Err(ChainGuardError::SessionExpired)
+        );
+
+        // Principals without a session are not scoped
+        assert!(sessions.authorize(&mock_principal(3), &swap, HOUR).is_ok());
+    }
+
+    #[test]
+    fn test_budget_is_counted_in_one_token() {
+        let mut sessions = SessionManager::new();
+        let agent = mock_principal(2);
+        let config = SessionConfig { allowed_action_types: vec![], ..mock_config(agent) };
+        sessions.create(config, mock_principal(1), 0).unwrap();
+
+        let weth = Action::Transfer {
+            chain: "sepolia".to_string(),
+            token: "WETH".to_string(),
+            to: "0x123".to_string(),
+            amount: 1,
+        };
+        assert_eq!(
+            sessions.authorize(&agent, &weth, HOUR),
+            Err(ChainGuardError::SessionTokenNotAllowed { token: "WETH".to_string() })
+        );
+
+        // Swaps are checked against the token they spend
+        let swap = |token_in: &str| Action::Swap {
+            chain: "sepolia".to_string(),
+            token_in: token_in.to_string(),
+            token_out: "USDC".to_string(),
+            amount_in: 100,
+            min_amount_out: 1,
+            fee_tier: None,
+        };
+        assert!(sessions.authorize(&agent, &swap("USDC"), HOUR).is_ok());
+        assert!(sessions.authorize(&agent, &swap("WETH"), HOUR).is_err());
+
+        // Sessions minted without a budget token are refused until re-minted
+        let mut legacy = sessions.get(&agent).unwrap().clone();
+        legacy.budget_token = None;
+        sessions.restore_session(legacy);
+        assert_eq!(
+            sessions.authorize(&agent, &mock_transfer("sepolia", 100), HOUR),
+            Err(ChainGuardError::SessionTokenNotAllowed { token: "USDC".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_budget_charge_and_refund() {
+        let mut sessions = SessionManager::new();
+        let agent = mock_principal(2);
+        sessions.create(mock_config(agent), mock_principal(1), 0).unwrap();
+
+        sessions.charge(&agent, 400);
+        sessions.charge(&agent, 400);
+        assert_eq!(
+            sessions.authorize(&agent, &mock_transfer("sepolia", 300), HOUR),
+            Err(ChainGuardError::SessionBudgetExceeded { remaining: 200, requested: 300 })
+        );
+
+        sessions.refund(&agent, 400);
+        assert_eq!(sessions.get(&agent).unwrap().spent, 400);
+        assert!(sessions.authorize(&agent, &mock_transfer("sepolia", 300), HOUR).is_ok());
+    }
+
+    #[test]
+    fn test_revoke_session() {
+        let mut sessions = SessionManager::new();
+        let agent = mock_principal(2);
+        assert_eq!(sessions.revoke(&agent), Err(ChainGuardError::SessionNotFound));
+
+        sessions.create(mock_config(agent), mock_principal(1), 0).unwrap();
+        sessions.revoke(&agent).unwrap();
+        assert_eq!(
+            sessions.authorize(&agent, &mock_transfer("sepolia", 100), HOUR),
+            Err(ChainGuardError::SessionRevoked)
+        );
+
+        // Re-minting replaces the revoked session
+        sessions.create(mock_config(agent), mock_principal(1), HOUR).unwrap();
+        assert!(sessions.authorize(&agent, &mock_transfer("sepolia", 100), HOUR).is_ok());
+    }
+}
@@ -0,0 +1,288 @@
+use crate::types::*;
+use candid::Principal;
+use std::collections::HashMap;
+
+const DAY_NANOS: u64 = 86_400 * 1_000_000_000;
+
+// Tracks executed volume per (requester, token, chain), plus volume reserved
+// by actions that are awaiting signatures or execution.
+pub struct SpendLedger {
+    entries: Vec<SpendEntry>,
+    reservations: HashMap<u64, SpendEntry>,  // keyed by audit entry id
+}
+
+impl SpendLedger {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            reservations: HashMap::new(),
+        }
+    }
+
+    // Reserve volume for an action that has passed policy but not executed yet
+    pub fn reserve(&mut self, audit_id: u64, action: &Action, requester: Principal, current_time: u64) {
+        self.reservations.insert(audit_id, Self::entry_for(action, requester, current_time));
+    }
+
+    // Drop a reservation (request rejected, expired or execution failed)
+    pub fn release(&mut self, audit_id: u64) -> Option<SpendEntry> {
+        self.reservations.remove(&audit_id)
+    }
+
+    pub fn get_reservation(&self, audit_id: u64) -> Option<&SpendEntry> {
+        self.reservations.get(&audit_id)
+    }
+
+    // Turn a reservation into executed volume
+    pub fn commit(&mut self, audit_id: u64, current_time: u64) -> bool {
+        match self.reservations.remove(&audit_id) {
+            Some(mut entry) => {
+                self.prune(current_time);
+                entry.timestamp = current_time;
+                self.entries.push(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Volume executed in the last 24 hours, plus pending reservations
+    pub fn rolling_24h(&self, requester: &Principal, token: &str, chain: &str, current_time: u64) -> u64 {
+        let since = current_time.saturating_sub(DAY_NANOS);
+        self.executed_since(requester, token, chain, since) + self.reserved(requester, token, chain)
+    }
+
+    // Volume executed since 00:00 UTC today, plus pending reservations
+    pub fn calendar_day(&self, requester: &Principal, token: &str, chain: &str, current_time: u64) -> u64 {
+        let since = current_time - current_time % DAY_NANOS;
+        self.executed_since(requester, token, chain, since) + self.reserved(requester, token, chain)
+    }
+
+    // Rolling 24h volume for the token and chain an action spends from
+    pub fn spent_for_action(&self, action: &Action, requester: &Principal, current_time: u64) -> u64 {
+        let (token, chain, _) = Self::action_spend(action);
+        self.rolling_24h(requester, &token, &chain, current_time)
+    }
+
+    pub fn summary(&self, requester: &Principal, token: &str, chain: &str, current_time: u64) -> SpendSummary {
+        SpendSummary {
+            rolling_24h: self.rolling_24h(requester, token, chain, current_time),
+            calendar_day: self.calendar_day(requester, token, chain, current_time),
+            reserved: self.reserved(requester, token, chain),
+        }
+    }
+
+    fn executed_since(&self, requester: &Principal, token: &str, chain: &str, since: u64) -> u64 {
+        self.entries
+            .iter()
+            .filter(|e| e.timestamp >= since && Self::matches(e, requester, token, chain))
+            .fold(0u64, |acc, e| acc.saturating_add(e.amount))
+    }
+
+    fn reserved(&self, requester: &Principal, token: &str, chain: &str) -> u64 {
+        self.reservations
+            .values()
+            .filter(|e| Self::matches(e, requester, token, chain))
+            .fold(0u64, |acc, e| acc.saturating_add(e.amount))
+    }
+
+    fn matches(entry: &SpendEntry, requester: &Principal, token: &str, chain: &str) -> bool {
+        entry.requester == *requester && entry.token == token && entry.chain == chain
+    }
+
+    // Entries older than 24h no longer count towards either window
+    fn prune(&mut self, current_time: u64) {
+        let cutoff = current_time.saturating_sub(DAY_NANOS);
+        self.entries.retain(|e| e.timestamp >= cutoff);
+    }
+
+    fn entry_for(action: &Action, requester: Principal, current_time: u64) -> SpendEntry {
+        let (token, chain, amount) = Self::action_spend(action);
+        SpendEntry {
+            requester,
+            token,
+            chain,
+            amount,
+            timestamp: current_time,
+        }
+    }
+
+    // (token, chain, amount) an action spends; swaps spend token_in
+    fn action_spend(action: &Action) -> (String, String, u64) {
+        match action {
+            Action::Swap { chain, token_in, amount_in, .. } => (token_in.clone(), chain.clone(), *amount_in),
+            Action::Transfer { chain, token, amount, .. } => (token.clone(), chain.clone(), *amount),
+            Action::ApproveToken { chain, token, amount, .. } => (token.clone(), chain.clone(), *amount),
+        }
+    }
+
+    pub fn get_entries(&self) -> Vec<SpendEntry> {
+        self.entries.clone()
+    }
+
+    pub fn get_reservations(&self) -> Vec<(u64, SpendEntry)> {
+        self.reservations
+            .iter()
+            .map(|(id, entry)| (*id, entry.clone()))
+            .collect()
+    }
+
+    // Restore ledger from stable memory (for post_upgrade)
+    pub fn restore(&mut self, entries: Vec<SpendEntry>, reservations: Vec<(u64, SpendEntry)>) {
+        self.entries = entries;
+        self.reservations = reservations.into_iter().collect();
+    }
+}
+
+impl Default for SpendLedger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+
+    const HOUR: u64 = 3600 * 1_000_000_000;
+
+    fn mock_principal(id: u8) -> Principal {
+        let mut bytes = [0u8; 29];
+        bytes[0] = id;
+        Principal::from_slice(&bytes)
+    }
+
+    // Reserve and immediately commit, as request_action does for allowed actions
+    fn record_spend(ledger: &mut SpendLedger, action: &Action, requester: Principal, time: u64) {
+        ledger.reserve(u64::MAX, action, requester, time);
+        ledger.commit(u64::MAX, time);
+    }
+
+    fn mock_transfer(amount: u64) -> Action {
+        Action::Transfer {
+            chain: "ethereum".to_string(),
+            token: "USDC".to_string(),
+            to: "0x123".to_string(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_rolling_24h_window() {
+        let mut ledger = SpendLedger::new();
+        let agent = mock_principal(1);
+        let start = 10 * DAY_NANOS;
+
+        record_spend(&mut ledger, &mock_transfer(1000), agent, start);
+        record_spend(&mut ledger, &mock_transfer(500), agent, start + 12 * HOUR);
+
+        assert_eq!(ledger.rolling_24h(&agent, "USDC", "ethereum", start + 12 * HOUR), 1500);
+        // First spend falls out of the window after 24h
+        assert_eq!(ledger.rolling_24h(&agent, "USDC", "ethereum", start + 25 * HOUR), 500);
+        assert_eq!(ledger.rolling_24h(&agent, "USDC", "ethereum", start + 37 * HOUR), 0);
+    }
+
+    #[test]
+    fn test_calendar_day_resets_at_midnight() {
+        let mut ledger = SpendLedger::new();
+        let agent = mock_principal(1);
+        let midnight = 10 * DAY_NANOS;
+
+        record_spend(&mut ledger, &mock_transfer(1000), agent, midnight - HOUR);
+        record_spend(&mut ledger, &mock_transfer(500), agent, midnight + HOUR);
+
+        let now = midnight + 2 * HOUR;
+        assert_eq!(ledger.calendar_day(&agent, "USDC", "ethereum", now), 500);
+        assert_eq!(ledger.rolling_24h(&agent, "USDC", "ethereum", now), 1500);
+    }
+
+    #[test]
+    fn test_scoped_by_requester_token_and_chain() {
+        let mut ledger = SpendLedger::new();
+        let agent1 = mock_principal(1);
+        let agent2 = mock_principal(2);
+        let now = 10 * DAY_NANOS;
+
+        record_spend(&mut ledger, &mock_transfer(1000), agent1, now);
+        record_spend(
+            &mut ledger,
+            &Action::Swap {
+                chain: "ethereum".to_string(),
+                token_in: "USDC".to_string(),
+                token_out: "WETH".to_string(),
+                amount_in: 200,
+                min_amount_out: 1,
+                fee_tier: None,
+            },
+            agent1,
+            now,
+        );
+        record_spend(
+            &mut ledger,
+            &Action::Transfer {
+                chain: "polygon".to_string(),
+                token: "USDC".to_string(),
+                to: "0x123".to_string(),
+                amount: 300,
+            },
+            agent1,
+            now,
+        );
+
+        assert_eq!(ledger.rolling_24h(&agent1, "USDC", "ethereum", now), 1200);
+        assert_eq!(ledger.rolling_24h(&agent1, "USDC", "polygon", now), 300);
+        assert_eq!(ledger.rolling_24h(&agent1, "WETH", "ethereum", now), 0);
+        assert_eq!(ledger.rolling_24h(&agent2, "USDC", "ethereum", now), 0);
+    }
+
+    #[test]
+    fn test_reservation_counts_until_released() {
+        let mut ledger = SpendLedger::new();
+        let agent = mock_principal(1);
+        let now = 10 * DAY_NANOS;
+
+        ledger.reserve(7, &mock_transfer(2000), agent, now);
+        let summary = ledger.summary(&agent, "USDC", "ethereum", now);
+        assert_eq!(summary.reserved, 2000);
+        assert_eq!(summary.rolling_24h, 2000);
+        assert_eq!(summary.calendar_day, 2000);
+
+        assert!(ledger.release(7).is_some());
+        assert!(ledger.release(7).is_none());
+        assert_eq!(ledger.rolling_24h(&agent, "USDC", "ethereum", now), 0);
+    }
+
+    #[test]
+    fn test_commit_reservation() {
+        let mut ledger = SpendLedger::new();
+        let agent = mock_principal(1);
+        let now = 10 * DAY_NANOS;
+
+        ledger.reserve(7, &mock_transfer(2000), agent, now);
+        assert!(ledger.commit(7, now + HOUR));
+        assert!(!ledger.commit(7, now + HOUR));
+
+        let summary = ledger.summary(&agent, "USDC", "ethereum", now + HOUR);
+        assert_eq!(summary.reserved, 0);
+        assert_eq!(summary.rolling_24h, 2000);
+
+        // Executed volume is dated from execution, not from the request
+        assert_eq!(ledger.rolling_24h(&agent, "USDC", "ethereum", now + 24 * HOUR), 2000);
+    }
+
+    #[test]
+    fn test_restore() {
+        let mut ledger = SpendLedger::new();
+        let agent = mock_principal(1);
+        let now = 10 * DAY_NANOS;
+
+        record_spend(&mut ledger, &mock_transfer(1000), agent, now);
+        ledger.reserve(3, &mock_transfer(500), agent, now);
+
+        let mut restored = SpendLedger::new();
+        restored.restore(ledger.get_entries(), ledger.get_reservations());
+        assert_eq!(restored.rolling_24h(&agent, "USDC", "ethereum", now), 1500);
+        assert!(restored.release(3).is_some());
+    }
+}
@@ -1,24 +1,49 @@
 use candid::{CandidType, Deserialize, Principal};
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
 use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap};
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::cell::RefCell;
 
 use crate::types::*;
 
 // Type aliases for stable memory
-type Memory = VirtualMemory<DefaultMemoryImpl>;
+pub type Memory = VirtualMemory<DefaultMemoryImpl>;
 type ConfigMemory = StableBTreeMap<u8, Vec<u8>, Memory>;
 type RoleMemory = StableBTreeMap<Vec<u8>, Vec<u8>, Memory>;
 type PolicyMemory = StableBTreeMap<u64, Vec<u8>, Memory>;
 type PendingRequestMemory = StableBTreeMap<u64, Vec<u8>, Memory>;
-type AuditMemory = StableBTreeMap<u64, Vec<u8>, Memory>;
+type ExecutionMemory = StableBTreeMap<Vec<u8>, Vec<u8>, Memory>;
+type SpendMemory = StableBTreeMap<u64, Vec<u8>, Memory>;
+type PolicyVersionMemory = StableBTreeMap<u64, Vec<u8>, Memory>;
+type CustomRoleMemory = StableBTreeMap<String, Vec<u8>, Memory>;
+type SessionMemory = StableBTreeMap<Vec<u8>, Vec<u8>, Memory>;
+
+// Spend reservation keyed by the audit entry id of its request
+type SpendReservation = (u64, SpendEntry);
 
 const CONFIG_MEMORY_ID: MemoryId = MemoryId::new(0);
 const ROLE_MEMORY_ID: MemoryId = MemoryId::new(1);
 const POLICY_MEMORY_ID: MemoryId = MemoryId::new(2);
 const PENDING_REQUEST_MEMORY_ID: MemoryId = MemoryId::new(3);
 const AUDIT_MEMORY_ID: MemoryId = MemoryId::new(4);
+const EXECUTION_MEMORY_ID: MemoryId = MemoryId::new(5);
+const SPEND_MEMORY_ID: MemoryId = MemoryId::new(6);
+const SPEND_RESERVATION_MEMORY_ID: MemoryId = MemoryId::new(7);
+const POLICY_VERSION_MEMORY_ID: MemoryId = MemoryId::new(8);
+const CUSTOM_ROLE_MEMORY_ID: MemoryId = MemoryId::new(9);
+const SESSION_MEMORY_ID: MemoryId = MemoryId::new(10);
+const AUDIT_INDEX_MEMORY_ID: MemoryId = MemoryId::new(11);
+
+// Keys in CONFIG_STORE
+const NEXT_POLICY_ID_KEY: u8 = 1;
+const OWNERSHIP_PROPOSAL_KEY: u8 = 2;
+const PAUSED_KEY: u8 = 3;
+const EXECUTOR_CONFIG_KEY: u8 = 4;
+const SCHEMA_VERSION_KEY: u8 = 5;
+const SIGNER_WEIGHTS_KEY: u8 = 6;
+const REJECTION_CONFIG_KEY: u8 = 7;
+const SIGNING_RULES_KEY: u8 = 8;
 
 thread_local! {
     static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> =
@@ -48,27 +73,44 @@ thread_local! {
         )
     );
 
-    static AUDIT_STORE: RefCell<AuditMemory> = RefCell::new(
+    static EXECUTION_STORE: RefCell<ExecutionMemory> = RefCell::new(
         StableBTreeMap::init(
-            MEMORY_MANAGER.with(|m| m.borrow().get(AUDIT_MEMORY_ID)),
+            MEMORY_MANAGER.with(|m| m.borrow().get(EXECUTION_MEMORY_ID)),
         )
     );
-}
 
-// Serializable state for upgrade persistence
-#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
-pub struct StableState {
-    pub config: Option<ChainGuardConfig>,
-    pub role_assignments: Vec<(Principal, Vec<Role>)>,
-    pub policies: Vec<Policy>,
-    pub pending_requests: Vec<PendingRequest>,
-    pub audit_entries: Vec<AuditEntry>,
-    pub paused: bool,
-    pub daily_volume: u64,
-    pub last_reset: u64,
-    pub executor_config: ExecutorConfig,
+    static SPEND_STORE: RefCell<SpendMemory> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(SPEND_MEMORY_ID)),
+        )
+    );
+
+    static SPEND_RESERVATION_STORE: RefCell<SpendMemory> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(SPEND_RESERVATION_MEMORY_ID)),
+        )
+    );
+
+    static POLICY_VERSION_STORE: RefCell<PolicyVersionMemory> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(POLICY_VERSION_MEMORY_ID)),
+        )
+    );
+
+    static CUSTOM_ROLE_STORE: RefCell<CustomRoleMemory> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(CUSTOM_ROLE_MEMORY_ID)),
+        )
+    );
+
+    static SESSION_STORE: RefCell<SessionMemory> = RefCell::new(
+        StableBTreeMap::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(SESSION_MEMORY_ID)),
+        )
+    );
 }
 
+// Signing key the executor was created with
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
 pub struct ExecutorConfig {
     pub key_name: String,
@@ -88,18 +130,50 @@ pub fn store_config(config: &ChainGuardConfig) -> Result<(), String> {
 }
 
 // Load config
-pub fn load_config() -> Option<ChainGuardConfig> {
+pub fn load_config() -> Result<Option<ChainGuardConfig>, String> {
+    load_config_value(0, "config")
+}
+
+// Decode a stored record; a record that no longer decodes fails the load
+// instead of being skipped (layout changes need a migration)
+fn decode<T: CandidType + DeserializeOwned>(bytes: &[u8], what: &str) -> Result<T, String> {
+    candid::decode_one(bytes).map_err(|e| format!("Failed to decode {}: {}", what, e))
+}
+
+// Decode a CONFIG_STORE value (None if the key was never written)
+fn load_config_value<T: CandidType + DeserializeOwned>(key: u8, what: &str) -> Result<Option<T>, String> {
+    CONFIG_STORE.with(|store| store.borrow().get(&key))
+        .map(|bytes| decode(&bytes, what))
+        .transpose()
+}
+
+// Store the layout version the stable structures are written in
+pub fn store_schema_version(version: u32) -> Result<(), String> {
+    let encoded = candid::encode_one(version)
+        .map_err(|e| format!("Failed to encode schema version: {}", e))?;
     CONFIG_STORE.with(|store| {
-        store.borrow().get(&0).and_then(|bytes| {
-            candid::decode_one(&bytes).ok()
-        })
-    })
+        store.borrow_mut().insert(SCHEMA_VERSION_KEY, encoded);
+    });
+
+    Ok(())
 }
 
-// Store role assignment
-pub fn store_role(principal: &Principal, roles: &Vec<Role>) -> Result<(), String> {
+// Load the layout version (None for canisters written before versioning)
+pub fn load_schema_version() -> Result<Option<u32>, String> {
+    load_config_value(SCHEMA_VERSION_KEY, "schema version")
+}
+
+// Store role grants for a principal (no grants removes the key)
+pub fn store_role(principal: &Principal, grants: &[RoleAssignment]) -> Result<(), String> {
     let key = principal.as_slice().to_vec();
-    let encoded = candid::encode_one(roles)
+    if grants.is_empty() {
+        ROLE_STORE.with(|store| {
+            store.borrow_mut().remove(&key);
+        });
+        return Ok(());
+    }
+
+    let encoded = candid::encode_one(grants)
         .map_err(|e| format!("Failed to encode roles: {}", e))?;
 
     ROLE_STORE.with(|store| {
@@ -109,61 +183,142 @@ pub fn store_role(principal: &Principal, roles: &Vec<Role>) -> Result<(), String
     Ok(())
 }
 
-// Load role assignment
-pub fn load_role(principal: &Principal) -> Option<Vec<Role>> {
-    let key = principal.as_slice().to_vec();
+// Load all role grants
+pub fn load_all_roles() -> Result<Vec<RoleAssignment>, String> {
     ROLE_STORE.with(|store| {
-        store.borrow().get(&key).and_then(|bytes| {
-            candid::decode_one(&bytes).ok()
-        })
+        let store = store.borrow();
+        let mut grants = Vec::new();
+        for (_, value_bytes) in store.iter() {
+            grants.extend(decode::<Vec<RoleAssignment>>(&value_bytes, "role grants")?);
+        }
+        Ok(grants)
     })
 }
 
-// Load all roles
-pub fn load_all_roles() -> Vec<(Principal, Vec<Role>)> {
+// Rewrite role grants stored as a bare Vec<Role> (before expiry existed)
+// as permanent RoleAssignment lists
+pub fn migrate_legacy_role_grants() -> Result<(), String> {
     ROLE_STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = store.iter().collect();
+        for (key, value_bytes) in entries {
+            if candid::decode_one::<Vec<RoleAssignment>>(&value_bytes).is_ok() {
+                continue;
+            }
+            let principal = Principal::try_from_slice(&key)
+                .map_err(|e| format!("Invalid role grant key: {}", e))?;
+            let roles: Vec<Role> = decode(&value_bytes, "legacy role grants")?;
+            let grants: Vec<RoleAssignment> = roles.into_iter().map(|role| RoleAssignment {
+                principal,
+                role,
+                assigned_at: 0,
+                assigned_by: principal,
+                expires_at: None,
+            }).collect();
+            let encoded = candid::encode_one(&grants)
+                .map_err(|e| format!("Failed to encode roles: {}", e))?;
+            store.insert(key, encoded);
+        }
+        Ok(())
+    })
+}
+
+// Store custom role definitions, replacing any previous snapshot
+// (deleted roles must not come back after an upgrade)
+pub fn store_custom_roles(roles: &[(String, Vec<Permission>)]) -> Result<(), String> {
+    CUSTOM_ROLE_STORE.with(|store| -> Result<(), String> {
+        let mut store = store.borrow_mut();
+        let keys: Vec<String> = store.iter().map(|(k, _)| k).collect();
+        for key in keys {
+            store.remove(&key);
+        }
+        for (name, permissions) in roles {
+            let encoded = candid::encode_one(permissions)
+                .map_err(|e| format!("Failed to encode custom role: {}", e))?;
+            store.insert(name.clone(), encoded);
+        }
+        Ok(())
+    })
+}
+
+// Load custom role definitions
+pub fn load_custom_roles() -> Result<Vec<(String, Vec<Permission>)>, String> {
+    CUSTOM_ROLE_STORE.with(|store| {
         let store = store.borrow();
-        store.iter().filter_map(|(key_bytes, value_bytes)| {
-            let principal = Principal::try_from_slice(&key_bytes).ok()?;
-            let roles: Vec<Role> = candid::decode_one(&value_bytes).ok()?;
-            Some((principal, roles))
+        store.iter().map(|(name, value_bytes)| {
+            let permissions = decode(&value_bytes, "custom role")?;
+            Ok((name, permissions))
         }).collect()
     })
 }
 
-// Store policy
-pub fn store_policy(index: u64, policy: &Policy) -> Result<(), String> {
+// Store policy (keyed by its stable ID)
+pub fn store_policy(id: u64, policy: &Policy) -> Result<(), String> {
     let encoded = candid::encode_one(policy)
         .map_err(|e| format!("Failed to encode policy: {}", e))?;
 
     POLICY_STORE.with(|store| {
-        store.borrow_mut().insert(index, encoded);
+        store.borrow_mut().insert(id, encoded);
     });
 
     Ok(())
 }
 
-// Load policy
-pub fn load_policy(index: u64) -> Option<Policy> {
+// Store all policies, replacing any previous snapshot
+// (removed policies must not come back after an upgrade)
+pub fn store_policies(policies: &[Policy], next_policy_id: u64) -> Result<(), String> {
     POLICY_STORE.with(|store| {
-        store.borrow().get(&index).and_then(|bytes| {
-            candid::decode_one(&bytes).ok()
-        })
-    })
+        let mut store = store.borrow_mut();
+        let keys: Vec<u64> = store.iter().map(|(k, _)| k).collect();
+        for key in keys {
+            store.remove(&key);
+        }
+    });
+
+    for policy in policies {
+        store_policy(policy.id.unwrap_or_default(), policy)?;
+    }
+
+    let encoded = candid::encode_one(next_policy_id)
+        .map_err(|e| format!("Failed to encode policy id counter: {}", e))?;
+    CONFIG_STORE.with(|store| {
+        store.borrow_mut().insert(NEXT_POLICY_ID_KEY, encoded);
+    });
+
+    Ok(())
+}
+
+// Load the next policy ID (0 if never stored)
+pub fn load_next_policy_id() -> Result<u64, String> {
+    Ok(load_config_value(NEXT_POLICY_ID_KEY, "policy id counter")?.unwrap_or(0))
+}
+
+// Store the pending ownership proposal (None clears a stale one)
+pub fn store_ownership_proposal(proposal: Option<&OwnershipProposal>) -> Result<(), String> {
+    let encoded = candid::encode_one(proposal)
+        .map_err(|e| format!("Failed to encode ownership proposal: {}", e))?;
+    CONFIG_STORE.with(|store| {
+        store.borrow_mut().insert(OWNERSHIP_PROPOSAL_KEY, encoded);
+    });
+
+    Ok(())
+}
+
+// Load the pending ownership proposal
+pub fn load_ownership_proposal() -> Result<Option<OwnershipProposal>, String> {
+    Ok(load_config_value::<Option<OwnershipProposal>>(OWNERSHIP_PROPOSAL_KEY, "ownership proposal")?.flatten())
 }
 
 // Load all policies
-pub fn load_all_policies() -> Vec<Policy> {
+pub fn load_all_policies() -> Result<Vec<Policy>, String> {
     POLICY_STORE.with(|store| {
         let store = store.borrow();
-        store.iter().filter_map(|(_, value_bytes)| {
-            candid::decode_one(&value_bytes).ok()
-        }).collect()
+        store.iter().map(|(_, value_bytes)| decode(&value_bytes, "policy")).collect()
     })
 }
 
-// Store pending request
-pub fn store_pending_request(request: &PendingRequest) -> Result<(), String> {
+// Store a threshold request of any status, so its history survives upgrades
+pub fn store_threshold_request(request: &PendingRequest) -> Result<(), String> {
     let encoded = candid::encode_one(request)
         .map_err(|e| format!("Failed to encode request: {}", e))?;
 
@@ -174,53 +329,385 @@ pub fn store_pending_request(request: &PendingRequest) -> Result<(), String> {
     Ok(())
 }
 
-// Load pending request
-pub fn load_pending_request(id: u64) -> Option<PendingRequest> {
+// Load all threshold requests
+pub fn load_all_threshold_requests() -> Result<Vec<PendingRequest>, String> {
     PENDING_REQUEST_STORE.with(|store| {
-        store.borrow().get(&id).and_then(|bytes| {
-            candid::decode_one(&bytes).ok()
-        })
+        let store = store.borrow();
+        store.iter().map(|(_, value_bytes)| decode(&value_bytes, "threshold request")).collect()
     })
 }
 
-// Load all pending requests
-pub fn load_all_pending_requests() -> Vec<PendingRequest> {
+// Write raw bytes as a threshold request, to seed layouts of earlier versions
+#[cfg(test)]
+pub fn store_raw_threshold_request(id: u64, bytes: Vec<u8>) {
     PENDING_REQUEST_STORE.with(|store| {
+        store.borrow_mut().insert(id, bytes);
+    });
+}
+
+// Threshold requests stored before weighted voting (schema version 1)
+#[derive(CandidType, Deserialize)]
+struct UnweightedSignature {
+    signer: Principal,
+    signed_at: u64,
+}
+
+#[derive(CandidType, Deserialize)]
+struct UnweightedRequest {
+    id: u64,
+    action: Action,
+    requester: Principal,
+    created_at: u64,
+    expires_at: u64,
+    required_signatures: u8,
+    from_roles: Option<Vec<Role>>,  // Absent on requests written before signer roles
+    collected_signatures: Vec<UnweightedSignature>,
+    status: RequestStatus,
+}
+
+// Threshold requests stored before rejection quorums (schema version 2)
+#[derive(CandidType, Deserialize)]
+struct WeightedRequest {
+    id: u64,
+    action: Action,
+    requester: Principal,
+    created_at: u64,
+    expires_at: u64,
+    required_signatures: u8,
+    from_roles: Vec<Role>,
+    collected_signatures: Vec<Signature>,
+    status: RequestStatus,
+    required_weight: u64,
+    current_weight: u64,
+}
+
+// Rewrite unweighted threshold requests; every earlier signature weighed 1
+pub fn migrate_unweighted_requests() -> Result<(), String> {
+    PENDING_REQUEST_STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let entries: Vec<(u64, Vec<u8>)> = store.iter().collect();
+        for (id, value_bytes) in entries {
+            if candid::decode_one::<WeightedRequest>(&value_bytes).is_ok() {
+                continue;
+            }
+            let legacy: UnweightedRequest = decode(&value_bytes, "unweighted threshold request")?;
+            let request = WeightedRequest {
+                id: legacy.id,
+                action: legacy.action,
+                requester: legacy.requester,
+                created_at: legacy.created_at,
+                expires_at: legacy.expires_at,
+                required_signatures: legacy.required_signatures,
+                from_roles: legacy.from_roles.unwrap_or_default(),
+                current_weight: legacy.collected_signatures.len() as u64,
+                collected_signatures: legacy.collected_signatures.into_iter().map(|s| Signature {
+                    signer: s.signer,
+                    signed_at: s.signed_at,
+                    weight: 1,
+                }).collect(),
+                status: legacy.status,
+                required_weight: legacy.required_signatures as u64,
+            };
+            let encoded = candid::encode_one(&request)
+                .map_err(|e| format!("Failed to encode request: {}", e))?;
+            store.insert(id, encoded);
+        }
+        Ok(())
+    })
+}
+
+// Add rejection records to threshold requests; earlier rejections kept no
+// signer or reason, and a single rejection was enough
+pub fn migrate_requests_without_rejections() -> Result<(), String> {
+    PENDING_REQUEST_STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let entries: Vec<(u64, Vec<u8>)> = store.iter().collect();
+        for (id, value_bytes) in entries {
+            if candid::decode_one::<PendingRequest>(&value_bytes).is_ok() {
+                continue;
+            }
+            let legacy: WeightedRequest = decode(&value_bytes, "threshold request without rejections")?;
+            let request = PendingRequest {
+                id: legacy.id,
+                action: legacy.action,
+                requester: legacy.requester,
+                created_at: legacy.created_at,
+                expires_at: legacy.expires_at,
+                required_signatures: legacy.required_signatures,
+                from_roles: legacy.from_roles,
+                collected_signatures: legacy.collected_signatures,
+                status: legacy.status,
+                required_weight: legacy.required_weight,
+                current_weight: legacy.current_weight,
+                rejections: Vec::new(),
+                required_rejections: 1,
+                timelock: None,
+                executable_at: None,
+                failed_at: None,
+            };
+            let encoded = candid::encode_one(&request)
+                .map_err(|e| format!("Failed to encode request: {}", e))?;
+            store.insert(id, encoded);
+        }
+        Ok(())
+    })
+}
+
+// Store the rejection quorum and veto principals
+pub fn store_rejection_config(config: &RejectionConfig) -> Result<(), String> {
+    let encoded = candid::encode_one(config)
+        .map_err(|e| format!("Failed to encode rejection config: {}", e))?;
+    CONFIG_STORE.with(|store| {
+        store.borrow_mut().insert(REJECTION_CONFIG_KEY, encoded);
+    });
+
+    Ok(())
+}
+
+// Load the rejection config (single rejection, no vetoes if never stored)
+pub fn load_rejection_config() -> Result<RejectionConfig, String> {
+    Ok(load_config_value(REJECTION_CONFIG_KEY, "rejection config")?.unwrap_or_default())
+}
+
+// Store the separation-of-duties rules
+pub fn store_signing_rules(rules: &SigningRules) -> Result<(), String> {
+    let encoded = candid::encode_one(rules)
+        .map_err(|e| format!("Failed to encode signing rules: {}", e))?;
+    CONFIG_STORE.with(|store| {
+        store.borrow_mut().insert(SIGNING_RULES_KEY, encoded);
+    });
+
+    Ok(())
+}
+
+// Load the signing rules (requester excluded, no daily cap if never stored)
+pub fn load_signing_rules() -> Result<SigningRules, String> {
+    Ok(load_config_value(SIGNING_RULES_KEY, "signing rules")?.unwrap_or_default())
+}
+
+// Store signer weights
+pub fn store_signer_weights(weights: &[SignerWeight]) -> Result<(), String> {
+    let encoded = candid::encode_one(weights)
+        .map_err(|e| format!("Failed to encode signer weights: {}", e))?;
+    CONFIG_STORE.with(|store| {
+        store.borrow_mut().insert(SIGNER_WEIGHTS_KEY, encoded);
+    });
+
+    Ok(())
+}
+
+// Load signer weights (none if never stored)
+pub fn load_signer_weights() -> Result<Vec<SignerWeight>, String> {
+    Ok(load_config_value(SIGNER_WEIGHTS_KEY, "signer weights")?.unwrap_or_default())
+}
+
+// Store the emergency pause flag
+pub fn store_paused(paused: bool) -> Result<(), String> {
+    let encoded = candid::encode_one(paused)
+        .map_err(|e| format!("Failed to encode paused flag: {}", e))?;
+    CONFIG_STORE.with(|store| {
+        store.borrow_mut().insert(PAUSED_KEY, encoded);
+    });
+
+    Ok(())
+}
+
+// Load the emergency pause flag (false if never stored)
+pub fn load_paused() -> Result<bool, String> {
+    Ok(load_config_value(PAUSED_KEY, "paused flag")?.unwrap_or(false))
+}
+
+// Store the executor's signing key configuration
+pub fn store_executor_config(config: &ExecutorConfig) -> Result<(), String> {
+    let encoded = candid::encode_one(config)
+        .map_err(|e| format!("Failed to encode executor config: {}", e))?;
+    CONFIG_STORE.with(|store| {
+        store.borrow_mut().insert(EXECUTOR_CONFIG_KEY, encoded);
+    });
+
+    Ok(())
+}
+
+// Load the executor's signing key configuration
+pub fn load_executor_config() -> Result<Option<ExecutorConfig>, String> {
+    load_config_value(EXECUTOR_CONFIG_KEY, "executor config")
+}
+
+// Memories owned by the audit log, which writes its entries and indexes directly
+pub fn audit_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(AUDIT_MEMORY_ID))
+}
+
+pub fn audit_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(AUDIT_INDEX_MEMORY_ID))
+}
+
+// Audit entries recorded only action requests before the typed event log
+#[derive(CandidType, Deserialize)]
+struct LegacyAuditEntry {
+    id: u64,
+    timestamp: u64,
+    action_type: String,
+    action_params: String,
+    requester: Principal,
+    policy_result: PolicyResult,
+    threshold_request_id: Option<u64>,
+    execution_result: Option<ExecutionResult>,
+    policy_version: Option<u64>,
+}
+
+// Typed audit entries stored before hash chaining; chained when the log is opened
+#[derive(CandidType, Deserialize)]
+struct UnchainedAuditEntry {
+    id: u64,
+    timestamp: u64,
+    caller: Principal,
+    event: AuditEvent,
+}
+
+// Decode a stored audit entry, accepting the formats of earlier versions
+pub fn decode_audit_entry(bytes: &[u8]) -> Option<AuditEntry> {
+    if let Ok(entry) = candid::decode_one::<AuditEntry>(bytes) {
+        return Some(entry);
+    }
+    if let Ok(entry) = candid::decode_one::<UnchainedAuditEntry>(bytes) {
+        return Some(AuditEntry {
+            id: entry.id,
+            timestamp: entry.timestamp,
+            caller: entry.caller,
+            event: entry.event,
+            prev_hash: Vec::new(),
+            hash: Vec::new(),
+        });
+    }
+    let legacy: LegacyAuditEntry = candid::decode_one(bytes).ok()?;
+    Some(AuditEntry {
+        id: legacy.id,
+        timestamp: legacy.timestamp,
+        caller: legacy.requester,
+        event: AuditEvent::ActionRequested(ActionEvent {
+            action_type: legacy.action_type,
+            action_params: legacy.action_params,
+            policy_result: legacy.policy_result,
+            threshold_request_id: legacy.threshold_request_id,
+            execution_result: legacy.execution_result,
+            policy_version: legacy.policy_version,
+        }),
+        prev_hash: Vec::new(),
+        hash: Vec::new(),
+    })
+}
+
+// Store last execution record (keyed by requester, policy ID and action type)
+pub fn store_execution_record(record: &ExecutionRecord) -> Result<(), String> {
+    let key = candid::encode_args((record.requester, record.policy_id, &record.action_type))
+        .map_err(|e| format!("Failed to encode execution key: {}", e))?;
+    let encoded = candid::encode_one(record)
+        .map_err(|e| format!("Failed to encode execution record: {}", e))?;
+
+    EXECUTION_STORE.with(|store| {
+        store.borrow_mut().insert(key, encoded);
+    });
+
+    Ok(())
+}
+
+// Load all execution records
+pub fn load_all_execution_records() -> Result<Vec<ExecutionRecord>, String> {
+    EXECUTION_STORE.with(|store| {
         let store = store.borrow();
-        store.iter().filter_map(|(_, value_bytes)| {
-            candid::decode_one(&value_bytes).ok()
-        }).collect()
+        store.iter().map(|(_, value_bytes)| decode(&value_bytes, "execution record")).collect()
+    })
+}
+
+// Store the spend ledger, replacing any previous snapshot
+// (released reservations must not come back after an upgrade)
+pub fn store_spend_ledger(entries: &[SpendEntry], reservations: &[(u64, SpendEntry)]) -> Result<(), String> {
+    SPEND_STORE.with(|store| -> Result<(), String> {
+        let mut store = store.borrow_mut();
+        let keys: Vec<u64> = store.iter().map(|(k, _)| k).collect();
+        for key in keys {
+            store.remove(&key);
+        }
+        for (index, entry) in entries.iter().enumerate() {
+            let encoded = candid::encode_one(entry)
+                .map_err(|e| format!("Failed to encode spend entry: {}", e))?;
+            store.insert(index as u64, encoded);
+        }
+        Ok(())
+    })?;
+
+    SPEND_RESERVATION_STORE.with(|store| -> Result<(), String> {
+        let mut store = store.borrow_mut();
+        let keys: Vec<u64> = store.iter().map(|(k, _)| k).collect();
+        for key in keys {
+            store.remove(&key);
+        }
+        for (audit_id, entry) in reservations {
+            let encoded = candid::encode_one(entry)
+                .map_err(|e| format!("Failed to encode spend reservation: {}", e))?;
+            store.insert(*audit_id, encoded);
+        }
+        Ok(())
     })
 }
 
-// Store audit entry
-pub fn store_audit_entry(entry: &AuditEntry) -> Result<(), String> {
-    let encoded = candid::encode_one(entry)
-        .map_err(|e| format!("Failed to encode audit entry: {}", e))?;
+// Load the spend ledger (entries, reservations keyed by audit entry id)
+pub fn load_spend_ledger() -> Result<(Vec<SpendEntry>, Vec<SpendReservation>), String> {
+    let entries = SPEND_STORE.with(|store| {
+        let store = store.borrow();
+        store.iter().map(|(_, value_bytes)| decode(&value_bytes, "spend entry")).collect::<Result<Vec<_>, String>>()
+    })?;
+
+    let reservations = SPEND_RESERVATION_STORE.with(|store| {
+        let store = store.borrow();
+        store.iter().map(|(key, value_bytes)| {
+            let entry: SpendEntry = decode(&value_bytes, "spend reservation")?;
+            Ok((key, entry))
+        }).collect::<Result<Vec<_>, String>>()
+    })?;
+
+    Ok((entries, reservations))
+}
+
+// Store policy version
+pub fn store_policy_version(version: &PolicyVersion) -> Result<(), String> {
+    let encoded = candid::encode_one(version)
+        .map_err(|e| format!("Failed to encode policy version: {}", e))?;
 
-    AUDIT_STORE.with(|store| {
-        store.borrow_mut().insert(entry.id, encoded);
+    POLICY_VERSION_STORE.with(|store| {
+        store.borrow_mut().insert(version.version, encoded);
     });
 
     Ok(())
 }
 
-// Load audit entry
-pub fn load_audit_entry(id: u64) -> Option<AuditEntry> {
-    AUDIT_STORE.with(|store| {
-        store.borrow().get(&id).and_then(|bytes| {
-            candid::decode_one(&bytes).ok()
-        })
+// Load all policy versions
+pub fn load_all_policy_versions() -> Result<Vec<PolicyVersion>, String> {
+    POLICY_VERSION_STORE.with(|store| {
+        let store = store.borrow();
+        store.iter().map(|(_, value_bytes)| decode(&value_bytes, "policy version")).collect()
     })
 }
 
-// Load all audit entries
-pub fn load_all_audit_entries() -> Vec<AuditEntry> {
-    AUDIT_STORE.with(|store| {
+// Store agent session (keyed by session principal)
+pub fn store_session(session: &AgentSession) -> Result<(), String> {
+    let key = session.principal.as_slice().to_vec();
+    let encoded = candid::encode_one(session)
+        .map_err(|e| format!("Failed to encode session: {}", e))?;
+
+    SESSION_STORE.with(|store| {
+        store.borrow_mut().insert(key, encoded);
+    });
+
+    Ok(())
+}
+
+// Load all agent sessions
+pub fn load_all_sessions() -> Result<Vec<AgentSession>, String> {
+    SESSION_STORE.with(|store| {
         let store = store.borrow();
-        store.iter().filter_map(|(_, value_bytes)| {
-            candid::decode_one(&value_bytes).ok()
-        }).collect()
+        store.iter().map(|(_, value_bytes)| decode(&value_bytes, "session")).collect()
     })
 }
 
@@ -258,11 +745,31 @@ pub fn clear_all_stable_storage() {
         }
     });
 
-    AUDIT_STORE.with(|store| {
+    EXECUTION_STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let keys: Vec<Vec<u8>> = store.iter().map(|(k, _)| k).collect();
+        for key in keys {
+            store.remove(&key);
+        }
+    });
+
+    let _ = store_spend_ledger(&[], &[]);
+
+    POLICY_VERSION_STORE.with(|store| {
         let mut store = store.borrow_mut();
         let keys: Vec<u64> = store.iter().map(|(k, _)| k).collect();
         for key in keys {
             store.remove(&key);
         }
     });
+
+    let _ = store_custom_roles(&[]);
+
+    SESSION_STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let keys: Vec<Vec<u8>> = store.iter().map(|(k, _)| k).collect();
+        for key in keys {
+            store.remove(&key);
+        }
+    });
 }
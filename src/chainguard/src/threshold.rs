@@ -1,11 +1,18 @@
+use crate::errors::{ChainGuardError, ChainGuardResult};
 use crate::types::*;
 use candid::Principal;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+const DAY_NANOS: u64 = 86_400 * 1_000_000_000;
 
 pub struct ThresholdSigner {
     pending_requests: HashMap<u64, PendingRequest>,
     next_id: u64,
     default_expiry: u64,  // nanoseconds
+    signer_weights: Vec<SignerWeight>,
+    rejection_config: RejectionConfig,
+    signing_rules: SigningRules,
+    executing: HashSet<u64>,  // Time-locked requests whose execution is in flight
 }
 
 impl ThresholdSigner {
@@ -14,14 +21,26 @@ impl ThresholdSigner {
             pending_requests: HashMap::new(),
             next_id: 0,
             default_expiry: 86400 * 1_000_000_000,  // 24 hours in nanoseconds
+            signer_weights: Vec::new(),
+            rejection_config: RejectionConfig::default(),
+            signing_rules: SigningRules::default(),
+            executing: HashSet::new(),
         }
     }
 
+    // required_weight: weight needed for approval (None = required_signatures);
+    // expiry: signing window in nanoseconds (None = default_expiry);
+    // timelock: delay in nanoseconds between approval and execution
+    #[allow(clippy::too_many_arguments)]
     pub fn create_request(
         &mut self,
         action: Action,
         requester: Principal,
         required_signatures: u8,
+        required_weight: Option<u64>,
+        from_roles: Vec<Role>,
+        expiry: Option<u64>,
+        timelock: Option<u64>,
         current_time: u64,
     ) -> PendingRequest {
         let id = self.next_id;
@@ -32,10 +51,18 @@ impl ThresholdSigner {
             action,
             requester,
             created_at: current_time,
-            expires_at: current_time + self.default_expiry,
+            expires_at: current_time.saturating_add(expiry.unwrap_or(self.default_expiry)),
             required_signatures,
+            from_roles,
             collected_signatures: Vec::new(),
             status: RequestStatus::Pending,
+            required_weight: required_weight.unwrap_or(required_signatures as u64),
+            current_weight: 0,
+            rejections: Vec::new(),
+            required_rejections: self.rejection_config.threshold,
+            timelock,
+            executable_at: None,
+            failed_at: None,
         };
 
         self.pending_requests.insert(id, request.clone());
@@ -46,64 +73,229 @@ impl ThresholdSigner {
         &mut self,
         request_id: u64,
         signer: Principal,
+        signer_roles: &[Role],
         current_time: u64,
-    ) -> Result<PendingRequest, String> {
+    ) -> ChainGuardResult<PendingRequest> {
+        let approvals_today = self.pending_requests
+            .get(&request_id)
+            .map(|r| self.approvals_given(&signer, &r.requester, current_time))
+            .unwrap_or(0);
         let request = self.pending_requests
             .get_mut(&request_id)
-            .ok_or("Request not found")?;
+            .ok_or(ChainGuardError::RequestNotFound { id: request_id })?;
 
         // Check if expired
         if current_time > request.expires_at {
-            request.status = RequestStatus::Expired;
-            return Err("Request expired".to_string());
+            if request.status == RequestStatus::Pending {
+                request.status = RequestStatus::Expired;
+            }
+            return Err(ChainGuardError::RequestExpired);
         }
 
-        // Check if already signed by this signer
+        // Check if already signed by this signer; a principal votes once, so
+        // one who rejected cannot sign as well
         if request.collected_signatures.iter().any(|s| s.signer == signer) {
-            return Err("Already signed by this principal".to_string());
+            return Err(ChainGuardError::RequestAlreadySigned);
+        }
+        if request.rejections.iter().any(|r| r.signer == signer) {
+            return Err(ChainGuardError::RequestAlreadyRejected);
         }
 
         // Check status
         if request.status != RequestStatus::Pending {
-            return Err(format!("Request is not pending, status: {:?}", request.status));
+            return Err(ChainGuardError::InvalidRequestStatus {
+                expected: format!("{:?}", RequestStatus::Pending),
+                actual: format!("{:?}", request.status),
+            });
+        }
+
+        // Check signer holds one of the eligible roles
+        if !request.from_roles.is_empty()
+            && !signer_roles.iter().any(|r| request.from_roles.contains(r))
+        {
+            return Err(ChainGuardError::SignerNotEligible { required_roles: request.from_roles.clone() });
+        }
+
+        // Separation of duties: no self-approval, and an optional daily cap per requester
+        if self.signing_rules.exclude_requester && signer == request.requester {
+            return Err(ChainGuardError::RequesterCannotSign);
+        }
+        if let Some(limit) = self.signing_rules.max_daily_approvals_per_requester {
+            if approvals_today >= limit {
+                return Err(ChainGuardError::DailyApprovalLimitReached { limit });
+            }
         }
 
-        // Add signature
+        // Add signature; only the roles the request accepts carry weight
+        let counted_roles: Vec<Role> = signer_roles
+            .iter()
+            .filter(|r| request.from_roles.is_empty() || request.from_roles.contains(r))
+            .cloned()
+            .collect();
+        let weight = Self::weight_of(&self.signer_weights, &signer, &counted_roles);
         request.collected_signatures.push(Signature {
             signer,
             signed_at: current_time,
+            weight,
         });
+        request.current_weight = request.current_weight.saturating_add(weight);
 
-        // Check if threshold reached
-        if request.collected_signatures.len() >= request.required_signatures as usize {
+        // Check if threshold reached; a time-lock delays execution from now
+        if request.current_weight >= request.required_weight {
             request.status = RequestStatus::Approved;
+            request.executable_at = request.timelock.map(|delay| current_time.saturating_add(delay));
         }
 
         Ok(request.clone())
     }
 
+    // Record a signer's rejection; the request is rejected once the quorum is
+    // reached or a veto principal rejects
     pub fn reject_request(
         &mut self,
         request_id: u64,
-        _reason: String,
-    ) -> Result<(), String> {
+        signer: Principal,
+        signer_roles: &[Role],
+        reason: String,
+        current_time: u64,
+    ) -> ChainGuardResult<PendingRequest> {
         let request = self.pending_requests
             .get_mut(&request_id)
-            .ok_or("Request not found")?;
+            .ok_or(ChainGuardError::RequestNotFound { id: request_id })?;
 
-        request.status = RequestStatus::Rejected;
-        Ok(())
+        if current_time > request.expires_at {
+            if request.status == RequestStatus::Pending {
+                request.status = RequestStatus::Expired;
+            }
+            return Err(ChainGuardError::RequestExpired);
+        }
+
+        if request.status != RequestStatus::Pending {
+            return Err(ChainGuardError::InvalidRequestStatus {
+                expected: format!("{:?}", RequestStatus::Pending),
+                actual: format!("{:?}", request.status),
+            });
+        }
+
+        // A principal votes once: a signer cannot also reject (vetoes included)
+        if request.rejections.iter().any(|r| r.signer == signer) {
+            return Err(ChainGuardError::RequestAlreadyRejected);
+        }
+        if request.collected_signatures.iter().any(|s| s.signer == signer) {
+            return Err(ChainGuardError::RequestAlreadySigned);
+        }
+
+        // Vetoes skip the eligible-role check; other rejectors must be eligible signers
+        let veto = self.rejection_config.veto_principals.contains(&signer);
+        if !veto
+            && !request.from_roles.is_empty()
+            && !signer_roles.iter().any(|r| request.from_roles.contains(r))
+        {
+            return Err(ChainGuardError::SignerNotEligible { required_roles: request.from_roles.clone() });
+        }
+
+        request.rejections.push(Rejection {
+            signer,
+            reason,
+            rejected_at: current_time,
+            veto,
+        });
+
+        if veto || request.rejections.len() as u64 >= request.required_rejections {
+            request.status = RequestStatus::Rejected;
+        }
+
+        Ok(request.clone())
     }
 
-    pub fn mark_executed(&mut self, request_id: u64) -> Result<(), String> {
+    pub fn mark_executed(&mut self, request_id: u64) -> ChainGuardResult<()> {
         let request = self.pending_requests
             .get_mut(&request_id)
-            .ok_or("Request not found")?;
+            .ok_or(ChainGuardError::RequestNotFound { id: request_id })?;
 
         request.status = RequestStatus::Executed;
+        self.executing.remove(&request_id);
+        Ok(())
+    }
+
+    // A time-locked execution failed: the request stays Approved so a signer can retry it
+    // through execute_request or an Emergency holder can cancel it; the timer skips it
+    pub fn mark_failed(&mut self, request_id: u64, current_time: u64) -> ChainGuardResult<()> {
+        let request = self.pending_requests
+            .get_mut(&request_id)
+            .ok_or(ChainGuardError::RequestNotFound { id: request_id })?;
+
+        request.failed_at = Some(current_time);
+        self.executing.remove(&request_id);
         Ok(())
     }
 
+    // Claim a time-locked request whose window has passed for execution;
+    // a claimed request cannot be claimed again or cancelled
+    pub fn start_execution(&mut self, request_id: u64, current_time: u64) -> ChainGuardResult<PendingRequest> {
+        let request = self.pending_requests
+            .get(&request_id)
+            .ok_or(ChainGuardError::RequestNotFound { id: request_id })?;
+
+        let executable_at = match (&request.status, request.executable_at) {
+            (RequestStatus::Approved, Some(executable_at)) if !self.executing.contains(&request_id) => executable_at,
+            _ => {
+                return Err(ChainGuardError::InvalidRequestStatus {
+                    expected: "Approved (time-locked)".to_string(),
+                    actual: self.status_label(request),
+                });
+            }
+        };
+        if current_time < executable_at {
+            return Err(ChainGuardError::RequestTimelocked { executable_at });
+        }
+
+        self.executing.insert(request_id);
+        Ok(request.clone())
+    }
+
+    // Cancel a time-locked request before its execution starts
+    pub fn cancel_request(&mut self, request_id: u64) -> ChainGuardResult<PendingRequest> {
+        let request = self.pending_requests
+            .get(&request_id)
+            .ok_or(ChainGuardError::RequestNotFound { id: request_id })?;
+
+        if request.status != RequestStatus::Approved
+            || request.executable_at.is_none()
+            || self.executing.contains(&request_id)
+        {
+            return Err(ChainGuardError::InvalidRequestStatus {
+                expected: "Approved (time-locked)".to_string(),
+                actual: self.status_label(request),
+            });
+        }
+
+        let request = self.pending_requests.get_mut(&request_id).unwrap();
+        request.status = RequestStatus::Cancelled;
+        Ok(request.clone())
+    }
+
+    // Time-locked requests whose window has passed, that are not executing and have not failed
+    pub fn due_requests(&self, current_time: u64) -> Vec<u64> {
+        let mut due: Vec<u64> = self.pending_requests
+            .values()
+            .filter(|r| r.status == RequestStatus::Approved && !self.executing.contains(&r.id))
+            .filter(|r| r.failed_at.is_none())
+            .filter(|r| r.executable_at.is_some_and(|at| current_time >= at))
+            .map(|r| r.id)
+            .collect();
+        due.sort_unstable();
+        due
+    }
+
+    fn status_label(&self, request: &PendingRequest) -> String {
+        if self.executing.contains(&request.id) {
+            "Executing".to_string()
+        } else {
+            format!("{:?}", request.status)
+        }
+    }
+
     pub fn get_pending_requests(&self) -> Vec<PendingRequest> {
         self.pending_requests
             .values()
@@ -112,6 +304,18 @@ impl ThresholdSigner {
             .collect()
     }
 
+    // Approved requests waiting out their time-lock (the ones that can still be cancelled
+    // or retried), soonest first
+    pub fn get_timelocked_requests(&self) -> Vec<PendingRequest> {
+        let mut requests: Vec<PendingRequest> = self.pending_requests
+            .values()
+            .filter(|r| r.status == RequestStatus::Approved && r.executable_at.is_some())
+            .cloned()
+            .collect();
+        requests.sort_by_key(|r| (r.executable_at, r.id));
+        requests
+    }
+
     pub fn get_request(&self, id: u64) -> Option<&PendingRequest> {
         self.pending_requests.get(&id)
     }
@@ -123,13 +327,17 @@ impl ThresholdSigner {
             .unwrap_or(false)
     }
 
-    // Cleanup expired requests
-    pub fn cleanup_expired(&mut self, current_time: u64) {
+    // Mark overdue pending requests as expired; returns their ids in order
+    pub fn cleanup_expired(&mut self, current_time: u64) -> Vec<u64> {
+        let mut expired = Vec::new();
         for request in self.pending_requests.values_mut() {
             if request.status == RequestStatus::Pending && current_time > request.expires_at {
                 request.status = RequestStatus::Expired;
+                expired.push(request.id);
             }
         }
+        expired.sort_unstable();
+        expired
     }
 
     // Restore request from stable memory (for post_upgrade)
@@ -143,6 +351,94 @@ impl ThresholdSigner {
         self.pending_requests.insert(request.id, request);
         Ok(())
     }
+
+    // Set or clear (None) the weight of a principal or role; returns the previous weight
+    pub fn set_signer_weight(&mut self, target: WeightTarget, weight: Option<u64>) -> ChainGuardResult<Option<u64>> {
+        if weight == Some(0) {
+            return Err(ChainGuardError::InvalidConfiguration {
+                reason: "Signer weight must be at least 1".to_string(),
+            });
+        }
+
+        let before = self.signer_weights.iter().find(|w| w.target == target).map(|w| w.weight);
+        self.signer_weights.retain(|w| w.target != target);
+        if let Some(weight) = weight {
+            self.signer_weights.push(SignerWeight { target, weight });
+        }
+        Ok(before)
+    }
+
+    pub fn list_signer_weights(&self) -> Vec<SignerWeight> {
+        self.signer_weights.clone()
+    }
+
+    // A principal's own weight, else the heaviest of its weighted roles, else 1
+    fn weight_of(weights: &[SignerWeight], signer: &Principal, signer_roles: &[Role]) -> u64 {
+        let own = weights.iter().find(|w| w.target == WeightTarget::Principal(*signer));
+        if let Some(own) = own {
+            return own.weight;
+        }
+        weights
+            .iter()
+            .filter(|w| matches!(&w.target, WeightTarget::Role(role) if signer_roles.contains(role)))
+            .map(|w| w.weight)
+            .max()
+            .unwrap_or(1)
+    }
+
+    // Replace the rejection quorum and veto list; applies to requests created afterwards
+    pub fn set_rejection_config(&mut self, config: RejectionConfig) -> ChainGuardResult<RejectionConfig> {
+        if config.threshold == 0 {
+            return Err(ChainGuardError::InvalidConfiguration {
+                reason: "Rejection threshold must be at least 1".to_string(),
+            });
+        }
+        Ok(std::mem::replace(&mut self.rejection_config, config))
+    }
+
+    pub fn rejection_config(&self) -> &RejectionConfig {
+        &self.rejection_config
+    }
+
+    // Replace the separation-of-duties rules; returns the previous rules
+    pub fn set_signing_rules(&mut self, rules: SigningRules) -> ChainGuardResult<SigningRules> {
+        if rules.max_daily_approvals_per_requester == Some(0) {
+            return Err(ChainGuardError::InvalidConfiguration {
+                reason: "Daily approval limit must be at least 1".to_string(),
+            });
+        }
+        Ok(std::mem::replace(&mut self.signing_rules, rules))
+    }
+
+    pub fn signing_rules(&self) -> &SigningRules {
+        &self.signing_rules
+    }
+
+    // Restore the signing rules from stable memory (for post_upgrade)
+    pub fn restore_signing_rules(&mut self, rules: SigningRules) {
+        self.signing_rules = rules;
+    }
+
+    // Signatures a signer gave requests of this requester within the last 24h
+    fn approvals_given(&self, signer: &Principal, requester: &Principal, current_time: u64) -> u64 {
+        let since = current_time.saturating_sub(DAY_NANOS);
+        self.pending_requests
+            .values()
+            .filter(|r| r.requester == *requester)
+            .flat_map(|r| r.collected_signatures.iter())
+            .filter(|s| s.signer == *signer && s.signed_at > since)
+            .count() as u64
+    }
+
+    // Restore the rejection config from stable memory (for post_upgrade)
+    pub fn restore_rejection_config(&mut self, config: RejectionConfig) {
+        self.rejection_config = config;
+    }
+
+    // Restore signer weights from stable memory (for post_upgrade)
+    pub fn restore_signer_weights(&mut self, weights: Vec<SignerWeight>) {
+        self.signer_weights = weights;
+    }
 }
 
 impl Default for ThresholdSigner {
@@ -156,6 +452,8 @@ mod tests {
     use super::*;
     use candid::Principal;
 
+    const DAY: u64 = 86400 * 1_000_000_000;
+
     fn mock_principal(id: u8) -> Principal {
         let mut bytes = [0u8; 29];
         bytes[0] = id;
@@ -177,7 +475,7 @@ mod tests {
         let requester = mock_principal(1);
         let action = mock_action();
 
-        let request = ts.create_request(action.clone(), requester, 2, 1000);
+        let request = ts.create_request(action.clone(), requester, 2, None, vec![], None, None, 1000);
 
         assert_eq!(request.id, 0);
         assert_eq!(request.requester, requester);
@@ -185,7 +483,7 @@ mod tests {
         assert_eq!(request.collected_signatures.len(), 0);
         assert_eq!(request.status, RequestStatus::Pending);
         assert_eq!(request.created_at, 1000);
-        assert_eq!(request.expires_at, 1000 + 86400); // default expiry
+        assert_eq!(request.expires_at, 1000 + DAY); // default expiry
     }
 
     #[test]
@@ -195,11 +493,11 @@ mod tests {
         let signer1 = mock_principal(2);
         let action = mock_action();
 
-        let request = ts.create_request(action, requester, 2, 1000);
+        let request = ts.create_request(action, requester, 2, None, vec![], None, None, 1000);
         let request_id = request.id;
 
         // First signature
-        let result = ts.sign_request(request_id, signer1, 1500);
+        let result = ts.sign_request(request_id, signer1, &[Role::Operator], 1500);
         assert!(result.is_ok());
         let updated = result.unwrap();
         assert_eq!(updated.collected_signatures.len(), 1);
@@ -214,14 +512,14 @@ mod tests {
         let signer2 = mock_principal(3);
         let action = mock_action();
 
-        let request = ts.create_request(action, requester, 2, 1000);
+        let request = ts.create_request(action, requester, 2, None, vec![], None, None, 1000);
         let request_id = request.id;
 
         // First signature
-        ts.sign_request(request_id, signer1, 1500).unwrap();
+        ts.sign_request(request_id, signer1, &[Role::Operator], 1500).unwrap();
 
         // Second signature - threshold reached
-        let result = ts.sign_request(request_id, signer2, 1600);
+        let result = ts.sign_request(request_id, signer2, &[Role::Operator], 1600);
         assert!(result.is_ok());
         let updated = result.unwrap();
         assert_eq!(updated.collected_signatures.len(), 2);
@@ -235,16 +533,16 @@ mod tests {
         let signer = mock_principal(2);
         let action = mock_action();
 
-        let request = ts.create_request(action, requester, 2, 1000);
+        let request = ts.create_request(action, requester, 2, None, vec![], None, None, 1000);
         let request_id = request.id;
 
         // First signature
-        ts.sign_request(request_id, signer, 1500).unwrap();
+        ts.sign_request(request_id, signer, &[Role::Operator], 1500).unwrap();
 
         // Try to sign again with same signer
-        let result = ts.sign_request(request_id, signer, 1600);
+        let result = ts.sign_request(request_id, signer, &[Role::Operator], 1600);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Already signed by this principal");
+        assert_eq!(result.unwrap_err(), ChainGuardError::RequestAlreadySigned);
     }
 
     #[test]
@@ -254,13 +552,13 @@ mod tests {
         let signer = mock_principal(2);
         let action = mock_action();
 
-        let request = ts.create_request(action, requester, 2, 1000);
+        let request = ts.create_request(action, requester, 2, None, vec![], None, None, 1000);
         let request_id = request.id;
 
         // Try to sign after expiry
-        let result = ts.sign_request(request_id, signer, 1000 + 86400 + 1);
+        let result = ts.sign_request(request_id, signer, &[Role::Operator], 1000 + DAY + 1);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Request expired");
+        assert_eq!(result.unwrap_err(), ChainGuardError::RequestExpired);
 
         // Check status was updated
         let req = ts.get_request(request_id).unwrap();
@@ -272,9 +570,9 @@ mod tests {
         let mut ts = ThresholdSigner::new();
         let signer = mock_principal(1);
 
-        let result = ts.sign_request(999, signer, 1000);
+        let result = ts.sign_request(999, signer, &[Role::Operator], 1000);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Request not found");
+        assert_eq!(result.unwrap_err(), ChainGuardError::RequestNotFound { id: 999 });
     }
 
     #[test]
@@ -283,14 +581,16 @@ mod tests {
         let requester = mock_principal(1);
         let action = mock_action();
 
-        let request = ts.create_request(action, requester, 2, 1000);
+        let request = ts.create_request(action, requester, 2, None, vec![], None, None, 1000);
         let request_id = request.id;
 
-        let result = ts.reject_request(request_id, "Security concern".to_string());
+        let result = ts.reject_request(request_id, mock_principal(2), &[Role::Operator], "Security concern".to_string(), 1500);
         assert!(result.is_ok());
 
         let req = ts.get_request(request_id).unwrap();
         assert_eq!(req.status, RequestStatus::Rejected);
+        assert_eq!(req.rejections[0].signer, mock_principal(2));
+        assert_eq!(req.rejections[0].reason, "Security concern");
     }
 
     #[test]
@@ -299,7 +599,7 @@ mod tests {
         let requester = mock_principal(1);
         let action = mock_action();
 
-        let request = ts.create_request(action, requester, 2, 1000);
+        let request = ts.create_request(action, requester, 2, None, vec![], None, None, 1000);
         let request_id = request.id;
 
         let result = ts.mark_executed(request_id);
@@ -316,9 +616,9 @@ mod tests {
         let action = mock_action();
 
         // Create multiple requests
-        ts.create_request(action.clone(), requester, 2, 1000);
-        ts.create_request(action.clone(), requester, 2, 2000);
-        ts.create_request(action.clone(), requester, 2, 3000);
+        ts.create_request(action.clone(), requester, 2, None, vec![], None, None, 1000);
+        ts.create_request(action.clone(), requester, 2, None, vec![], None, None, 2000);
+        ts.create_request(action.clone(), requester, 2, None, vec![], None, None, 3000);
 
         // Mark one as executed
         ts.mark_executed(1).unwrap();
@@ -336,16 +636,16 @@ mod tests {
         let signer2 = mock_principal(3);
         let action = mock_action();
 
-        let request = ts.create_request(action, requester, 2, 1000);
+        let request = ts.create_request(action, requester, 2, None, vec![], None, None, 1000);
         let request_id = request.id;
 
         assert!(!ts.is_approved(request_id));
 
         // Add signatures
-        ts.sign_request(request_id, signer1, 1500).unwrap();
+        ts.sign_request(request_id, signer1, &[Role::Operator], 1500).unwrap();
         assert!(!ts.is_approved(request_id));
 
-        ts.sign_request(request_id, signer2, 1600).unwrap();
+        ts.sign_request(request_id, signer2, &[Role::Operator], 1600).unwrap();
         assert!(ts.is_approved(request_id));
     }
 
@@ -356,12 +656,12 @@ mod tests {
         let action = mock_action();
 
         // Create requests at different times
-        ts.create_request(action.clone(), requester, 2, 1000);
-        ts.create_request(action.clone(), requester, 2, 2000);
-        ts.create_request(action.clone(), requester, 2, 3000);
+        ts.create_request(action.clone(), requester, 2, None, vec![], None, None, 1000);
+        ts.create_request(action.clone(), requester, 2, None, vec![], None, None, 2000);
+        ts.create_request(action.clone(), requester, 2, None, vec![], None, None, 3000);
 
-        // Cleanup at time that expires first two
-        ts.cleanup_expired(1000 + 86400 + 1);
+        // Cleanup at time that expires only the first
+        assert_eq!(ts.cleanup_expired(1000 + DAY + 1), vec![0]);
 
         let req0 = ts.get_request(0).unwrap();
         let req1 = ts.get_request(1).unwrap();
@@ -370,6 +670,28 @@ mod tests {
         assert_eq!(req0.status, RequestStatus::Expired);
         assert_eq!(req1.status, RequestStatus::Pending);
         assert_eq!(req2.status, RequestStatus::Pending);
+
+        // Already expired requests are not reported again
+        assert_eq!(ts.cleanup_expired(3000 + DAY + 1), vec![1, 2]);
+        assert!(ts.cleanup_expired(4000 + DAY).is_empty());
+    }
+
+    #[test]
+    fn test_request_expiry_override() {
+        let mut ts = ThresholdSigner::new();
+        let requester = mock_principal(1);
+        let hour = 3600 * 1_000_000_000;
+
+        let short = ts.create_request(mock_action(), requester, 2, None, vec![], Some(hour), None, 1000);
+        let default = ts.create_request(mock_action(), requester, 2, None, vec![], None, None, 1000);
+        assert_eq!(short.expires_at, 1000 + hour);
+        assert_eq!(default.expires_at, 1000 + DAY);
+
+        assert_eq!(
+            ts.sign_request(short.id, mock_principal(2), &[], 1000 + hour + 1).unwrap_err(),
+            ChainGuardError::RequestExpired
+        );
+        assert!(ts.sign_request(default.id, mock_principal(2), &[], 1000 + hour + 1).is_ok());
     }
 
     #[test]
@@ -378,15 +700,38 @@ mod tests {
         let requester = mock_principal(1);
         let action = mock_action();
 
-        let req1 = ts.create_request(action.clone(), requester, 2, 1000);
-        let req2 = ts.create_request(action.clone(), requester, 2, 2000);
-        let req3 = ts.create_request(action, requester, 2, 3000);
+        let req1 = ts.create_request(action.clone(), requester, 2, None, vec![], None, None, 1000);
+        let req2 = ts.create_request(action.clone(), requester, 2, None, vec![], None, None, 2000);
+        let req3 = ts.create_request(action, requester, 2, None, vec![], None, None, 3000);
 
         assert_eq!(req1.id, 0);
         assert_eq!(req2.id, 1);
         assert_eq!(req3.id, 2);
     }
 
+    #[test]
+    fn test_sign_request_role_restriction() {
+        let mut ts = ThresholdSigner::new();
+        let requester = mock_principal(1);
+        let owner = mock_principal(2);
+        let operator = mock_principal(3);
+
+        let request = ts.create_request(mock_action(), requester, 1, None, vec![Role::Owner], None, None, 1000);
+        assert_eq!(request.from_roles, vec![Role::Owner]);
+
+        // Operator is not eligible
+        let result = ts.sign_request(request.id, operator, &[Role::Operator], 1500);
+        assert_eq!(
+            result.unwrap_err(),
+            ChainGuardError::SignerNotEligible { required_roles: vec![Role::Owner] }
+        );
+        assert_eq!(ts.get_request(request.id).unwrap().collected_signatures.len(), 0);
+
+        // Owner (holding any eligible role) can sign
+        let result = ts.sign_request(request.id, owner, &[Role::Operator, Role::Owner], 1600);
+        assert_eq!(result.unwrap().status, RequestStatus::Approved);
+    }
+
     #[test]
     fn test_sign_after_approval() {
         let mut ts = ThresholdSigner::new();
@@ -396,16 +741,296 @@ mod tests {
         let signer3 = mock_principal(4);
         let action = mock_action();
 
-        let request = ts.create_request(action, requester, 2, 1000);
+        let request = ts.create_request(action, requester, 2, None, vec![], None, None, 1000);
         let request_id = request.id;
 
         // Reach threshold
-        ts.sign_request(request_id, signer1, 1500).unwrap();
-        ts.sign_request(request_id, signer2, 1600).unwrap();
+        ts.sign_request(request_id, signer1, &[Role::Operator], 1500).unwrap();
+        ts.sign_request(request_id, signer2, &[Role::Operator], 1600).unwrap();
 
         // Try to sign after approval
-        let result = ts.sign_request(request_id, signer3, 1700);
+        let result = ts.sign_request(request_id, signer3, &[Role::Operator], 1700);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not pending"));
+        assert!(matches!(result.unwrap_err(), ChainGuardError::InvalidRequestStatus { .. }));
+    }
+
+    #[test]
+    fn test_weighted_signatures() {
+        let mut ts = ThresholdSigner::new();
+        let cfo = mock_principal(2);
+        let operator1 = mock_principal(3);
+        let operator2 = mock_principal(4);
+        ts.set_signer_weight(WeightTarget::Principal(cfo), Some(3)).unwrap();
+        ts.set_signer_weight(WeightTarget::Role(Role::Operator), Some(1)).unwrap();
+
+        // Two operators do not reach a weight of 3
+        let request = ts.create_request(mock_action(), mock_principal(1), 3, None, vec![], None, None, 1000);
+        assert_eq!(request.required_weight, 3);
+        ts.sign_request(request.id, operator1, &[Role::Operator], 1500).unwrap();
+        let updated = ts.sign_request(request.id, operator2, &[Role::Operator], 1600).unwrap();
+        assert_eq!(updated.current_weight, 2);
+        assert_eq!(updated.status, RequestStatus::Pending);
+
+        // The CFO alone does
+        let request = ts.create_request(mock_action(), mock_principal(1), 3, None, vec![], None, None, 1000);
+        let updated = ts.sign_request(request.id, cfo, &[Role::Operator], 1500).unwrap();
+        assert_eq!(updated.collected_signatures[0].weight, 3);
+        assert_eq!(updated.current_weight, 3);
+        assert_eq!(updated.status, RequestStatus::Approved);
+    }
+
+    #[test]
+    fn test_policy_required_weight_and_eligible_roles() {
+        let mut ts = ThresholdSigner::new();
+        let admin = mock_principal(2);
+        let operator = mock_principal(3);
+        ts.set_signer_weight(WeightTarget::Role(Role::Owner), Some(5)).unwrap();
+        ts.set_signer_weight(WeightTarget::Role(Role::Operator), Some(2)).unwrap();
+
+        // A policy weight overrides the signature count
+        let request = ts.create_request(mock_action(), mock_principal(1), 1, Some(4), vec![Role::Operator], None, None, 1000);
+        assert_eq!(request.required_weight, 4);
+
+        // An Owner signing an Operator-only request carries the Operator weight only
+        let updated = ts.sign_request(request.id, admin, &[Role::Operator, Role::Owner], 1500).unwrap();
+        assert_eq!(updated.collected_signatures[0].weight, 2);
+        assert_eq!(updated.status, RequestStatus::Pending);
+        let updated = ts.sign_request(request.id, operator, &[Role::Operator], 1600).unwrap();
+        assert_eq!(updated.current_weight, 4);
+        assert_eq!(updated.status, RequestStatus::Approved);
+    }
+
+    #[test]
+    fn test_signer_weight_resolution() {
+        let mut ts = ThresholdSigner::new();
+        let signer = mock_principal(2);
+        ts.set_signer_weight(WeightTarget::Role(Role::Operator), Some(2)).unwrap();
+        ts.set_signer_weight(WeightTarget::Role(Role::Owner), Some(5)).unwrap();
+
+        // Heaviest role wins; unweighted signers count 1; a principal's own weight wins over roles
+        assert_eq!(ThresholdSigner::weight_of(&ts.signer_weights, &signer, &[Role::Operator, Role::Owner]), 5);
+        assert_eq!(ThresholdSigner::weight_of(&ts.signer_weights, &signer, &[Role::Viewer]), 1);
+        ts.set_signer_weight(WeightTarget::Principal(signer), Some(1)).unwrap();
+        assert_eq!(ThresholdSigner::weight_of(&ts.signer_weights, &signer, &[Role::Owner]), 1);
+
+        // Clearing returns the previous weight; zero is rejected
+        assert_eq!(ts.set_signer_weight(WeightTarget::Role(Role::Owner), None), Ok(Some(5)));
+        assert_eq!(ts.list_signer_weights().len(), 2);
+        assert!(ts.set_signer_weight(WeightTarget::Role(Role::Owner), Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_rejection_quorum() {
+        let mut ts = ThresholdSigner::new();
+        ts.set_rejection_config(RejectionConfig { threshold: 2, veto_principals: vec![] }).unwrap();
+        let request = ts.create_request(mock_action(), mock_principal(1), 2, None, vec![], None, None, 1000);
+        assert_eq!(request.required_rejections, 2);
+
+        let first = ts.reject_request(request.id, mock_principal(2), &[Role::Operator], "Too large".to_string(), 1500).unwrap();
+        assert_eq!(first.status, RequestStatus::Pending);
+        assert_eq!(
+            ts.reject_request(request.id, mock_principal(2), &[Role::Operator], "Again".to_string(), 1550).unwrap_err(),
+            ChainGuardError::RequestAlreadyRejected
+        );
+
+        let second = ts.reject_request(request.id, mock_principal(3), &[Role::Operator], "Unknown recipient".to_string(), 1600).unwrap();
+        assert_eq!(second.status, RequestStatus::Rejected);
+        let reasons: Vec<&str> = second.rejections.iter().map(|r| r.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["Too large", "Unknown recipient"]);
+
+        // Rejected requests can no longer be signed or rejected
+        assert!(ts.sign_request(request.id, mock_principal(4), &[Role::Operator], 1700).is_err());
+        assert!(ts.reject_request(request.id, mock_principal(4), &[Role::Operator], "Late".to_string(), 1700).is_err());
+    }
+
+    #[test]
+    fn test_one_vote_per_principal() {
+        let mut ts = ThresholdSigner::new();
+        let cfo = mock_principal(9);
+        ts.set_rejection_config(RejectionConfig { threshold: 2, veto_principals: vec![cfo] }).unwrap();
+        let request = ts.create_request(mock_action(), mock_principal(1), 3, None, vec![], None, None, 1000);
+
+        // A signer cannot also reject, even with a veto
+        ts.sign_request(request.id, mock_principal(2), &[Role::Operator], 1500).unwrap();
+        ts.sign_request(request.id, cfo, &[Role::Operator], 1500).unwrap();
+        assert_eq!(
+            ts.reject_request(request.id, mock_principal(2), &[Role::Operator], "No".to_string(), 1600).unwrap_err(),
+            ChainGuardError::RequestAlreadySigned
+        );
+        assert_eq!(
+            ts.reject_request(request.id, cfo, &[], "Veto".to_string(), 1600).unwrap_err(),
+            ChainGuardError::RequestAlreadySigned
+        );
+
+        // A rejector cannot also sign
+        ts.reject_request(request.id, mock_principal(3), &[Role::Operator], "No".to_string(), 1600).unwrap();
+        assert_eq!(
+            ts.sign_request(request.id, mock_principal(3), &[Role::Operator], 1700).unwrap_err(),
+            ChainGuardError::RequestAlreadyRejected
+        );
+
+        let request = ts.get_request(request.id).unwrap();
+        assert_eq!(request.current_weight, 2);
+        assert_eq!(request.rejections.len(), 1);
+        assert_eq!(request.status, RequestStatus::Pending);
+    }
+
+    #[test]
+    fn test_veto_rejection() {
+        let mut ts = ThresholdSigner::new();
+        let cfo = mock_principal(9);
+        ts.set_rejection_config(RejectionConfig { threshold: 3, veto_principals: vec![cfo] }).unwrap();
+        let request = ts.create_request(mock_action(), mock_principal(1), 2, None, vec![Role::Owner], None, None, 1000);
+
+        // Non-eligible signers cannot reject, but a veto principal can, alone
+        assert!(matches!(
+            ts.reject_request(request.id, mock_principal(2), &[Role::Operator], "No".to_string(), 1500),
+            Err(ChainGuardError::SignerNotEligible { .. })
+        ));
+        let vetoed = ts.reject_request(request.id, cfo, &[], "Veto".to_string(), 1500).unwrap();
+        assert!(vetoed.rejections[0].veto);
+        assert_eq!(vetoed.status, RequestStatus::Rejected);
+
+        assert!(ts.set_rejection_config(RejectionConfig { threshold: 0, veto_principals: vec![] }).is_err());
+    }
+
+    #[test]
+    fn test_requester_cannot_sign() {
+        let mut ts = ThresholdSigner::new();
+        let requester = mock_principal(1);
+        let request = ts.create_request(mock_action(), requester, 2, None, vec![], None, None, 1000);
+
+        // Excluded by default
+        assert_eq!(
+            ts.sign_request(request.id, requester, &[Role::Operator], 1500).unwrap_err(),
+            ChainGuardError::RequesterCannotSign
+        );
+
+        ts.set_signing_rules(SigningRules { exclude_requester: false, max_daily_approvals_per_requester: None }).unwrap();
+        assert!(ts.sign_request(request.id, requester, &[Role::Operator], 1500).is_ok());
+    }
+
+    #[test]
+    fn test_daily_approval_limit() {
+        let mut ts = ThresholdSigner::new();
+        let requester = mock_principal(1);
+        let signer = mock_principal(2);
+        let before = ts.set_signing_rules(SigningRules { exclude_requester: true, max_daily_approvals_per_requester: Some(2) }).unwrap();
+        assert_eq!(before, SigningRules::default());
+
+        for _ in 0..2 {
+            let request = ts.create_request(mock_action(), requester, 2, None, vec![], None, None, 1000);
+            ts.sign_request(request.id, signer, &[Role::Operator], 1500).unwrap();
+        }
+        let third = ts.create_request(mock_action(), requester, 2, None, vec![], None, None, 1000);
+        assert_eq!(
+            ts.sign_request(third.id, signer, &[Role::Operator], 1600).unwrap_err(),
+            ChainGuardError::DailyApprovalLimitReached { limit: 2 }
+        );
+
+        // Other requesters are counted separately, and the window rolls over
+        let other = ts.create_request(mock_action(), mock_principal(3), 2, None, vec![], None, None, 1000);
+        assert!(ts.sign_request(other.id, signer, &[Role::Operator], 1600).is_ok());
+        let late = ts.create_request(mock_action(), requester, 2, None, vec![], Some(2 * DAY), None, 1000);
+        assert!(ts.sign_request(late.id, signer, &[Role::Operator], 1500 + DAY + 1).is_ok());
+
+        assert!(ts.set_signing_rules(SigningRules { exclude_requester: true, max_daily_approvals_per_requester: Some(0) }).is_err());
+    }
+
+    #[test]
+    fn test_timelocked_execution() {
+        let mut ts = ThresholdSigner::new();
+        let hour = 3600 * 1_000_000_000;
+        let request = ts.create_request(mock_action(), mock_principal(1), 1, None, vec![], None, Some(6 * hour), 1000);
+
+        // Approval starts the time-lock instead of releasing the action
+        let approved = ts.sign_request(request.id, mock_principal(2), &[Role::Operator], 2000).unwrap();
+        assert_eq!(approved.status, RequestStatus::Approved);
+        assert_eq!(approved.executable_at, Some(2000 + 6 * hour));
+        assert!(ts.due_requests(2000 + hour).is_empty());
+        assert_eq!(
+            ts.start_execution(request.id, 2000 + hour).unwrap_err(),
+            ChainGuardError::RequestTimelocked { executable_at: 2000 + 6 * hour }
+        );
+
+        // Once claimed it runs only once and can no longer be cancelled
+        assert_eq!(ts.due_requests(2000 + 6 * hour), vec![request.id]);
+        ts.start_execution(request.id, 2000 + 6 * hour).unwrap();
+        assert!(ts.due_requests(2000 + 6 * hour).is_empty());
+        assert!(ts.start_execution(request.id, 2000 + 6 * hour).is_err());
+        assert!(ts.cancel_request(request.id).is_err());
+
+        ts.mark_executed(request.id).unwrap();
+        assert_eq!(ts.get_request(request.id).unwrap().status, RequestStatus::Executed);
+    }
+
+    #[test]
+    fn test_failed_timelocked_execution_can_be_retried() {
+        let mut ts = ThresholdSigner::new();
+        let hour = 3600 * 1_000_000_000;
+        let request = ts.create_request(mock_action(), mock_principal(1), 1, None, vec![], None, Some(hour), 1000);
+        ts.sign_request(request.id, mock_principal(2), &[Role::Operator], 2000).unwrap();
+
+        // A failed attempt leaves the request Approved and releases the claim
+        ts.start_execution(request.id, 2000 + hour).unwrap();
+        ts.mark_failed(request.id, 2000 + hour).unwrap();
+        let failed = ts.get_request(request.id).unwrap();
+        assert_eq!(failed.status, RequestStatus::Approved);
+        assert_eq!(failed.failed_at, Some(2000 + hour));
+
+        // The timer leaves it alone; a manual retry can claim it again, or it can be cancelled
+        assert!(ts.due_requests(2000 + 2 * hour).is_empty());
+        assert_eq!(ts.get_timelocked_requests().len(), 1);
+        ts.start_execution(request.id, 2000 + 2 * hour).unwrap();
+        ts.mark_failed(request.id, 2000 + 2 * hour).unwrap();
+        assert_eq!(ts.cancel_request(request.id).unwrap().status, RequestStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_cancel_timelocked_request() {
+        let mut ts = ThresholdSigner::new();
+        let hour = 3600 * 1_000_000_000;
+
+        // Requests without a time-lock cannot be cancelled or executed through the time-lock
+        let immediate = ts.create_request(mock_action(), mock_principal(1), 1, None, vec![], None, None, 1000);
+        let approved = ts.sign_request(immediate.id, mock_principal(2), &[Role::Operator], 1500).unwrap();
+        assert_eq!(approved.executable_at, None);
+        assert!(ts.cancel_request(immediate.id).is_err());
+        assert!(ts.start_execution(immediate.id, 1500).is_err());
+
+        let request = ts.create_request(mock_action(), mock_principal(1), 1, None, vec![], None, Some(hour), 1000);
+        assert!(ts.cancel_request(request.id).is_err()); // still pending
+        ts.sign_request(request.id, mock_principal(2), &[Role::Operator], 1500).unwrap();
+
+        let cancelled = ts.cancel_request(request.id).unwrap();
+        assert_eq!(cancelled.status, RequestStatus::Cancelled);
+        assert!(ts.due_requests(1500 + hour).is_empty());
+        assert!(ts.start_execution(request.id, 1500 + hour).is_err());
+    }
+
+    #[test]
+    fn test_list_timelocked_requests() {
+        let mut ts = ThresholdSigner::new();
+        let hour = 3600 * 1_000_000_000;
+
+        let pending = ts.create_request(mock_action(), mock_principal(1), 2, None, vec![], None, Some(hour), 1000);
+        let immediate = ts.create_request(mock_action(), mock_principal(1), 1, None, vec![], None, None, 1000);
+        let later = ts.create_request(mock_action(), mock_principal(1), 1, None, vec![], None, Some(2 * hour), 1000);
+        let sooner = ts.create_request(mock_action(), mock_principal(1), 1, None, vec![], None, Some(hour), 1000);
+        ts.sign_request(pending.id, mock_principal(2), &[Role::Operator], 1500).unwrap();
+        ts.sign_request(immediate.id, mock_principal(2), &[Role::Operator], 1500).unwrap();
+        ts.sign_request(later.id, mock_principal(2), &[Role::Operator], 1500).unwrap();
+        ts.sign_request(sooner.id, mock_principal(2), &[Role::Operator], 1600).unwrap();
+
+        // Only approved time-locked requests are listed, soonest first; pending ones stay in get_pending_requests
+        let ids: Vec<u64> = ts.get_timelocked_requests().iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![sooner.id, later.id]);
+        assert_eq!(ts.get_pending_requests().len(), 1);
+
+        // Cancelled and executed requests drop off the list
+        ts.cancel_request(sooner.id).unwrap();
+        ts.start_execution(later.id, 1500 + 2 * hour).unwrap();
+        ts.mark_executed(later.id).unwrap();
+        assert!(ts.get_timelocked_requests().is_empty());
     }
 }
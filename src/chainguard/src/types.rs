@@ -1,6 +1,8 @@
 use candid::{CandidType, Deserialize, Principal};
 use serde::Serialize;
 
+use crate::errors::ChainGuardError;
+
 // ============== ROLES & PERMISSIONS ==============
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
@@ -8,6 +10,7 @@ pub enum Role {
     Owner,
     Operator,
     Viewer,
+    Custom(String),  // Admin-defined role, see RoleDefinition
 }
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
@@ -19,40 +22,169 @@ pub enum Permission {
     Emergency,      // Can pause/resume
 }
 
-#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+// Named permission set; built-in roles are listed with their fixed permissions
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct RoleDefinition {
+    pub role: Role,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct RoleAssignment {
     pub principal: Principal,
     pub role: Role,
     pub assigned_at: u64,
     pub assigned_by: Principal,
+    pub expires_at: Option<u64>,  // None = permanent grant
+}
+
+impl RoleAssignment {
+    pub fn is_active(&self, current_time: u64) -> bool {
+        self.expires_at.is_none_or(|expires_at| current_time < expires_at)
+    }
+}
+
+// Pending two-step ownership transfer
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct OwnershipProposal {
+    pub proposed_owner: Principal,
+    pub proposed_by: Principal,
+    pub proposed_at: u64,
 }
 
 // ============== POLICIES ==============
 
-#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct Policy {
+    pub id: Option<u64>,  // Assigned by ChainGuard on add; never reused
     pub name: String,
     pub conditions: Vec<Condition>,
     pub action: PolicyAction,
     pub priority: u32,  // Lower = higher priority
+    pub utc_offset_minutes: Option<i32>,  // Local time offset for TimeWindow (default UTC)
 }
 
-#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub enum Condition {
     MaxAmount(u64),                    // Max amount per transaction (for Allow/RequireThreshold)
     MinAmount(u64),                    // Min amount per transaction (for Deny)
     DailyLimit(u64),                   // Max daily volume
     AllowedTokens(Vec<String>),        // Whitelist of token addresses
     AllowedChains(Vec<String>),        // Whitelist of chains
-    TimeWindow { start: u64, end: u64 }, // Allowed hours (UTC)
+    TimeWindow { start: u64, end: u64, days: Option<u8> }, // Allowed hours [start, end), wraps past midnight if start > end; days bitmask (bit 0 = Monday)
     Cooldown(u64),                     // Seconds between operations
+    AllowedRecipients(Vec<String>),    // Whitelist of Transfer recipients (case-insensitive)
+    DeniedRecipients(Vec<String>),     // Blacklist of Transfer recipients (case-insensitive)
+    AllowedSpenders(Vec<String>),      // Whitelist of ApproveToken spenders (case-insensitive)
+    All(Vec<Condition>),               // Every nested condition must match
+    Any(Vec<Condition>),               // At least one nested condition must match
+    Not(Box<Condition>),               // Nested condition must not match
 }
 
-#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub enum PolicyAction {
     Allow,
     Deny,
-    RequireThreshold { required: u8, from_roles: Vec<Role> },
+    // expiry_seconds: how long signatures are collected (None = the 24h default)
+    // timelock_seconds: delay between approval and execution (None = execute on approval)
+    // required_weight: signer weight needed for approval (None = one per required signature)
+    RequireThreshold {
+        required: u8,
+        from_roles: Vec<Role>,
+        expiry_seconds: Option<u64>,
+        timelock_seconds: Option<u64>,
+        required_weight: Option<u64>,
+    },
+}
+
+// Last successful execution of an action type under a policy, used by Cooldown
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ExecutionRecord {
+    pub requester: Principal,
+    pub policy_id: u64,
+    pub action_type: String,
+    pub executed_at: u64,
+}
+
+// ============== POLICY HISTORY ==============
+
+// What produced a policy-set version
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum PolicyChange {
+    Initialized,
+    Added(u64),       // Policy ID
+    Updated(u64),     // Policy ID
+    Removed(u64),     // Policy ID
+    RolledBack(u64),  // Version restored
+}
+
+// Immutable snapshot of the full policy set after a change
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct PolicyVersion {
+    pub version: u64,
+    pub previous_version: Option<u64>,
+    pub author: Principal,
+    pub timestamp: u64,
+    pub change: PolicyChange,
+    pub policies: Vec<Policy>,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct PolicyVersionSummary {
+    pub version: u64,
+    pub previous_version: Option<u64>,
+    pub author: Principal,
+    pub timestamp: u64,
+    pub change: PolicyChange,
+    pub policy_count: u64,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct PolicyModification {
+    pub id: u64,
+    pub before: Policy,
+    pub after: Policy,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct PolicyDiff {
+    pub from_version: u64,
+    pub to_version: u64,
+    pub added: Vec<Policy>,
+    pub removed: Vec<Policy>,
+    pub modified: Vec<PolicyModification>,
+}
+
+// ============== POLICY DRY-RUN ==============
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct ActionExplanation {
+    pub result: PolicyResult,             // Same decision request_action would reach
+    pub policies: Vec<PolicyTrace>,       // Every policy, in priority order
+    pub requester: Principal,
+    pub daily_spent: u64,
+    pub evaluated_at: u64,
+    pub paused: bool,
+    pub has_execute_permission: bool,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct PolicyTrace {
+    pub id: u64,
+    pub name: String,
+    pub priority: u32,
+    pub action: PolicyAction,
+    pub matched: bool,
+    pub conditions: Vec<ConditionTrace>,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct ConditionTrace {
+    pub condition: String,
+    pub passed: bool,
+    pub actual: String,
+    pub expected: String,
+    pub nested: Vec<ConditionTrace>,  // For All/Any/Not
 }
 
 // ============== ACTIONS ==============
@@ -91,14 +223,76 @@ pub struct PendingRequest {
     pub created_at: u64,
     pub expires_at: u64,
     pub required_signatures: u8,
+    pub from_roles: Vec<Role>,  // Roles eligible to sign (empty = any signer)
     pub collected_signatures: Vec<Signature>,
     pub status: RequestStatus,
+    pub required_weight: u64,  // Approved once current_weight reaches this
+    pub current_weight: u64,   // Sum of the collected signatures' weights
+    pub rejections: Vec<Rejection>,
+    pub required_rejections: u64,  // Rejected once this many signers reject (a veto rejects at once)
+    pub timelock: Option<u64>,       // Nanoseconds between approval and execution
+    pub executable_at: Option<u64>,  // Set on approval of a time-locked request
+    pub failed_at: Option<u64>,      // Last failed time-locked execution; retried only through execute_request
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct Rejection {
+    pub signer: Principal,
+    pub reason: String,
+    pub rejected_at: u64,
+    pub veto: bool,
+}
+
+// How many signer rejections reject a request, and who can reject one alone
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct RejectionConfig {
+    pub threshold: u64,
+    pub veto_principals: Vec<Principal>,
+}
+
+impl Default for RejectionConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 1,
+            veto_principals: Vec::new(),
+        }
+    }
+}
+
+// Separation of duties between a request's requester and its signers
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct SigningRules {
+    pub exclude_requester: bool,                  // Requesters cannot sign their own requests
+    pub max_daily_approvals_per_requester: Option<u64>,  // Signatures one signer may give one requester per rolling 24h
+}
+
+impl Default for SigningRules {
+    fn default() -> Self {
+        Self {
+            exclude_requester: true,
+            max_daily_approvals_per_requester: None,
+        }
+    }
 }
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
 pub struct Signature {
     pub signer: Principal,
     pub signed_at: u64,
+    pub weight: u64,  // Signer's weight when the signature was given
+}
+
+// Vote weight of a principal or of everyone holding a role (signers without one weigh 1)
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct SignerWeight {
+    pub target: WeightTarget,
+    pub weight: u64,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum WeightTarget {
+    Principal(Principal),
+    Role(Role),
 }
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
@@ -108,6 +302,7 @@ pub enum RequestStatus {
     Executed,
     Expired,
     Rejected,
+    Cancelled,  // Cancelled by an Emergency holder during its time-lock
 }
 
 // ============== AUDIT LOG ==============
@@ -116,18 +311,127 @@ pub enum RequestStatus {
 pub struct AuditEntry {
     pub id: u64,
     pub timestamp: u64,
+    pub caller: Principal,  // Requester for actions; the canister itself for timer events
+    pub event: AuditEvent,
+    pub prev_hash: Vec<u8>,  // Hash of the previous entry (32 zero bytes for the first)
+    pub hash: Vec<u8>,       // SHA-256 over prev_hash and this entry's contents
+}
+
+impl AuditEntry {
+    // Action details, if this entry records an action request
+    pub fn action(&self) -> Option<&ActionEvent> {
+        match &self.event {
+            AuditEvent::ActionRequested(action) => Some(action),
+            _ => None,
+        }
+    }
+}
+
+// Action request as evaluated by the policy engine
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct ActionEvent {
     pub action_type: String,
     pub action_params: String,  // JSON serialized
-    pub requester: Principal,
     pub policy_result: PolicyResult,
     pub threshold_request_id: Option<u64>,
-    pub execution_result: Option<ExecutionResult>,
+    pub execution_result: Option<ExecutionResult>,  // Only on entries recorded before ActionExecuted
+    pub policy_version: Option<u64>,  // Policy-set version that decided this entry
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub enum AuditEvent {
+    // Agent actions
+    ActionRequested(ActionEvent),
+    ActionExecuted { audit_id: u64, result: ExecutionResult },
+
+    // Signers
+    RequestSigned {
+        request_id: u64,
+        signatures: u64,
+        required: u8,
+        status: RequestStatus,
+        current_weight: Option<u64>,   // None on entries recorded before weighted voting
+        required_weight: Option<u64>,
+        executable_at: Option<u64>,    // Set when the approval starts a time-lock
+    },
+    RequestRejected {
+        request_id: u64,
+        reason: String,
+        rejections: Option<u64>,   // None on entries recorded before rejection quorums
+        required_rejections: Option<u64>,
+        veto: Option<bool>,
+        status: Option<RequestStatus>,
+    },
+    RequestExpired { request_id: u64 },
+    RequestCancelled { request_id: u64, reason: String },
+    RejectionConfigChanged { before: RejectionConfig, after: RejectionConfig },
+    SigningRulesChanged { before: SigningRules, after: SigningRules },
+    SignerWeightChanged { target: WeightTarget, before: Option<u64>, after: Option<u64> },
+
+    // Roles
+    RoleAssigned { before: Option<RoleAssignment>, after: RoleAssignment },
+    RoleRevoked { before: RoleAssignment },
+    RoleExpired { before: RoleAssignment },
+    RoleDefinitionChanged { name: String, before: Option<Vec<Permission>>, after: Option<Vec<Permission>> },
+    OwnershipProposed { proposal: OwnershipProposal },
+    OwnershipProposalCancelled { proposal: OwnershipProposal },
+    OwnershipTransferred { proposal: OwnershipProposal },
+
+    // Policies (version = policy-set version after the change)
+    PolicyAdded { version: u64, after: Policy },
+    PolicyUpdated { version: u64, before: Policy, after: Policy },
+    PolicyRemoved { version: u64, before: Policy },
+    PoliciesRolledBack { version: u64, restored_version: u64, before: Vec<Policy>, after: Vec<Policy> },
+
+    // Configuration and emergency controls
+    Initialized { config: ChainGuardConfig },
+    Paused,
+    Resumed,
+
+    // Agent sessions
+    SessionCreated { before: Option<AgentSession>, after: AgentSession },
+    SessionRevoked { before: AgentSession },
+}
+
+// Filters and cursor for a page of audit entries (newest first). Action filters
+// (action_type through threshold_request_id) only match action requests.
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct AuditQuery {
+    pub cursor: Option<u64>,  // next_cursor of the previous page
+    pub limit: Option<u64>,   // 0 returns only the total
+    pub start: Option<u64>,   // Timestamp range (nanoseconds, inclusive)
+    pub end: Option<u64>,
+    pub requester: Option<Principal>,  // Caller of the entry
+    pub action_type: Option<String>,   // "swap" | "transfer" | "approve"
+    pub decision: Option<PolicyDecision>,
+    pub chain: Option<String>,
+    pub token: Option<String>,  // Swaps match on either side
+    pub success: Option<bool>,  // Execution outcome; unexecuted actions never match
+    pub threshold_request_id: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    pub results: Vec<AuditEntry>,  // ActionExecuted entries for the action requests in this page
+    pub next_cursor: Option<u64>,  // None on the last page
+    pub total: u64,                // Entries matching the filters across all pages
+}
+
+// Audit entries with the certificate over the log tip, for external verification
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct CertifiedAuditRange {
+    pub entries: Vec<AuditEntry>,
+    pub tip_id: Option<u64>,         // Id of the newest entry; None while the log is empty
+    pub tip_hash: Vec<u8>,           // Hash published as the canister's certified data
+    pub certificate: Option<Vec<u8>>,  // System certificate over tip_hash
 }
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
 pub struct PolicyResult {
     pub decision: PolicyDecision,
     pub matched_policy: Option<String>,
+    pub matched_policy_id: Option<u64>,
     pub reason: String,
 }
 
@@ -146,13 +450,61 @@ pub struct ExecutionResult {
     pub error: Option<String>,
 }
 
+// ============== SPEND LEDGER ==============
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct SpendEntry {
+    pub requester: Principal,
+    pub token: String,
+    pub chain: String,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct SpendSummary {
+    pub rolling_24h: u64,   // Executed in the last 24h, plus reserved
+    pub calendar_day: u64,  // Executed since 00:00 UTC, plus reserved
+    pub reserved: u64,      // Pending threshold requests and in-flight executions
+}
+
+// ============== AGENT SESSIONS ==============
+
+// Scoped credential minted by an Owner for an automated agent
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct AgentSession {
+    pub principal: Principal,
+    pub created_by: Principal,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub total_budget: u64,                  // Max amount across all actions of the session
+    pub spent: u64,                         // Charged so far (includes pending and in-flight actions)
+    pub max_per_transaction: u64,
+    pub allowed_action_types: Vec<String>,  // "swap", "transfer", "approve" (empty = any)
+    pub allowed_chains: Vec<String>,        // Empty = any
+    pub revoked: bool,
+    pub budget_token: Option<String>,       // Token the budget is counted in; None on sessions minted before, which must be re-minted
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct SessionConfig {
+    pub principal: Principal,
+    pub expires_at: u64,
+    pub total_budget: u64,
+    pub max_per_transaction: u64,
+    pub allowed_action_types: Vec<String>,
+    pub allowed_chains: Vec<String>,
+    pub budget_token: String,  // Only actions spending this token are allowed (swaps spend token_in)
+}
+
 // ============== API RESPONSES ==============
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+#[allow(clippy::large_enum_variant)]  // Candid response type, returned once per call
 pub enum ActionResult {
     Executed(ExecutionResult),
     PendingSignatures(PendingRequest),
-    Denied { reason: String },
+    Denied { error: ChainGuardError },
 }
 
 // ============== CONFIGURATION ==============